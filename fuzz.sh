@@ -36,7 +36,7 @@ ENTRYPOINT \
 EOF
 
 if [ -z "$1" ]; then
-  echo "Usage: fuzz.sh [ clean | ethernet | arp | ipv4 | ipv6 | tcp | udp | icmp | gre ]"
+  echo "Usage: fuzz.sh [ clean | ethernet | arp | ipv4 | ipv6 | tcp | udp | icmp | gre | mpls | vxlan | geneve | gtpu | ppp | erspan | esp | ah | igmp | sctp | udplite ]"
 fi
 
 ${DOCKER} run --init --rm -v "$(pwd):/usr/local/src/pdu" -e FUZZ_TARGET=$1 pdu-fuzz
@@ -0,0 +1,39 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use pdu::*;
+
+pub fn fuzz(data: &[u8]) {
+    if let Ok(ah_pdu) = AhPdu::new(data) {
+        ah_pdu.computed_ihl();
+        ah_pdu.next_header();
+        ah_pdu.payload_length();
+        ah_pdu.spi();
+        ah_pdu.sequence_number();
+        ah_pdu.icv();
+        ah_pdu.inner().ok();
+    }
+}
+
+fn main() {
+    loop {
+        honggfuzz::fuzz!(|data: &[u8]| {
+            fuzz(data);
+        });
+    }
+}
@@ -0,0 +1,49 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use pdu::*;
+
+pub fn fuzz(data: &[u8]) {
+    if let Ok(erspan_pdu) = ErspanPdu::new(data) {
+        erspan_pdu.computed_ihl();
+        erspan_pdu.version();
+        erspan_pdu.vlan();
+        erspan_pdu.cos();
+        erspan_pdu.truncated();
+        erspan_pdu.session_id();
+        erspan_pdu.index();
+        erspan_pdu.timestamp();
+        erspan_pdu.sgt();
+        erspan_pdu.frame_type();
+        erspan_pdu.hardware_id();
+        erspan_pdu.direction();
+        erspan_pdu.granularity();
+        erspan_pdu.has_platform_subheader();
+        erspan_pdu.platform_id();
+        erspan_pdu.platform_info();
+        erspan_pdu.inner().ok();
+    }
+}
+
+fn main() {
+    loop {
+        honggfuzz::fuzz!(|data: &[u8]| {
+            fuzz(data);
+        });
+    }
+}
@@ -28,6 +28,9 @@ pub fn fuzz(data: &[u8]) {
             gre_pdu.computed_checksum();
             gre_pdu.key();
             gre_pdu.sequence_number();
+            gre_pdu.payload_length();
+            gre_pdu.call_id();
+            gre_pdu.acknowledgement_number();
         }
         Err(_) => {}
     }
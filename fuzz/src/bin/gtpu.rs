@@ -0,0 +1,52 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use pdu::*;
+
+pub fn fuzz(data: &[u8]) {
+    if let Ok(gtpu_pdu) = GtpuPdu::new(data) {
+        gtpu_pdu.computed_ihl();
+        gtpu_pdu.version();
+        gtpu_pdu.protocol_type();
+        gtpu_pdu.message_type();
+        gtpu_pdu.length();
+        gtpu_pdu.teid();
+        gtpu_pdu.sequence_number();
+        gtpu_pdu.npdu_number();
+        gtpu_pdu.next_extension_header_type();
+        for extension_header in gtpu_pdu.extension_headers() {
+            match extension_header {
+                GtpuExtensionHeader::Raw { .. } => {
+                    continue;
+                }
+                GtpuExtensionHeader::PduSessionContainer { .. } => {
+                    continue;
+                }
+            }
+        }
+        gtpu_pdu.inner().ok();
+    }
+}
+
+fn main() {
+    loop {
+        honggfuzz::fuzz!(|data: &[u8]| {
+            fuzz(data);
+        });
+    }
+}
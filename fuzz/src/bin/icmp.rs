@@ -19,7 +19,10 @@
 use pdu::*;
 
 pub fn fuzz(data: &[u8]) {
-    match IcmpPdu::new(&data) {
+    if let Ok(icmp_pdu) = IcmpPdu::new(data) {
+        icmp_pdu.inner().ok();
+    }
+    match IcmpPdu::new_icmpv4(data) {
         Ok(icmp_pdu) => {
             icmp_pdu.message_type();
             icmp_pdu.message_code();
@@ -41,9 +44,61 @@ pub fn fuzz(data: &[u8]) {
                 .unwrap(),
             );
             icmp_pdu.computed_checksum(&ip);
+            icmp_pdu.is_icmpv4();
+            icmp_pdu.computed_original_ports();
+            match icmp_pdu.inner() {
+                Ok(Icmp::Icmpv4(Icmpv4::DestinationUnreachable { original: Some(original), .. }))
+                | Ok(Icmp::Icmpv4(Icmpv4::Redirect { original: Some(original), .. }))
+                | Ok(Icmp::Icmpv4(Icmpv4::TimeExceeded { original: Some(original) }))
+                | Ok(Icmp::Icmpv4(Icmpv4::ParameterProblem { original: Some(original), .. })) => {
+                    original.inner().ok();
+                }
+                _ => {}
+            }
         }
         Err(_) => {}
     }
+    if let Ok(icmp_pdu) = IcmpPdu::new_icmpv6(data) {
+        icmp_pdu.computed_original_ports();
+        if let Ok(Icmp::Icmpv6(icmpv6)) = icmp_pdu.inner() {
+            match icmpv6 {
+                Icmpv6::DestinationUnreachable { original: Some(original) }
+                | Icmpv6::PacketTooBig { original: Some(original), .. }
+                | Icmpv6::TimeExceeded { original: Some(original) }
+                | Icmpv6::ParameterProblem { original: Some(original), .. } => {
+                    original.inner().ok();
+                }
+                Icmpv6::RouterSolicitation { options }
+                | Icmpv6::RouterAdvertisement { options, .. }
+                | Icmpv6::NeighborSolicitation { options, .. }
+                | Icmpv6::NeighborAdvertisement { options, .. }
+                | Icmpv6::Redirect { options, .. } => {
+                    for option in options {
+                        match option {
+                            NdpOption::RecursiveDnsServer { addresses, .. } => {
+                                addresses.count();
+                            }
+                            NdpOption::DnsSearchList { domains, .. } => {
+                                for labels in domains {
+                                    labels.count();
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Icmpv6::MulticastListenerQueryV2 { sources, .. } => {
+                    sources.count();
+                }
+                Icmpv6::MulticastListenerReportV2 { records } => {
+                    for record in records {
+                        record.sources.count();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 fn main() {
@@ -42,6 +42,28 @@ pub fn fuzz(data: &[u8]) {
                     Ipv4Option::Raw { .. } => {
                         continue;
                     }
+                    Ipv4Option::End => {
+                        continue;
+                    }
+                    Ipv4Option::NoOp => {
+                        continue;
+                    }
+                    Ipv4Option::RecordRoute { addresses, .. }
+                    | Ipv4Option::LooseSourceRoute { addresses, .. }
+                    | Ipv4Option::StrictSourceRoute { addresses, .. } => for _ in addresses {},
+                    Ipv4Option::Timestamp { timestamps, .. } => for _ in timestamps {},
+                    Ipv4Option::RouterAlert { .. } => {
+                        continue;
+                    }
+                    Ipv4Option::Security { .. } => {
+                        continue;
+                    }
+                    Ipv4Option::Cipso { .. } => {
+                        continue;
+                    }
+                    Ipv4Option::StreamId { .. } => {
+                        continue;
+                    }
                 }
             }
         }
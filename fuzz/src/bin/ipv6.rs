@@ -35,6 +35,7 @@ pub fn fuzz(data: &[u8]) {
             ipv6_pdu.hop_limit();
             ipv6_pdu.source_address();
             ipv6_pdu.destination_address();
+            ipv6_pdu.computed_final_destination();
             for extension_header in ipv6_pdu.extension_headers() {
                 match extension_header {
                     Ipv6ExtensionHeader::Raw { .. } => {
@@ -43,6 +44,58 @@ pub fn fuzz(data: &[u8]) {
                     Ipv6ExtensionHeader::Fragment { .. } => {
                         continue;
                     }
+                    Ipv6ExtensionHeader::Ah { .. } => {
+                        continue;
+                    }
+                    Ipv6ExtensionHeader::HopByHop { options } | Ipv6ExtensionHeader::DestinationOptions { options } => {
+                        for option in options {
+                            match option {
+                                Ipv6Option::Raw { .. } => {
+                                    continue;
+                                }
+                                Ipv6Option::Pad1 => {
+                                    continue;
+                                }
+                                Ipv6Option::PadN { .. } => {
+                                    continue;
+                                }
+                                Ipv6Option::RouterAlert { .. } => {
+                                    continue;
+                                }
+                                Ipv6Option::JumboPayload { .. } => {
+                                    continue;
+                                }
+                                Ipv6Option::TunnelEncapsulationLimit { .. } => {
+                                    continue;
+                                }
+                                Ipv6Option::Ioam { .. } => {
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+                    Ipv6ExtensionHeader::Routing { .. } => {
+                        continue;
+                    }
+                    Ipv6ExtensionHeader::SegmentRouting { segments, tlvs, .. } => {
+                        for _ in segments {}
+                        for tlv in tlvs {
+                            match tlv {
+                                SrhTlv::Raw { .. } => {
+                                    continue;
+                                }
+                                SrhTlv::Pad1 => {
+                                    continue;
+                                }
+                                SrhTlv::PadN { .. } => {
+                                    continue;
+                                }
+                                SrhTlv::Hmac { .. } => {
+                                    continue;
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -0,0 +1,49 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use pdu::*;
+
+pub fn fuzz(data: &[u8]) {
+    if let Ok(sctp_pdu) = SctpPdu::new(data) {
+        sctp_pdu.source_port();
+        sctp_pdu.destination_port();
+        sctp_pdu.verification_tag();
+        sctp_pdu.checksum();
+        sctp_pdu.computed_checksum();
+        for chunk in sctp_pdu.chunks() {
+            match chunk {
+                SctpChunk::Init { parameters, .. } | SctpChunk::InitAck { parameters, .. } => {
+                    parameters.count();
+                }
+                SctpChunk::Sack { gap_ack_blocks, duplicate_tsns, .. } => {
+                    gap_ack_blocks.count();
+                    duplicate_tsns.count();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn main() {
+    loop {
+        honggfuzz::fuzz!(|data: &[u8]| {
+            fuzz(data);
+        });
+    }
+}
@@ -80,6 +80,24 @@ pub fn fuzz(data: &[u8]) {
                     TcpOption::Timestamp { .. } => {
                         continue;
                     }
+                    TcpOption::Md5Signature { .. } => {
+                        continue;
+                    }
+                    TcpOption::UserTimeout { .. } => {
+                        continue;
+                    }
+                    TcpOption::Authentication { .. } => {
+                        continue;
+                    }
+                    TcpOption::Mptcp(_) => {
+                        continue;
+                    }
+                    TcpOption::FastOpen { .. } => {
+                        continue;
+                    }
+                    TcpOption::FastOpenExperimental { .. } => {
+                        continue;
+                    }
                 }
             }
         }
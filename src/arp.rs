@@ -112,3 +112,89 @@ impl<'a> ArpPdu<'a> {
         target_protocol_address
     }
 }
+
+/// Represents a mutable ARP payload
+#[derive(Debug)]
+pub struct ArpPduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+impl<'a> ArpPduMut<'a> {
+    /// Constructs an [`ArpPduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        ArpPdu::new(buffer)?;
+        Ok(ArpPduMut { buffer })
+    }
+
+    /// Returns a read-only [`ArpPdu`] view of this PDU
+    pub fn as_pdu(&self) -> ArpPdu<'_> {
+        ArpPdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`ArpPdu`] view of this PDU
+    pub fn into_pdu(self) -> ArpPdu<'a> {
+        ArpPdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    pub fn set_hardware_type(&mut self, hardware_type: u16) {
+        self.buffer[0..=1].copy_from_slice(&hardware_type.to_be_bytes());
+    }
+
+    pub fn set_protocol_type(&mut self, protocol_type: u16) {
+        self.buffer[2..=3].copy_from_slice(&protocol_type.to_be_bytes());
+    }
+
+    pub fn set_hardware_length(&mut self, hardware_length: u8) -> Result<()> {
+        if hardware_length != 6 {
+            // we only support 6-octet hardware addresses
+            return Err(Error::Malformed);
+        }
+        self.buffer[4] = hardware_length;
+        Ok(())
+    }
+
+    pub fn set_protocol_length(&mut self, protocol_length: u8) -> Result<()> {
+        if protocol_length != 4 {
+            // we only support 4-octet protocol addresses
+            return Err(Error::Malformed);
+        }
+        self.buffer[5] = protocol_length;
+        Ok(())
+    }
+
+    pub fn set_opcode(&mut self, opcode: u16) {
+        self.buffer[6..=7].copy_from_slice(&opcode.to_be_bytes());
+    }
+
+    pub fn set_sender_hardware_address(&mut self, sender_hardware_address: [u8; 6]) {
+        self.buffer[8..14].copy_from_slice(&sender_hardware_address);
+    }
+
+    pub fn set_sender_protocol_address(&mut self, sender_protocol_address: [u8; 4]) {
+        self.buffer[14..18].copy_from_slice(&sender_protocol_address);
+    }
+
+    pub fn set_target_hardware_address(&mut self, target_hardware_address: [u8; 6]) {
+        self.buffer[18..24].copy_from_slice(&target_hardware_address);
+    }
+
+    pub fn set_target_protocol_address(&mut self, target_protocol_address: [u8; 4]) {
+        self.buffer[24..28].copy_from_slice(&target_protocol_address);
+    }
+}
@@ -0,0 +1,194 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use crate::{
+    AhBuilder, Error, EtherType, EthernetBuilder, IcmpBuilder, Ip, IpProto, Ipv4Builder, Ipv6Builder, Result,
+    TcpBuilder, UdpBuilder,
+};
+
+/// Builds a complete Ethernet/IP/L4 packet from a stack of per-layer builders
+///
+/// Each layer is optional. EtherTypes, IP protocol numbers, lengths, and checksums are filled in automatically based on
+/// the layers that are present.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PacketBuilder<'a> {
+    ethernet: Option<EthernetBuilder>,
+    ip: Option<IpBuilder<'a>>,
+    ah: Option<AhBuilder<'a>>,
+    transport: Option<TransportBuilder<'a>>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum IpBuilder<'a> {
+    Ipv4(Ipv4Builder<'a>),
+    Ipv6(Ipv6Builder<'a>),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum TransportBuilder<'a> {
+    Tcp(TcpBuilder<'a>),
+    Udp(UdpBuilder),
+    Icmp(IcmpBuilder),
+}
+
+impl<'a> PacketBuilder<'a> {
+    /// Constructs an empty [`PacketBuilder`]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn ethernet(mut self, ethernet: EthernetBuilder) -> Self {
+        self.ethernet = Some(ethernet);
+        self
+    }
+
+    pub fn ipv4(mut self, ipv4: Ipv4Builder<'a>) -> Self {
+        self.ip = Some(IpBuilder::Ipv4(ipv4));
+        self
+    }
+
+    pub fn ipv6(mut self, ipv6: Ipv6Builder<'a>) -> Self {
+        self.ip = Some(IpBuilder::Ipv6(ipv6));
+        self
+    }
+
+    /// Inserts an authentication header between the IP header and the transport layer
+    pub fn ah(mut self, ah: AhBuilder<'a>) -> Self {
+        self.ah = Some(ah);
+        self
+    }
+
+    pub fn tcp(mut self, tcp: TcpBuilder<'a>) -> Self {
+        self.transport = Some(TransportBuilder::Tcp(tcp));
+        self
+    }
+
+    pub fn udp(mut self, udp: UdpBuilder) -> Self {
+        self.transport = Some(TransportBuilder::Udp(udp));
+        self
+    }
+
+    pub fn icmp(mut self, icmp: IcmpBuilder) -> Self {
+        self.transport = Some(TransportBuilder::Icmp(icmp));
+        self
+    }
+
+    /// Returns the number of bytes that [`PacketBuilder::build`] will write for a payload of `payload_length` bytes
+    pub fn computed_length(&self, payload_length: usize) -> usize {
+        let ethernet_length = self.ethernet.map(|ethernet| ethernet.computed_ihl()).unwrap_or(0);
+        let ip_length = match self.ip {
+            Some(IpBuilder::Ipv4(ipv4)) => ipv4.computed_ihl(),
+            Some(IpBuilder::Ipv6(ipv6)) => ipv6.computed_ihl(),
+            None => 0,
+        };
+        let ah_length = self.ah.map(|ah| ah.computed_ihl()).unwrap_or(0);
+        let transport_length = match self.transport {
+            Some(TransportBuilder::Tcp(tcp)) => tcp.computed_data_offset(),
+            Some(TransportBuilder::Udp(udp)) => udp.computed_data_offset(),
+            Some(TransportBuilder::Icmp(icmp)) => icmp.computed_data_offset(),
+            None => 0,
+        };
+        ethernet_length + ip_length + ah_length + transport_length + payload_length
+    }
+
+    /// Serializes every layer followed by `payload` into `buffer`, returning the number of bytes written
+    pub fn build(&self, buffer: &mut [u8], payload: &[u8]) -> Result<usize> {
+        let length = self.computed_length(payload.len());
+        if buffer.len() < length {
+            return Err(Error::Truncated);
+        }
+        let buffer = &mut buffer[..length];
+        buffer[length - payload.len()..].copy_from_slice(payload);
+
+        let ethernet_length = self.ethernet.map(|ethernet| ethernet.computed_ihl()).unwrap_or(0);
+        let (ethernet_buffer, buffer) = buffer.split_at_mut(ethernet_length);
+
+        let (ethertype, ip_length) = match self.ip {
+            Some(IpBuilder::Ipv4(ipv4)) => (EtherType::IPV4, ipv4.computed_ihl()),
+            Some(IpBuilder::Ipv6(ipv6)) => (EtherType::IPV6, ipv6.computed_ihl()),
+            None => (0, 0),
+        };
+        let ah_length = self.ah.map(|ah| ah.computed_ihl()).unwrap_or(0);
+        let (ip_buffer, transport_buffer) = buffer.split_at_mut(ip_length + ah_length);
+
+        let mut protocol = match (self.ip, self.transport) {
+            (_, Some(TransportBuilder::Tcp(_))) => Some(IpProto::TCP),
+            (_, Some(TransportBuilder::Udp(_))) => Some(IpProto::UDP),
+            (Some(IpBuilder::Ipv6(_)), Some(TransportBuilder::Icmp(_))) => Some(IpProto::ICMP6),
+            (_, Some(TransportBuilder::Icmp(_))) => Some(IpProto::ICMP),
+            (_, None) => None,
+        };
+        // an authentication header sits between the IP header and the transport layer, and carries its protocol
+        let mut ah = self.ah;
+        if let Some(builder) = ah {
+            if let Some(protocol) = protocol {
+                ah = Some(builder.next_header(protocol));
+            }
+            protocol = Some(IpProto::AH);
+        }
+
+        let (header_buffer, ah_buffer) = ip_buffer.split_at_mut(ip_length);
+        let payload_length = ah_buffer.len() + transport_buffer.len();
+        match self.ip {
+            Some(IpBuilder::Ipv4(mut ipv4)) => {
+                if let Some(protocol) = protocol {
+                    ipv4 = ipv4.protocol(protocol);
+                }
+                ipv4.write_header(header_buffer, payload_length)?;
+            }
+            Some(IpBuilder::Ipv6(mut ipv6)) => {
+                if let Some(protocol) = protocol {
+                    ipv6 = ipv6.next_header(protocol);
+                }
+                ipv6.write_header(header_buffer, payload_length)?;
+            }
+            None => {}
+        }
+        if let Some(ah) = ah {
+            match self.ip {
+                // over ipv6, the header must be a multiple of 64 bits long (rfc4302)
+                Some(IpBuilder::Ipv6(_)) if ah_length % 8 != 0 => return Err(Error::Malformed),
+                None => return Err(Error::Malformed),
+                _ => {}
+            }
+            ah.write_header(ah_buffer)?;
+        }
+
+        if let Some(transport) = self.transport {
+            if self.ip.is_none() {
+                // transport checksums require an IP pseudo-header
+                return Err(Error::Malformed);
+            }
+            let ip = Ip::new(ip_buffer)?;
+            match transport {
+                TransportBuilder::Tcp(tcp) => tcp.write(transport_buffer, &ip)?,
+                TransportBuilder::Udp(udp) => udp.write(transport_buffer, &ip)?,
+                TransportBuilder::Icmp(icmp) => icmp.write(transport_buffer, &ip)?,
+            }
+        }
+
+        if let Some(mut ethernet) = self.ethernet {
+            if self.ip.is_some() {
+                ethernet = ethernet.ethertype(ethertype);
+            }
+            ethernet.write_header(ethernet_buffer)?;
+        }
+
+        Ok(length)
+    }
+}
@@ -0,0 +1,277 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use crate::{
+    Ah, AhPdu, Erspan, ErspanPdu, Ethernet, EthernetPdu, Geneve, GenevePdu, Gre, GrePdu, Gtpu, GtpuPdu, IcmpPdu,
+    IgmpPdu, Ip, Ipv4, Ipv4Pdu, Ipv6, Ipv6Pdu, Mpls, MplsPdu, Ppp, PppPdu, SctpPdu, TcpPdu, Udp, UdpLitePdu, UdpPdu,
+    Vxlan, VxlanPdu,
+};
+
+/// Describes the outcome of verifying the checksum of a PDU
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ChecksumStatus {
+    /// The checksum matches the computed checksum
+    Good,
+    /// The checksum does not match the computed checksum
+    Bad,
+    /// The PDU has no checksum, or its checksum cannot be verified (e.g. because the datagram is fragmented)
+    NotChecked,
+}
+
+impl ChecksumStatus {
+    pub(crate) fn from_checksums(checksum: u16, computed_checksum: u16) -> Self {
+        // 0x0000 and 0xFFFF are both representations of zero in one's complement arithmetic
+        if checksum == computed_checksum
+            || (checksum | computed_checksum == 0xFFFF && checksum & computed_checksum == 0)
+        {
+            ChecksumStatus::Good
+        } else {
+            ChecksumStatus::Bad
+        }
+    }
+}
+
+/// Identifies the protocol layer that a [`ChecksumStatus`] was reported for
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Layer {
+    Ethernet,
+    Arp,
+    Ipv4,
+    Ipv6,
+    Mpls,
+    Tcp,
+    Udp,
+    Icmp,
+    Gre,
+    Vxlan,
+    Geneve,
+    Gtpu,
+    Ppp,
+    Erspan,
+    Esp,
+    Ah,
+    Igmp,
+    Sctp,
+    UdpLite,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Pdu<'a> {
+    Ethernet(EthernetPdu<'a>),
+    Arp,
+    Ipv4(Ipv4Pdu<'a>),
+    Ipv6(Ipv6Pdu<'a>),
+    Mpls(MplsPdu<'a>),
+    Tcp(TcpPdu<'a>),
+    Udp(UdpPdu<'a>),
+    Icmp(IcmpPdu<'a>),
+    Gre(GrePdu<'a>),
+    Vxlan(VxlanPdu<'a>),
+    Geneve(GenevePdu<'a>),
+    Gtpu(GtpuPdu<'a>),
+    Ppp(PppPdu<'a>),
+    Erspan(ErspanPdu<'a>),
+    Esp,
+    Ah(AhPdu<'a>),
+    Igmp(IgmpPdu<'a>),
+    Sctp(SctpPdu<'a>),
+    UdpLite(UdpLitePdu<'a>),
+}
+
+/// Walks the layers of a PDU via their `inner()` payloads, yielding the [`ChecksumStatus`] of each layer
+///
+/// Iteration stops at the first layer whose payload is `Raw` or cannot be parsed.
+#[derive(Debug, Copy, Clone)]
+pub struct ChecksumIterator<'a> {
+    next: Option<Pdu<'a>>,
+    ip: Option<Ip<'a>>,
+    fragmented: bool,
+}
+
+impl<'a> ChecksumIterator<'a> {
+    pub(crate) fn from_ethernet(pdu: EthernetPdu<'a>) -> Self {
+        ChecksumIterator { next: Some(Pdu::Ethernet(pdu)), ip: None, fragmented: false }
+    }
+
+    pub(crate) fn from_ip(pdu: Ip<'a>) -> Self {
+        match pdu {
+            Ip::Ipv4(ipv4) => ChecksumIterator { next: Some(Pdu::Ipv4(ipv4)), ip: None, fragmented: false },
+            Ip::Ipv6(ipv6) => ChecksumIterator { next: Some(Pdu::Ipv6(ipv6)), ip: None, fragmented: false },
+        }
+    }
+
+    fn verify_transport<F>(&self, verify: F) -> ChecksumStatus
+    where
+        F: FnOnce(&Ip<'a>) -> ChecksumStatus,
+    {
+        match self.ip {
+            // the checksum of a fragmented datagram covers bytes that are not present in this fragment
+            Some(ip) if !self.fragmented => verify(&ip),
+            _ => ChecksumStatus::NotChecked,
+        }
+    }
+}
+
+impl<'a> Iterator for ChecksumIterator<'a> {
+    type Item = (Layer, ChecksumStatus);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (item, next) = match self.next.take()? {
+            Pdu::Ethernet(ethernet) => (
+                (Layer::Ethernet, ChecksumStatus::NotChecked),
+                match ethernet.into_inner() {
+                    Ok(Ethernet::Arp(_)) => Some(Pdu::Arp),
+                    Ok(Ethernet::Ipv4(ipv4)) => Some(Pdu::Ipv4(ipv4)),
+                    Ok(Ethernet::Ipv6(ipv6)) => Some(Pdu::Ipv6(ipv6)),
+                    Ok(Ethernet::Mpls(mpls)) => Some(Pdu::Mpls(mpls)),
+                    _ => None,
+                },
+            ),
+            Pdu::Arp => ((Layer::Arp, ChecksumStatus::NotChecked), None),
+            Pdu::Ipv4(ipv4) => {
+                self.ip = Some(Ip::Ipv4(ipv4));
+                self.fragmented |= ipv4.more_fragments() || ipv4.fragment_offset() > 0;
+                (
+                    (Layer::Ipv4, ipv4.verify_checksum()),
+                    match ipv4.into_inner() {
+                        Ok(Ipv4::Tcp(tcp)) => Some(Pdu::Tcp(tcp)),
+                        Ok(Ipv4::Udp(udp)) => Some(Pdu::Udp(udp)),
+                        Ok(Ipv4::Icmp(icmp)) => Some(Pdu::Icmp(icmp)),
+                        Ok(Ipv4::Gre(gre)) => Some(Pdu::Gre(gre)),
+                        Ok(Ipv4::Esp(_)) => Some(Pdu::Esp),
+                        Ok(Ipv4::Ah(ah)) => Some(Pdu::Ah(ah)),
+                        Ok(Ipv4::Igmp(igmp)) => Some(Pdu::Igmp(igmp)),
+                        Ok(Ipv4::Sctp(sctp)) => Some(Pdu::Sctp(sctp)),
+                        Ok(Ipv4::UdpLite(udplite)) => Some(Pdu::UdpLite(udplite)),
+                        _ => None,
+                    },
+                )
+            }
+            Pdu::Ipv6(ipv6) => {
+                self.ip = Some(Ip::Ipv6(ipv6));
+                self.fragmented |=
+                    ipv6.computed_more_fragments() == Some(true) || ipv6.computed_fragment_offset() > Some(0);
+                (
+                    (Layer::Ipv6, ChecksumStatus::NotChecked),
+                    match ipv6.into_inner() {
+                        Ok(Ipv6::Tcp(tcp)) => Some(Pdu::Tcp(tcp)),
+                        Ok(Ipv6::Udp(udp)) => Some(Pdu::Udp(udp)),
+                        Ok(Ipv6::Icmp(icmp)) => Some(Pdu::Icmp(icmp)),
+                        Ok(Ipv6::Gre(gre)) => Some(Pdu::Gre(gre)),
+                        Ok(Ipv6::Esp(_)) => Some(Pdu::Esp),
+                        Ok(Ipv6::Sctp(sctp)) => Some(Pdu::Sctp(sctp)),
+                        Ok(Ipv6::UdpLite(udplite)) => Some(Pdu::UdpLite(udplite)),
+                        _ => None,
+                    },
+                )
+            }
+            Pdu::Mpls(mpls) => (
+                (Layer::Mpls, ChecksumStatus::NotChecked),
+                match mpls.into_inner() {
+                    Ok(Mpls::Ethernet(ethernet)) => Some(Pdu::Ethernet(ethernet)),
+                    Ok(Mpls::Ipv4(ipv4)) => Some(Pdu::Ipv4(ipv4)),
+                    Ok(Mpls::Ipv6(ipv6)) => Some(Pdu::Ipv6(ipv6)),
+                    _ => None,
+                },
+            ),
+            Pdu::Tcp(tcp) => ((Layer::Tcp, self.verify_transport(|ip| tcp.verify_checksum(ip))), None),
+            Pdu::UdpLite(udplite) => ((Layer::UdpLite, self.verify_transport(|ip| udplite.verify_checksum(ip))), None),
+            Pdu::Udp(udp) => (
+                (Layer::Udp, self.verify_transport(|ip| udp.verify_checksum(ip))),
+                match udp.into_inner() {
+                    Ok(Udp::Vxlan(vxlan)) => Some(Pdu::Vxlan(vxlan)),
+                    Ok(Udp::Geneve(geneve)) => Some(Pdu::Geneve(geneve)),
+                    Ok(Udp::Gtpu(gtpu)) => Some(Pdu::Gtpu(gtpu)),
+                    _ => None,
+                },
+            ),
+            Pdu::Icmp(icmp) => ((Layer::Icmp, self.verify_transport(|ip| icmp.verify_checksum(ip))), None),
+            Pdu::Gre(gre) => (
+                (Layer::Gre, if self.fragmented { ChecksumStatus::NotChecked } else { gre.verify_checksum() }),
+                match gre.into_inner() {
+                    Ok(Gre::Ethernet(ethernet)) => Some(Pdu::Ethernet(ethernet)),
+                    Ok(Gre::Ipv4(ipv4)) => Some(Pdu::Ipv4(ipv4)),
+                    Ok(Gre::Ipv6(ipv6)) => Some(Pdu::Ipv6(ipv6)),
+                    Ok(Gre::Mpls(mpls)) => Some(Pdu::Mpls(mpls)),
+                    Ok(Gre::Ppp(ppp)) => Some(Pdu::Ppp(ppp)),
+                    Ok(Gre::Erspan(erspan)) => Some(Pdu::Erspan(erspan)),
+                    _ => None,
+                },
+            ),
+            Pdu::Vxlan(vxlan) => (
+                (Layer::Vxlan, ChecksumStatus::NotChecked),
+                match vxlan.into_inner() {
+                    Ok(Vxlan::Ethernet(ethernet)) => Some(Pdu::Ethernet(ethernet)),
+                    _ => None,
+                },
+            ),
+            Pdu::Geneve(geneve) => (
+                (Layer::Geneve, ChecksumStatus::NotChecked),
+                match geneve.into_inner() {
+                    Ok(Geneve::Ethernet(ethernet)) => Some(Pdu::Ethernet(ethernet)),
+                    Ok(Geneve::Ipv4(ipv4)) => Some(Pdu::Ipv4(ipv4)),
+                    Ok(Geneve::Ipv6(ipv6)) => Some(Pdu::Ipv6(ipv6)),
+                    _ => None,
+                },
+            ),
+            Pdu::Gtpu(gtpu) => (
+                (Layer::Gtpu, ChecksumStatus::NotChecked),
+                match gtpu.into_inner() {
+                    Ok(Gtpu::Ipv4(ipv4)) => Some(Pdu::Ipv4(ipv4)),
+                    Ok(Gtpu::Ipv6(ipv6)) => Some(Pdu::Ipv6(ipv6)),
+                    _ => None,
+                },
+            ),
+            Pdu::Ppp(ppp) => (
+                (Layer::Ppp, ChecksumStatus::NotChecked),
+                match ppp.into_inner() {
+                    Ok(Ppp::Ipv4(ipv4)) => Some(Pdu::Ipv4(ipv4)),
+                    Ok(Ppp::Ipv6(ipv6)) => Some(Pdu::Ipv6(ipv6)),
+                    _ => None,
+                },
+            ),
+            Pdu::Erspan(erspan) => (
+                (Layer::Erspan, ChecksumStatus::NotChecked),
+                match erspan.into_inner() {
+                    Ok(Erspan::Ethernet(ethernet)) => Some(Pdu::Ethernet(ethernet)),
+                    _ => None,
+                },
+            ),
+            Pdu::Esp => ((Layer::Esp, ChecksumStatus::NotChecked), None),
+            Pdu::Igmp(igmp) => {
+                ((Layer::Igmp, if self.fragmented { ChecksumStatus::NotChecked } else { igmp.verify_checksum() }), None)
+            }
+            Pdu::Sctp(sctp) => {
+                ((Layer::Sctp, if self.fragmented { ChecksumStatus::NotChecked } else { sctp.verify_checksum() }), None)
+            }
+            Pdu::Ah(ah) => (
+                (Layer::Ah, ChecksumStatus::NotChecked),
+                match ah.into_inner() {
+                    Ok(Ah::Tcp(tcp)) => Some(Pdu::Tcp(tcp)),
+                    Ok(Ah::Udp(udp)) => Some(Pdu::Udp(udp)),
+                    Ok(Ah::Icmp(icmp)) => Some(Pdu::Icmp(icmp)),
+                    Ok(Ah::Gre(gre)) => Some(Pdu::Gre(gre)),
+                    Ok(Ah::Esp(_)) => Some(Pdu::Esp),
+                    _ => None,
+                },
+            ),
+        };
+        self.next = next;
+        Some(item)
+    }
+}
@@ -0,0 +1,189 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{Error, Result};
+
+/// Represents an ERSPAN type II or type III header and payload
+///
+/// ERSPAN type I frames have no header, so they are represented as [`Gre::Ethernet`](crate::Gre::Ethernet).
+#[derive(Debug, Copy, Clone)]
+pub struct ErspanPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of an [`ErspanPdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Erspan<'a> {
+    Raw(&'a [u8]),
+    Ethernet(super::EthernetPdu<'a>),
+}
+
+impl<'a> ErspanPdu<'a> {
+    /// Constructs an [`ErspanPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        let pdu = ErspanPdu { buffer };
+        match pdu.version() {
+            1 => {}
+            2 => {
+                if buffer.len() < 12 || buffer.len() < pdu.computed_ihl() {
+                    return Err(Error::Truncated);
+                }
+            }
+            // we only support ERSPAN type II and type III frames
+            _ => return Err(Error::Malformed),
+        }
+        Ok(pdu)
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..self.computed_ihl()]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Erspan<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Erspan<'a>> {
+        let rest = &self.buffer[self.computed_ihl()..];
+        if rest.is_empty() || self.frame_type().unwrap_or_default() != 0 {
+            return Ok(Erspan::Raw(rest));
+        }
+        Ok(Erspan::Ethernet(super::EthernetPdu::new(rest)?))
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        match self.version() {
+            2 if self.has_platform_subheader() => 20,
+            2 => 12,
+            _ => 8,
+        }
+    }
+
+    pub fn version(&'a self) -> u8 {
+        self.buffer[0] >> 4
+    }
+
+    pub fn vlan(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[0..=1].try_into().unwrap()) & 0x0FFF
+    }
+
+    pub fn cos(&'a self) -> u8 {
+        self.buffer[2] >> 5
+    }
+
+    pub fn truncated(&'a self) -> bool {
+        (self.buffer[2] & 0x04) != 0
+    }
+
+    pub fn session_id(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[2..=3].try_into().unwrap()) & 0x03FF
+    }
+
+    pub fn index(&'a self) -> Option<u32> {
+        match self.version() {
+            1 => Some(u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap()) & 0x000F_FFFF),
+            _ => None,
+        }
+    }
+
+    pub fn timestamp(&'a self) -> Option<u32> {
+        match self.version() {
+            2 => Some(u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap())),
+            _ => None,
+        }
+    }
+
+    pub fn sgt(&'a self) -> Option<u16> {
+        match self.version() {
+            2 => Some(u16::from_be_bytes(self.buffer[8..=9].try_into().unwrap())),
+            _ => None,
+        }
+    }
+
+    pub fn frame_type(&'a self) -> Option<u8> {
+        match self.version() {
+            2 => Some((self.buffer[10] >> 2) & 0x1F),
+            _ => None,
+        }
+    }
+
+    pub fn hardware_id(&'a self) -> Option<u8> {
+        match self.version() {
+            2 => Some((u16::from_be_bytes(self.buffer[10..=11].try_into().unwrap()) >> 4) as u8 & 0x3F),
+            _ => None,
+        }
+    }
+
+    pub fn direction(&'a self) -> Option<bool> {
+        match self.version() {
+            2 => Some((self.buffer[11] & 0x08) != 0),
+            _ => None,
+        }
+    }
+
+    pub fn granularity(&'a self) -> Option<u8> {
+        match self.version() {
+            2 => Some((self.buffer[11] >> 1) & 0x03),
+            _ => None,
+        }
+    }
+
+    pub fn has_platform_subheader(&'a self) -> bool {
+        self.version() == 2 && (self.buffer[11] & 0x01) != 0
+    }
+
+    pub fn platform_id(&'a self) -> Option<u8> {
+        if self.has_platform_subheader() {
+            Some(self.buffer[12] >> 2)
+        } else {
+            None
+        }
+    }
+
+    pub fn platform_info(&'a self) -> Option<u64> {
+        if self.has_platform_subheader() {
+            Some(u64::from_be_bytes(self.buffer[12..=19].try_into().unwrap()) & 0x03FF_FFFF_FFFF_FFFF)
+        } else {
+            None
+        }
+    }
+}
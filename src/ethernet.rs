@@ -27,6 +27,13 @@ pub mod EtherType {
     pub const IPV4: u16 = 0x0800;
     pub const IPV6: u16 = 0x86DD;
     pub const DOT1Q: u16 = 0x8100;
+    pub const DOT1AD: u16 = 0x88A8;
+    pub const QINQ: u16 = 0x9100;
+    pub const MPLS: u16 = 0x8847;
+    pub const MPLS_MULTICAST: u16 = 0x8848;
+    pub const PPP: u16 = 0x880B;
+    pub const ERSPAN_TYPE_II: u16 = 0x88BE;
+    pub const ERSPAN_TYPE_III: u16 = 0x22EB;
     pub const TEB: u16 = 0x6558;
 }
 
@@ -43,6 +50,7 @@ pub enum Ethernet<'a> {
     Arp(super::ArpPdu<'a>),
     Ipv4(super::Ipv4Pdu<'a>),
     Ipv6(super::Ipv6Pdu<'a>),
+    Mpls(super::MplsPdu<'a>),
 }
 
 impl<'a> EthernetPdu<'a> {
@@ -51,10 +59,14 @@ impl<'a> EthernetPdu<'a> {
         if buffer.len() < 14 {
             return Err(Error::Truncated);
         }
-        let pdu = EthernetPdu { buffer };
-        if pdu.tpid() == EtherType::DOT1Q && buffer.len() < 18 {
-            return Err(Error::Truncated);
+        let mut ihl = 14;
+        while is_vlan_tpid(u16::from_be_bytes(buffer[ihl - 2..ihl].try_into().unwrap())) {
+            ihl += 4;
+            if buffer.len() < ihl {
+                return Err(Error::Truncated);
+            }
         }
+        let pdu = EthernetPdu { buffer };
         if pdu.ethertype() < 0x0600 {
             // we don't support 802.3 (LLC) frames
             return Err(Error::Malformed);
@@ -95,15 +107,18 @@ impl<'a> EthernetPdu<'a> {
             EtherType::ARP => Ethernet::Arp(super::ArpPdu::new(rest)?),
             EtherType::IPV4 => Ethernet::Ipv4(super::Ipv4Pdu::new(rest)?),
             EtherType::IPV6 => Ethernet::Ipv6(super::Ipv6Pdu::new(rest)?),
+            EtherType::MPLS | EtherType::MPLS_MULTICAST => Ethernet::Mpls(super::MplsPdu::new(rest)?),
             _ => Ethernet::Raw(rest),
         })
     }
 
+    /// Returns an iterator over the [`ChecksumStatus`](crate::ChecksumStatus) of each inner payload of this PDU
+    pub fn verify_checksums(&self) -> crate::ChecksumIterator<'a> {
+        crate::ChecksumIterator::from_ethernet(*self)
+    }
+
     pub fn computed_ihl(&'a self) -> usize {
-        match self.tpid() {
-            EtherType::DOT1Q => 18,
-            _ => 14,
-        }
+        14 + self.vlan_tags().count() * 4
     }
 
     pub fn source_address(&'a self) -> [u8; 6] {
@@ -123,30 +138,274 @@ impl<'a> EthernetPdu<'a> {
     }
 
     pub fn ethertype(&'a self) -> u16 {
-        match self.tpid() {
-            EtherType::DOT1Q => u16::from_be_bytes(self.buffer[16..=17].try_into().unwrap()),
-            ethertype => ethertype,
-        }
+        let ihl = self.computed_ihl();
+        u16::from_be_bytes(self.buffer[ihl - 2..ihl].try_into().unwrap())
+    }
+
+    /// Returns an iterator over the stack of VLAN tags in this header, starting with the outermost tag
+    pub fn vlan_tags(&'a self) -> VlanTagIterator<'a> {
+        VlanTagIterator { buffer: self.buffer, pos: 12 }
     }
 
     pub fn vlan(&'a self) -> Option<u16> {
-        match self.tpid() {
-            EtherType::DOT1Q => Some(u16::from_be_bytes(self.buffer[14..=15].try_into().unwrap()) & 0x0FFF),
-            _ => None,
-        }
+        self.vlan_tags().next().map(|tag| tag.vid)
     }
 
     pub fn vlan_pcp(&'a self) -> Option<u8> {
-        match self.tpid() {
-            EtherType::DOT1Q => Some((self.buffer[14] & 0xE0) >> 5),
-            _ => None,
-        }
+        self.vlan_tags().next().map(|tag| tag.pcp)
     }
 
     pub fn vlan_dei(&'a self) -> Option<bool> {
-        match self.tpid() {
-            EtherType::DOT1Q => Some(((self.buffer[14] & 0x10) >> 4) > 0),
-            _ => None,
+        self.vlan_tags().next().map(|tag| tag.dei)
+    }
+}
+
+fn is_vlan_tpid(tpid: u16) -> bool {
+    matches!(tpid, EtherType::DOT1Q | EtherType::DOT1AD | EtherType::QINQ)
+}
+
+/// Represents an 802.1Q or 802.1ad VLAN tag
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VlanTag {
+    pub tpid: u16,
+    pub pcp: u8,
+    pub dei: bool,
+    pub vid: u16,
+}
+
+/// Iterator over the stack of VLAN tags in an Ethernet header
+#[derive(Debug, Copy, Clone)]
+pub struct VlanTagIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for VlanTagIterator<'a> {
+    type Item = VlanTag;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.len() < self.pos + 6 {
+            return None;
+        }
+        let tpid = u16::from_be_bytes(self.buffer[self.pos..self.pos + 2].try_into().unwrap());
+        if !is_vlan_tpid(tpid) {
+            return None;
+        }
+        let tci = u16::from_be_bytes(self.buffer[self.pos + 2..self.pos + 4].try_into().unwrap());
+        self.pos += 4;
+        Some(VlanTag { tpid, pcp: (tci >> 13) as u8, dei: (tci & 0x1000) > 0, vid: tci & 0x0FFF })
+    }
+}
+
+/// Represents a mutable Ethernet header and payload
+#[derive(Debug)]
+pub struct EthernetPduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+/// Contains the mutable inner payload of an [`EthernetPduMut`]
+#[derive(Debug)]
+pub enum EthernetMut<'a> {
+    Raw(&'a mut [u8]),
+    Arp(super::ArpPduMut<'a>),
+    Ipv4(super::Ipv4PduMut<'a>),
+    Ipv6(super::Ipv6PduMut<'a>),
+}
+
+impl<'a> EthernetPduMut<'a> {
+    /// Constructs an [`EthernetPduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        EthernetPdu::new(buffer)?;
+        Ok(EthernetPduMut { buffer })
+    }
+
+    /// Returns a read-only [`EthernetPdu`] view of this PDU
+    pub fn as_pdu(&self) -> EthernetPdu<'_> {
+        EthernetPdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`EthernetPdu`] view of this PDU
+    pub fn into_pdu(self) -> EthernetPdu<'a> {
+        EthernetPdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<EthernetMut<'_>> {
+        EthernetPduMut { buffer: self.buffer }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<EthernetMut<'a>> {
+        let (ihl, ethertype) = {
+            let pdu = self.as_pdu();
+            (pdu.computed_ihl(), pdu.ethertype())
+        };
+        let rest = &mut self.buffer[ihl..];
+        Ok(match ethertype {
+            EtherType::ARP => EthernetMut::Arp(super::ArpPduMut::new(rest)?),
+            EtherType::IPV4 => EthernetMut::Ipv4(super::Ipv4PduMut::new(rest)?),
+            EtherType::IPV6 => EthernetMut::Ipv6(super::Ipv6PduMut::new(rest)?),
+            _ => EthernetMut::Raw(rest),
+        })
+    }
+
+    pub fn set_source_address(&mut self, source_address: [u8; 6]) {
+        self.buffer[6..12].copy_from_slice(&source_address);
+    }
+
+    pub fn set_destination_address(&mut self, destination_address: [u8; 6]) {
+        self.buffer[0..6].copy_from_slice(&destination_address);
+    }
+
+    pub fn set_tpid(&mut self, tpid: u16) -> Result<()> {
+        let previous = self.as_pdu().tpid();
+        self.buffer[12..=13].copy_from_slice(&tpid.to_be_bytes());
+        if let Err(e) = EthernetPdu::new(self.buffer) {
+            self.buffer[12..=13].copy_from_slice(&previous.to_be_bytes());
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn set_ethertype(&mut self, ethertype: u16) -> Result<()> {
+        if ethertype < 0x0600 {
+            // we don't support 802.3 (LLC) frames
+            return Err(Error::Malformed);
+        }
+        let offset = self.as_pdu().computed_ihl() - 2;
+        self.buffer[offset..offset + 2].copy_from_slice(&ethertype.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn set_vlan(&mut self, vlan: u16) -> Result<()> {
+        match self.as_pdu().tpid() {
+            tpid if is_vlan_tpid(tpid) => {
+                self.buffer[14] = (self.buffer[14] & 0xF0) | ((vlan >> 8) as u8 & 0x0F);
+                self.buffer[15] = vlan as u8;
+                Ok(())
+            }
+            _ => Err(Error::Malformed),
+        }
+    }
+
+    pub fn set_vlan_pcp(&mut self, vlan_pcp: u8) -> Result<()> {
+        match self.as_pdu().tpid() {
+            tpid if is_vlan_tpid(tpid) => {
+                self.buffer[14] = (self.buffer[14] & 0x1F) | ((vlan_pcp & 0x07) << 5);
+                Ok(())
+            }
+            _ => Err(Error::Malformed),
+        }
+    }
+
+    pub fn set_vlan_dei(&mut self, vlan_dei: bool) -> Result<()> {
+        match self.as_pdu().tpid() {
+            tpid if is_vlan_tpid(tpid) => {
+                self.buffer[14] = (self.buffer[14] & 0xEF) | ((vlan_dei as u8) << 4);
+                Ok(())
+            }
+            _ => Err(Error::Malformed),
+        }
+    }
+}
+
+/// Builds an Ethernet header in front of a payload
+#[derive(Debug, Copy, Clone, Default)]
+pub struct EthernetBuilder {
+    destination_address: [u8; 6],
+    source_address: [u8; 6],
+    vlan: Option<u16>,
+    vlan_pcp: u8,
+    vlan_dei: bool,
+    ethertype: u16,
+}
+
+impl EthernetBuilder {
+    /// Constructs an [`EthernetBuilder`] with all fields zeroed and no vlan tag
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn destination_address(mut self, destination_address: [u8; 6]) -> Self {
+        self.destination_address = destination_address;
+        self
+    }
+
+    pub fn source_address(mut self, source_address: [u8; 6]) -> Self {
+        self.source_address = source_address;
+        self
+    }
+
+    /// Adds an 802.1Q tag carrying the provided `vlan` to the header
+    pub fn vlan(mut self, vlan: u16) -> Self {
+        self.vlan = Some(vlan);
+        self
+    }
+
+    pub fn vlan_pcp(mut self, vlan_pcp: u8) -> Self {
+        self.vlan_pcp = vlan_pcp;
+        self
+    }
+
+    pub fn vlan_dei(mut self, vlan_dei: bool) -> Self {
+        self.vlan_dei = vlan_dei;
+        self
+    }
+
+    pub fn ethertype(mut self, ethertype: u16) -> Self {
+        self.ethertype = ethertype;
+        self
+    }
+
+    pub fn computed_ihl(&self) -> usize {
+        match self.vlan {
+            Some(_) => 18,
+            None => 14,
+        }
+    }
+
+    /// Serializes this header followed by `payload` into `buffer`, returning the number of bytes written
+    pub fn build(&self, buffer: &mut [u8], payload: &[u8]) -> Result<usize> {
+        let ihl = self.computed_ihl();
+        if buffer.len() < ihl + payload.len() {
+            return Err(Error::Truncated);
+        }
+        buffer[ihl..ihl + payload.len()].copy_from_slice(payload);
+        self.write_header(&mut buffer[..ihl])?;
+        Ok(ihl + payload.len())
+    }
+
+    pub(crate) fn write_header(&self, buffer: &mut [u8]) -> Result<()> {
+        if self.ethertype < 0x0600 {
+            // we don't support 802.3 (LLC) frames
+            return Err(Error::Malformed);
+        }
+        buffer[0..6].copy_from_slice(&self.destination_address);
+        buffer[6..12].copy_from_slice(&self.source_address);
+        if let Some(vlan) = self.vlan {
+            let tci = ((self.vlan_pcp as u16 & 0x07) << 13) | ((self.vlan_dei as u16) << 12) | (vlan & 0x0FFF);
+            buffer[12..=13].copy_from_slice(&EtherType::DOT1Q.to_be_bytes());
+            buffer[14..=15].copy_from_slice(&tci.to_be_bytes());
+            buffer[16..=17].copy_from_slice(&self.ethertype.to_be_bytes());
+        } else {
+            buffer[12..=13].copy_from_slice(&self.ethertype.to_be_bytes());
         }
+        Ok(())
     }
 }
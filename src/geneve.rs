@@ -0,0 +1,160 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{Error, Result};
+
+/// Represents a Geneve header and payload
+#[derive(Debug, Copy, Clone)]
+pub struct GenevePdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of a [`GenevePdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Geneve<'a> {
+    Raw(&'a [u8]),
+    Ethernet(super::EthernetPdu<'a>),
+    Ipv4(super::Ipv4Pdu<'a>),
+    Ipv6(super::Ipv6Pdu<'a>),
+}
+
+impl<'a> GenevePdu<'a> {
+    /// Constructs a [`GenevePdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        let pdu = GenevePdu { buffer };
+        if pdu.version() != 0 {
+            // we only support rfc8926 Geneve frames
+            return Err(Error::Malformed);
+        }
+        if buffer.len() < pdu.computed_ihl() {
+            return Err(Error::Truncated);
+        }
+        Ok(pdu)
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..self.computed_ihl()]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Geneve<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Geneve<'a>> {
+        let rest = &self.buffer[self.computed_ihl()..];
+        Ok(match self.protocol_type() {
+            super::EtherType::TEB => Geneve::Ethernet(super::EthernetPdu::new(rest)?),
+            super::EtherType::IPV4 => Geneve::Ipv4(super::Ipv4Pdu::new(rest)?),
+            super::EtherType::IPV6 => Geneve::Ipv6(super::Ipv6Pdu::new(rest)?),
+            _ => Geneve::Raw(rest),
+        })
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        8 + self.options_length() as usize * 4
+    }
+
+    pub fn version(&'a self) -> u8 {
+        self.buffer[0] >> 6
+    }
+
+    pub fn options_length(&'a self) -> u8 {
+        self.buffer[0] & 0x3F
+    }
+
+    pub fn oam(&'a self) -> bool {
+        (self.buffer[1] & 0x80) != 0
+    }
+
+    pub fn critical(&'a self) -> bool {
+        (self.buffer[1] & 0x40) != 0
+    }
+
+    pub fn protocol_type(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[2..=3].try_into().unwrap())
+    }
+
+    pub fn vni(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap()) >> 8
+    }
+
+    pub fn options(&'a self) -> GeneveOptionIterator<'a> {
+        GeneveOptionIterator { buffer: self.buffer, pos: 8, ihl: self.computed_ihl() }
+    }
+}
+
+/// Represents a Geneve option
+///
+/// The most significant bit of `option_type` indicates that the option is critical. `data` excludes the 4-byte option
+/// header.
+#[derive(Debug, Copy, Clone)]
+pub enum GeneveOption<'a> {
+    Raw { class: u16, option_type: u8, data: &'a [u8] },
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct GeneveOptionIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    ihl: usize,
+}
+
+impl<'a> Iterator for GeneveOptionIterator<'a> {
+    type Item = GeneveOption<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        if self.ihl < (pos + 4) {
+            return None;
+        }
+        let len = 4 + (self.buffer[pos + 3] & 0x1F) as usize * 4;
+        if self.ihl < (pos + len) {
+            return None;
+        }
+        self.pos += len;
+        Some(GeneveOption::Raw {
+            class: u16::from_be_bytes(self.buffer[pos..=pos + 1].try_into().unwrap()),
+            option_type: self.buffer[pos + 2],
+            data: &self.buffer[pos + 4..pos + len],
+        })
+    }
+}
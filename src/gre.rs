@@ -18,7 +18,7 @@
 
 use core::convert::TryInto;
 
-use crate::{util, Error, Result};
+use crate::{util, ChecksumStatus, Error, Result};
 
 /// Represents a GRE header and payload
 #[derive(Debug, Copy, Clone)]
@@ -33,6 +33,9 @@ pub enum Gre<'a> {
     Ethernet(super::EthernetPdu<'a>),
     Ipv4(super::Ipv4Pdu<'a>),
     Ipv6(super::Ipv6Pdu<'a>),
+    Mpls(super::MplsPdu<'a>),
+    Ppp(super::PppPdu<'a>),
+    Erspan(super::ErspanPdu<'a>),
 }
 
 impl<'a> GrePdu<'a> {
@@ -41,11 +44,15 @@ impl<'a> GrePdu<'a> {
         if buffer.len() < 4 {
             return Err(Error::Truncated);
         }
-        if buffer[1] & 0x07 != 0 {
-            // we only support rfc2784 GRE frames
+        if buffer[1] & 0x07 > 1 {
+            // we only support rfc2784 and rfc2637 (enhanced) GRE frames
             return Err(Error::Malformed);
         }
         let pdu = GrePdu { buffer };
+        if pdu.version() == 1 && !pdu.has_key() {
+            // enhanced GRE frames always carry a payload length and call id in the key field
+            return Err(Error::Malformed);
+        }
         if buffer.len() < pdu.computed_ihl() {
             return Err(Error::Truncated);
         }
@@ -85,6 +92,15 @@ impl<'a> GrePdu<'a> {
             super::EtherType::TEB => Gre::Ethernet(super::EthernetPdu::new(rest)?),
             super::EtherType::IPV4 => Gre::Ipv4(super::Ipv4Pdu::new(rest)?),
             super::EtherType::IPV6 => Gre::Ipv6(super::Ipv6Pdu::new(rest)?),
+            super::EtherType::MPLS | super::EtherType::MPLS_MULTICAST => Gre::Mpls(super::MplsPdu::new(rest)?),
+            super::EtherType::PPP if self.payload_length() != Some(0) => Gre::Ppp(super::PppPdu::new(rest)?),
+            // erspan type I frames have no sequence number and no erspan header
+            super::EtherType::ERSPAN_TYPE_II if !self.has_sequence_number() => {
+                Gre::Ethernet(super::EthernetPdu::new(rest)?)
+            }
+            super::EtherType::ERSPAN_TYPE_II | super::EtherType::ERSPAN_TYPE_III => {
+                Gre::Erspan(super::ErspanPdu::new(rest)?)
+            }
             _ => Gre::Raw(rest),
         })
     }
@@ -100,6 +116,9 @@ impl<'a> GrePdu<'a> {
         if self.has_sequence_number() {
             ihl += 4;
         }
+        if self.has_acknowledgement_number() {
+            ihl += 4;
+        }
         ihl
     }
 
@@ -123,6 +142,10 @@ impl<'a> GrePdu<'a> {
         (self.buffer[0] & 0x10) != 0
     }
 
+    pub fn has_acknowledgement_number(&'a self) -> bool {
+        self.version() == 1 && (self.buffer[1] & 0x80) != 0
+    }
+
     pub fn checksum(&'a self) -> Option<u16> {
         if self.has_checksum() {
             Some(u16::from_be_bytes(self.buffer[4..=5].try_into().unwrap()))
@@ -139,6 +162,13 @@ impl<'a> GrePdu<'a> {
         }
     }
 
+    pub fn verify_checksum(&'a self) -> ChecksumStatus {
+        match (self.checksum(), self.computed_checksum()) {
+            (Some(checksum), Some(computed_checksum)) => ChecksumStatus::from_checksums(checksum, computed_checksum),
+            _ => ChecksumStatus::NotChecked,
+        }
+    }
+
     pub fn key(&'a self) -> Option<u32> {
         if self.has_checksum() && self.has_key() {
             Some(u32::from_be_bytes(self.buffer[8..=11].try_into().unwrap()))
@@ -160,4 +190,170 @@ impl<'a> GrePdu<'a> {
             None
         }
     }
+
+    pub fn payload_length(&'a self) -> Option<u16> {
+        if self.version() == 1 {
+            self.key().map(|key| (key >> 16) as u16)
+        } else {
+            None
+        }
+    }
+
+    pub fn call_id(&'a self) -> Option<u16> {
+        if self.version() == 1 {
+            self.key().map(|key| key as u16)
+        } else {
+            None
+        }
+    }
+
+    pub fn acknowledgement_number(&'a self) -> Option<u32> {
+        if self.has_acknowledgement_number() {
+            let offset = self.computed_ihl() - 4;
+            Some(u32::from_be_bytes(self.buffer[offset..offset + 4].try_into().unwrap()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Represents a mutable GRE header and payload
+#[derive(Debug)]
+pub struct GrePduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+/// Contains the mutable inner payload of a [`GrePduMut`]
+#[derive(Debug)]
+pub enum GreMut<'a> {
+    Raw(&'a mut [u8]),
+    Ethernet(super::EthernetPduMut<'a>),
+    Ipv4(super::Ipv4PduMut<'a>),
+    Ipv6(super::Ipv6PduMut<'a>),
+}
+
+impl<'a> GrePduMut<'a> {
+    /// Constructs a [`GrePduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        GrePdu::new(buffer)?;
+        Ok(GrePduMut { buffer })
+    }
+
+    /// Returns a read-only [`GrePdu`] view of this PDU
+    pub fn as_pdu(&self) -> GrePdu<'_> {
+        GrePdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`GrePdu`] view of this PDU
+    pub fn into_pdu(self) -> GrePdu<'a> {
+        GrePdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<GreMut<'_>> {
+        GrePduMut { buffer: self.buffer }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<GreMut<'a>> {
+        let (ihl, ethertype) = {
+            let pdu = self.as_pdu();
+            (pdu.computed_ihl(), pdu.ethertype())
+        };
+        let rest = &mut self.buffer[ihl..];
+        Ok(match ethertype {
+            super::EtherType::TEB => GreMut::Ethernet(super::EthernetPduMut::new(rest)?),
+            super::EtherType::IPV4 => GreMut::Ipv4(super::Ipv4PduMut::new(rest)?),
+            super::EtherType::IPV6 => GreMut::Ipv6(super::Ipv6PduMut::new(rest)?),
+            _ => GreMut::Raw(rest),
+        })
+    }
+
+    pub fn set_version(&mut self, version: u8) -> Result<()> {
+        let previous = self.buffer[1];
+        self.buffer[1] = (self.buffer[1] & 0xF8) | (version & 0x07);
+        if let Err(e) = GrePdu::new(self.buffer) {
+            self.buffer[1] = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn set_ethertype(&mut self, ethertype: u16) {
+        self.buffer[2..=3].copy_from_slice(&ethertype.to_be_bytes());
+    }
+
+    pub fn set_checksum(&mut self, checksum: u16) -> Result<()> {
+        if !self.as_pdu().has_checksum() {
+            return Err(Error::Malformed);
+        }
+        self.buffer[4..=5].copy_from_slice(&checksum.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn set_key(&mut self, key: u32) -> Result<()> {
+        let pdu = self.as_pdu();
+        if !pdu.has_key() {
+            return Err(Error::Malformed);
+        }
+        let offset = if pdu.has_checksum() { 8 } else { 4 };
+        self.buffer[offset..offset + 4].copy_from_slice(&key.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn set_sequence_number(&mut self, sequence_number: u32) -> Result<()> {
+        let pdu = self.as_pdu();
+        if !pdu.has_sequence_number() {
+            return Err(Error::Malformed);
+        }
+        let offset = if pdu.has_acknowledgement_number() { pdu.computed_ihl() - 8 } else { pdu.computed_ihl() - 4 };
+        self.buffer[offset..offset + 4].copy_from_slice(&sequence_number.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn set_payload_length(&mut self, payload_length: u16) -> Result<()> {
+        let pdu = self.as_pdu();
+        if pdu.version() != 1 {
+            return Err(Error::Malformed);
+        }
+        let offset = if pdu.has_checksum() { 8 } else { 4 };
+        self.buffer[offset..offset + 2].copy_from_slice(&payload_length.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn set_call_id(&mut self, call_id: u16) -> Result<()> {
+        let pdu = self.as_pdu();
+        if pdu.version() != 1 {
+            return Err(Error::Malformed);
+        }
+        let offset = if pdu.has_checksum() { 10 } else { 6 };
+        self.buffer[offset..offset + 2].copy_from_slice(&call_id.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn set_acknowledgement_number(&mut self, acknowledgement_number: u32) -> Result<()> {
+        let pdu = self.as_pdu();
+        if !pdu.has_acknowledgement_number() {
+            return Err(Error::Malformed);
+        }
+        let offset = pdu.computed_ihl() - 4;
+        self.buffer[offset..offset + 4].copy_from_slice(&acknowledgement_number.to_be_bytes());
+        Ok(())
+    }
 }
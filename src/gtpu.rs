@@ -0,0 +1,234 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{Error, Result};
+
+/// Represents a GTP-U (GTPv1 user plane) header and payload
+#[derive(Debug, Copy, Clone)]
+pub struct GtpuPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of a [`GtpuPdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Gtpu<'a> {
+    Raw(&'a [u8]),
+    Ipv4(super::Ipv4Pdu<'a>),
+    Ipv6(super::Ipv6Pdu<'a>),
+}
+
+impl<'a> GtpuPdu<'a> {
+    /// Constructs a [`GtpuPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        let pdu = GtpuPdu { buffer };
+        if pdu.version() != 1 || !pdu.protocol_type() {
+            // we only support GTPv1 frames
+            return Err(Error::Malformed);
+        }
+        if pdu.has_extension_header() || pdu.has_sequence_number() || pdu.has_npdu_number() {
+            if buffer.len() < 12 {
+                return Err(Error::Truncated);
+            }
+            let mut pos = 12;
+            let mut next_type = buffer[11];
+            while next_type != 0 && pdu.has_extension_header() {
+                if buffer.len() <= pos {
+                    return Err(Error::Truncated);
+                }
+                let len = buffer[pos] as usize * 4;
+                if len == 0 {
+                    return Err(Error::Malformed);
+                }
+                if buffer.len() < pos + len {
+                    return Err(Error::Truncated);
+                }
+                pos += len;
+                next_type = buffer[pos - 1];
+            }
+        }
+        Ok(pdu)
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..self.computed_ihl()]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Gtpu<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Gtpu<'a>> {
+        let rest = &self.buffer[self.computed_ihl()..];
+        if self.message_type() != GtpuMessageType::G_PDU {
+            return Ok(Gtpu::Raw(rest));
+        }
+        Ok(match rest.first().map(|b| b >> 4) {
+            Some(4) => Gtpu::Ipv4(super::Ipv4Pdu::new(rest)?),
+            Some(6) => Gtpu::Ipv6(super::Ipv6Pdu::new(rest)?),
+            _ => Gtpu::Raw(rest),
+        })
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        if !(self.has_extension_header() || self.has_sequence_number() || self.has_npdu_number()) {
+            return 8;
+        }
+        let mut extension_headers = self.extension_headers();
+        while extension_headers.next().is_some() {}
+        extension_headers.pos
+    }
+
+    pub fn version(&'a self) -> u8 {
+        self.buffer[0] >> 5
+    }
+
+    pub fn protocol_type(&'a self) -> bool {
+        (self.buffer[0] & 0x10) != 0
+    }
+
+    pub fn has_extension_header(&'a self) -> bool {
+        (self.buffer[0] & 0x04) != 0
+    }
+
+    pub fn has_sequence_number(&'a self) -> bool {
+        (self.buffer[0] & 0x02) != 0
+    }
+
+    pub fn has_npdu_number(&'a self) -> bool {
+        (self.buffer[0] & 0x01) != 0
+    }
+
+    pub fn message_type(&'a self) -> u8 {
+        self.buffer[1]
+    }
+
+    pub fn length(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[2..=3].try_into().unwrap())
+    }
+
+    pub fn teid(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap())
+    }
+
+    pub fn sequence_number(&'a self) -> Option<u16> {
+        if self.has_sequence_number() {
+            Some(u16::from_be_bytes(self.buffer[8..=9].try_into().unwrap()))
+        } else {
+            None
+        }
+    }
+
+    pub fn npdu_number(&'a self) -> Option<u8> {
+        if self.has_npdu_number() {
+            Some(self.buffer[10])
+        } else {
+            None
+        }
+    }
+
+    pub fn next_extension_header_type(&'a self) -> Option<u8> {
+        if self.has_extension_header() {
+            Some(self.buffer[11])
+        } else {
+            None
+        }
+    }
+
+    pub fn extension_headers(&'a self) -> GtpuExtensionHeaderIterator<'a> {
+        GtpuExtensionHeaderIterator {
+            buffer: self.buffer,
+            pos: 12,
+            next_type: self.next_extension_header_type().unwrap_or_default(),
+        }
+    }
+}
+
+/// Provides constants representing various GTP-U message types supported by this crate
+#[allow(non_snake_case)]
+pub mod GtpuMessageType {
+    pub const ECHO_REQUEST: u8 = 1;
+    pub const ECHO_RESPONSE: u8 = 2;
+    pub const ERROR_INDICATION: u8 = 26;
+    pub const END_MARKER: u8 = 254;
+    pub const G_PDU: u8 = 255;
+}
+
+/// Represents a GTP-U extension header
+///
+/// `data` excludes the length and next extension header type fields.
+#[derive(Debug, Copy, Clone)]
+pub enum GtpuExtensionHeader<'a> {
+    Raw { header_type: u8, data: &'a [u8] },
+    PduSessionContainer { pdu_type: u8, qfi: u8 },
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct GtpuExtensionHeaderIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    next_type: u8,
+}
+
+impl<'a> Iterator for GtpuExtensionHeaderIterator<'a> {
+    type Item = GtpuExtensionHeader<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_type == 0 {
+            return None;
+        }
+        let pos = self.pos;
+        let len = *self.buffer.get(pos)? as usize * 4;
+        if len == 0 || self.buffer.len() < (pos + len) {
+            return None;
+        }
+        let header_type = self.next_type;
+        self.pos += len;
+        self.next_type = self.buffer[pos + len - 1];
+        let data = &self.buffer[pos + 1..pos + len - 1];
+        match header_type {
+            0x85 if data.len() >= 2 => {
+                Some(GtpuExtensionHeader::PduSessionContainer { pdu_type: data[0] >> 4, qfi: data[1] & 0x3F })
+            }
+            _ => Some(GtpuExtensionHeader::Raw { header_type, data }),
+        }
+    }
+}
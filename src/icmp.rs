@@ -18,27 +18,505 @@
 
 use core::convert::TryInto;
 
-use crate::{util, Error, Result};
+use crate::{util, ChecksumStatus, Error, IpProto, Ipv4Pdu, Ipv6AddressIterator, Ipv6Pdu, Result};
+
+/// Provides constants representing various ICMPv4 message types supported by this crate
+#[allow(non_snake_case)]
+pub mod IcmpType {
+    pub const ECHO_REPLY: u8 = 0;
+    pub const DESTINATION_UNREACHABLE: u8 = 3;
+    pub const REDIRECT: u8 = 5;
+    pub const ECHO_REQUEST: u8 = 8;
+    pub const TIME_EXCEEDED: u8 = 11;
+    pub const PARAMETER_PROBLEM: u8 = 12;
+    pub const TIMESTAMP_REQUEST: u8 = 13;
+    pub const TIMESTAMP_REPLY: u8 = 14;
+}
+
+/// Provides constants representing various ICMPv6 message types supported by this crate
+#[allow(non_snake_case)]
+pub mod Icmpv6Type {
+    pub const DESTINATION_UNREACHABLE: u8 = 1;
+    pub const PACKET_TOO_BIG: u8 = 2;
+    pub const TIME_EXCEEDED: u8 = 3;
+    pub const PARAMETER_PROBLEM: u8 = 4;
+    pub const ECHO_REQUEST: u8 = 128;
+    pub const ECHO_REPLY: u8 = 129;
+    pub const MULTICAST_LISTENER_QUERY: u8 = 130;
+    pub const MULTICAST_LISTENER_REPORT: u8 = 131;
+    pub const MULTICAST_LISTENER_DONE: u8 = 132;
+    pub const ROUTER_SOLICITATION: u8 = 133;
+    pub const ROUTER_ADVERTISEMENT: u8 = 134;
+    pub const NEIGHBOR_SOLICITATION: u8 = 135;
+    pub const NEIGHBOR_ADVERTISEMENT: u8 = 136;
+    pub const REDIRECT: u8 = 137;
+    pub const MULTICAST_LISTENER_REPORT_V2: u8 = 143;
+}
 
 /// Represents an ICMP payload
 #[derive(Debug, Copy, Clone)]
 pub struct IcmpPdu<'a> {
     buffer: &'a [u8],
+    // the ip version that the message was carried by, if known
+    version: Option<u8>,
 }
 
 /// Contains the inner payload of an [`IcmpPdu`]
+///
+/// Messages of an unknown address family or an unsupported type are contained in the [`Icmp::Raw`] variant.
 #[derive(Debug, Copy, Clone)]
 pub enum Icmp<'a> {
     Raw(&'a [u8]),
+    Icmpv4(Icmpv4<'a>),
+    Icmpv6(Icmpv6<'a>),
+}
+
+/// Contains a typed ICMPv4 message (rfc792)
+///
+/// Error messages quote the datagram that caused the error, which is typically truncated after the first 8 bytes of
+/// its payload. The quoted datagram is available as an `original` [`Ipv4Pdu`] when its header can be parsed, and its
+/// ports are available from [`IcmpPdu::computed_original_ports()`] even when its upper-layer header is incomplete.
+#[derive(Debug, Copy, Clone)]
+pub enum Icmpv4<'a> {
+    EchoReply {
+        identifier: u16,
+        sequence_number: u16,
+        data: &'a [u8],
+    },
+    DestinationUnreachable {
+        /// The MTU of the next-hop network when the code is 4 (fragmentation needed), as defined by RFC 1191
+        next_hop_mtu: Option<u16>,
+        original: Option<Ipv4Pdu<'a>>,
+    },
+    Redirect {
+        gateway: [u8; 4],
+        original: Option<Ipv4Pdu<'a>>,
+    },
+    EchoRequest {
+        identifier: u16,
+        sequence_number: u16,
+        data: &'a [u8],
+    },
+    TimeExceeded {
+        original: Option<Ipv4Pdu<'a>>,
+    },
+    ParameterProblem {
+        pointer: u8,
+        original: Option<Ipv4Pdu<'a>>,
+    },
+    TimestampRequest {
+        identifier: u16,
+        sequence_number: u16,
+        originate: u32,
+        receive: u32,
+        transmit: u32,
+    },
+    TimestampReply {
+        identifier: u16,
+        sequence_number: u16,
+        originate: u32,
+        receive: u32,
+        transmit: u32,
+    },
+}
+
+/// Contains a typed ICMPv6 message (rfc4443, rfc4861)
+///
+/// Error messages quote as much of the invoking packet as fits in the minimum IPv6 MTU, which is available as an
+/// `original` [`Ipv6Pdu`] when its header can be parsed.
+#[derive(Debug, Copy, Clone)]
+pub enum Icmpv6<'a> {
+    DestinationUnreachable {
+        original: Option<Ipv6Pdu<'a>>,
+    },
+    PacketTooBig {
+        mtu: u32,
+        original: Option<Ipv6Pdu<'a>>,
+    },
+    TimeExceeded {
+        original: Option<Ipv6Pdu<'a>>,
+    },
+    ParameterProblem {
+        pointer: u32,
+        original: Option<Ipv6Pdu<'a>>,
+    },
+    EchoRequest {
+        identifier: u16,
+        sequence_number: u16,
+        data: &'a [u8],
+    },
+    EchoReply {
+        identifier: u16,
+        sequence_number: u16,
+        data: &'a [u8],
+    },
+    RouterSolicitation {
+        options: NdpOptionIterator<'a>,
+    },
+    RouterAdvertisement {
+        current_hop_limit: u8,
+        managed_flag: bool,
+        other_flag: bool,
+        router_lifetime: u16,
+        reachable_time: u32,
+        retrans_timer: u32,
+        options: NdpOptionIterator<'a>,
+    },
+    NeighborSolicitation {
+        target_address: [u8; 16],
+        options: NdpOptionIterator<'a>,
+    },
+    NeighborAdvertisement {
+        router_flag: bool,
+        solicited_flag: bool,
+        override_flag: bool,
+        target_address: [u8; 16],
+        options: NdpOptionIterator<'a>,
+    },
+    Redirect {
+        target_address: [u8; 16],
+        destination_address: [u8; 16],
+        options: NdpOptionIterator<'a>,
+    },
+    /// MLDv1 query (rfc2710)
+    MulticastListenerQuery {
+        maximum_response_delay: u16,
+        multicast_address: [u8; 16],
+    },
+    /// MLDv2 query (rfc3810), which is distinguished from an MLDv1 query by its length
+    MulticastListenerQueryV2 {
+        maximum_response_code: u16,
+        multicast_address: [u8; 16],
+        suppress_router_processing: bool,
+        robustness: u8,
+        query_interval_code: u8,
+        sources: Ipv6AddressIterator<'a>,
+    },
+    /// MLDv1 report (rfc2710)
+    MulticastListenerReport {
+        multicast_address: [u8; 16],
+    },
+    /// MLDv1 done (rfc2710)
+    MulticastListenerDone {
+        multicast_address: [u8; 16],
+    },
+    /// MLDv2 report (rfc3810)
+    MulticastListenerReportV2 {
+        records: MulticastAddressRecordIterator<'a>,
+    },
+}
+
+impl<'a> Icmpv4<'a> {
+    // returns none for unsupported message types and messages that are too short for their type
+    fn parse(buffer: &'a [u8]) -> Option<Self> {
+        let identifier = u16::from_be_bytes(buffer[4..=5].try_into().unwrap());
+        let sequence_number = u16::from_be_bytes(buffer[6..=7].try_into().unwrap());
+        // the quoted datagram is parsed leniently, since it is usually truncated after the first 8 bytes of its payload
+        let original = Ipv4Pdu::new(&buffer[8..]).ok();
+        Some(match buffer[0] {
+            IcmpType::ECHO_REPLY => Icmpv4::EchoReply { identifier, sequence_number, data: &buffer[8..] },
+            IcmpType::DESTINATION_UNREACHABLE => Icmpv4::DestinationUnreachable {
+                next_hop_mtu: if buffer[1] == 4 { Some(sequence_number) } else { None },
+                original,
+            },
+            IcmpType::REDIRECT => Icmpv4::Redirect { gateway: buffer[4..8].try_into().unwrap(), original },
+            IcmpType::ECHO_REQUEST => Icmpv4::EchoRequest { identifier, sequence_number, data: &buffer[8..] },
+            IcmpType::TIME_EXCEEDED => Icmpv4::TimeExceeded { original },
+            IcmpType::PARAMETER_PROBLEM => Icmpv4::ParameterProblem { pointer: buffer[4], original },
+            IcmpType::TIMESTAMP_REQUEST | IcmpType::TIMESTAMP_REPLY if buffer.len() >= 20 => {
+                let originate = u32::from_be_bytes(buffer[8..12].try_into().unwrap());
+                let receive = u32::from_be_bytes(buffer[12..16].try_into().unwrap());
+                let transmit = u32::from_be_bytes(buffer[16..20].try_into().unwrap());
+                if buffer[0] == IcmpType::TIMESTAMP_REQUEST {
+                    Icmpv4::TimestampRequest { identifier, sequence_number, originate, receive, transmit }
+                } else {
+                    Icmpv4::TimestampReply { identifier, sequence_number, originate, receive, transmit }
+                }
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl<'a> Icmpv6<'a> {
+    // returns none for unsupported message types and messages that are too short for their type
+    fn parse(buffer: &'a [u8]) -> Option<Self> {
+        let word = u32::from_be_bytes(buffer[4..8].try_into().unwrap());
+        let identifier = u16::from_be_bytes(buffer[4..=5].try_into().unwrap());
+        let sequence_number = u16::from_be_bytes(buffer[6..=7].try_into().unwrap());
+        let address = |pos: usize| -> [u8; 16] { buffer[pos..pos + 16].try_into().unwrap() };
+        let original = Ipv6Pdu::new(&buffer[8..]).ok();
+        Some(match buffer[0] {
+            Icmpv6Type::DESTINATION_UNREACHABLE => Icmpv6::DestinationUnreachable { original },
+            Icmpv6Type::PACKET_TOO_BIG => Icmpv6::PacketTooBig { mtu: word, original },
+            Icmpv6Type::TIME_EXCEEDED => Icmpv6::TimeExceeded { original },
+            Icmpv6Type::PARAMETER_PROBLEM => Icmpv6::ParameterProblem { pointer: word, original },
+            Icmpv6Type::ECHO_REQUEST => Icmpv6::EchoRequest { identifier, sequence_number, data: &buffer[8..] },
+            Icmpv6Type::ECHO_REPLY => Icmpv6::EchoReply { identifier, sequence_number, data: &buffer[8..] },
+            Icmpv6Type::ROUTER_SOLICITATION => {
+                Icmpv6::RouterSolicitation { options: NdpOptionIterator::new(&buffer[8..]) }
+            }
+            Icmpv6Type::ROUTER_ADVERTISEMENT if buffer.len() >= 16 => Icmpv6::RouterAdvertisement {
+                current_hop_limit: buffer[4],
+                managed_flag: buffer[5] & 0x80 != 0,
+                other_flag: buffer[5] & 0x40 != 0,
+                router_lifetime: sequence_number,
+                reachable_time: u32::from_be_bytes(buffer[8..12].try_into().unwrap()),
+                retrans_timer: u32::from_be_bytes(buffer[12..16].try_into().unwrap()),
+                options: NdpOptionIterator::new(&buffer[16..]),
+            },
+            Icmpv6Type::NEIGHBOR_SOLICITATION if buffer.len() >= 24 => Icmpv6::NeighborSolicitation {
+                target_address: address(8),
+                options: NdpOptionIterator::new(&buffer[24..]),
+            },
+            Icmpv6Type::NEIGHBOR_ADVERTISEMENT if buffer.len() >= 24 => Icmpv6::NeighborAdvertisement {
+                router_flag: buffer[4] & 0x80 != 0,
+                solicited_flag: buffer[4] & 0x40 != 0,
+                override_flag: buffer[4] & 0x20 != 0,
+                target_address: address(8),
+                options: NdpOptionIterator::new(&buffer[24..]),
+            },
+            Icmpv6Type::REDIRECT if buffer.len() >= 40 => Icmpv6::Redirect {
+                target_address: address(8),
+                destination_address: address(24),
+                options: NdpOptionIterator::new(&buffer[40..]),
+            },
+            Icmpv6Type::MULTICAST_LISTENER_QUERY if buffer.len() >= 28 => {
+                let number_of_sources = u16::from_be_bytes(buffer[26..=27].try_into().unwrap()) as usize;
+                let sources = buffer.get(28..28 + number_of_sources * 16)?;
+                Icmpv6::MulticastListenerQueryV2 {
+                    maximum_response_code: identifier,
+                    multicast_address: address(8),
+                    suppress_router_processing: buffer[24] & 0x08 != 0,
+                    robustness: buffer[24] & 0x07,
+                    query_interval_code: buffer[25],
+                    sources: Ipv6AddressIterator::new(sources),
+                }
+            }
+            Icmpv6Type::MULTICAST_LISTENER_QUERY if buffer.len() >= 24 => {
+                Icmpv6::MulticastListenerQuery { maximum_response_delay: identifier, multicast_address: address(8) }
+            }
+            Icmpv6Type::MULTICAST_LISTENER_REPORT if buffer.len() >= 24 => {
+                Icmpv6::MulticastListenerReport { multicast_address: address(8) }
+            }
+            Icmpv6Type::MULTICAST_LISTENER_DONE if buffer.len() >= 24 => {
+                Icmpv6::MulticastListenerDone { multicast_address: address(8) }
+            }
+            Icmpv6Type::MULTICAST_LISTENER_REPORT_V2 => Icmpv6::MulticastListenerReportV2 {
+                records: MulticastAddressRecordIterator { buffer: &buffer[8..], pos: 0, remaining: sequence_number },
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Represents a multicast address record of an MLDv2 report (rfc3810)
+#[derive(Debug, Copy, Clone)]
+pub struct MulticastAddressRecord<'a> {
+    pub record_type: u8,
+    pub multicast_address: [u8; 16],
+    pub sources: Ipv6AddressIterator<'a>,
+    pub auxiliary_data: &'a [u8],
+}
+
+/// Iterator over the multicast address records of an MLDv2 report
+///
+/// Iteration stops after the number of records given in the report, or at the first record that extends beyond the
+/// end of the message.
+#[derive(Debug, Copy, Clone)]
+pub struct MulticastAddressRecordIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for MulticastAddressRecordIterator<'a> {
+    type Item = MulticastAddressRecord<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let pos = self.pos;
+        let header = self.buffer.get(pos..pos + 20)?;
+        let auxiliary_data_length = header[1] as usize * 4;
+        let sources_length = u16::from_be_bytes(header[2..=3].try_into().unwrap()) as usize * 16;
+        let end = pos + 20 + sources_length + auxiliary_data_length;
+        if self.buffer.len() < end {
+            return None;
+        }
+        self.pos = end;
+        self.remaining -= 1;
+        Some(MulticastAddressRecord {
+            record_type: header[0],
+            multicast_address: header[4..20].try_into().unwrap(),
+            sources: Ipv6AddressIterator::new(&self.buffer[pos + 20..pos + 20 + sources_length]),
+            auxiliary_data: &self.buffer[pos + 20 + sources_length..end],
+        })
+    }
+}
+
+/// Represents a Neighbor Discovery option (rfc4861)
+#[derive(Debug, Copy, Clone)]
+pub enum NdpOption<'a> {
+    Raw {
+        option_type: u8,
+        data: &'a [u8],
+    },
+    SourceLinkLayerAddress {
+        address: &'a [u8],
+    },
+    TargetLinkLayerAddress {
+        address: &'a [u8],
+    },
+    PrefixInformation {
+        prefix_length: u8,
+        on_link: bool,
+        autonomous: bool,
+        valid_lifetime: u32,
+        preferred_lifetime: u32,
+        prefix: [u8; 16],
+    },
+    Mtu {
+        mtu: u32,
+    },
+    /// Recursive DNS Server option (rfc8106)
+    RecursiveDnsServer {
+        lifetime: u32,
+        addresses: Ipv6AddressIterator<'a>,
+    },
+    /// DNS Search List option (rfc8106)
+    DnsSearchList {
+        lifetime: u32,
+        domains: DomainNameIterator<'a>,
+    },
+}
+
+/// Iterator over the options of a Neighbor Discovery message
+///
+/// Iteration stops at the first option with a length of zero or that extends beyond the end of the message.
+#[derive(Debug, Copy, Clone)]
+pub struct NdpOptionIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NdpOptionIterator<'a> {
+    fn new(buffer: &'a [u8]) -> Self {
+        NdpOptionIterator { buffer, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for NdpOptionIterator<'a> {
+    type Item = NdpOption<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        let option_type = *self.buffer.get(pos)?;
+        // the option length is expressed in units of 8 bytes, including the type and length fields
+        let len = *self.buffer.get(pos + 1)? as usize * 8;
+        if len == 0 || self.buffer.len() < (pos + len) {
+            return None;
+        }
+        self.pos += len;
+        let data = &self.buffer[(pos + 2)..(pos + len)];
+        match option_type {
+            1 => Some(NdpOption::SourceLinkLayerAddress { address: data }),
+            2 => Some(NdpOption::TargetLinkLayerAddress { address: data }),
+            3 if len == 32 => Some(NdpOption::PrefixInformation {
+                prefix_length: data[0],
+                on_link: data[1] & 0x80 != 0,
+                autonomous: data[1] & 0x40 != 0,
+                valid_lifetime: u32::from_be_bytes(data[2..6].try_into().unwrap()),
+                preferred_lifetime: u32::from_be_bytes(data[6..10].try_into().unwrap()),
+                prefix: data[14..30].try_into().unwrap(),
+            }),
+            5 if len == 8 => Some(NdpOption::Mtu { mtu: u32::from_be_bytes(data[2..6].try_into().unwrap()) }),
+            25 if len >= 24 => Some(NdpOption::RecursiveDnsServer {
+                lifetime: u32::from_be_bytes(data[2..6].try_into().unwrap()),
+                addresses: Ipv6AddressIterator::new(&data[6..]),
+            }),
+            31 if len >= 16 => Some(NdpOption::DnsSearchList {
+                lifetime: u32::from_be_bytes(data[2..6].try_into().unwrap()),
+                domains: DomainNameIterator { buffer: &data[6..], pos: 0 },
+            }),
+            _ => Some(NdpOption::Raw { option_type, data }),
+        }
+    }
+}
+
+/// Iterator over a list of domain names encoded as DNS labels (rfc1035), such as in a DNS Search List option
+///
+/// Iteration stops at the padding that follows the last domain name.
+#[derive(Debug, Copy, Clone)]
+pub struct DomainNameIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for DomainNameIterator<'a> {
+    type Item = DomainLabelIterator<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let mut pos = start;
+        loop {
+            let len = *self.buffer.get(pos)? as usize;
+            if len == 0 {
+                break;
+            }
+            pos += 1 + len;
+        }
+        if pos == start {
+            return None;
+        }
+        self.pos = pos + 1;
+        Some(DomainLabelIterator { buffer: &self.buffer[start..pos], pos: 0 })
+    }
+}
+
+/// Iterator over the labels of a domain name, from the most specific label to the least specific label
+#[derive(Debug, Copy, Clone)]
+pub struct DomainLabelIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for DomainLabelIterator<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = *self.buffer.get(self.pos)? as usize;
+        let label = self.buffer.get(self.pos + 1..self.pos + 1 + len)?;
+        self.pos += 1 + len;
+        Some(label)
+    }
 }
 
 impl<'a> IcmpPdu<'a> {
-    /// Constructs a [`IcmpPdu`] backed by the provided `buffer`
+    /// Constructs an [`IcmpPdu`] backed by the provided `buffer`
+    ///
+    /// The address family of the message is unknown, so its inner payload is always [`Icmp::Raw`]. Use
+    /// [`IcmpPdu::new_icmpv4()`] or [`IcmpPdu::new_icmpv6()`] to decode typed messages.
     pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        IcmpPdu::with_version(buffer, None)
+    }
+
+    /// Constructs an [`IcmpPdu`] containing an ICMPv4 message backed by the provided `buffer`
+    pub fn new_icmpv4(buffer: &'a [u8]) -> Result<Self> {
+        IcmpPdu::with_version(buffer, Some(4))
+    }
+
+    /// Constructs an [`IcmpPdu`] containing an ICMPv6 message backed by the provided `buffer`
+    pub fn new_icmpv6(buffer: &'a [u8]) -> Result<Self> {
+        IcmpPdu::with_version(buffer, Some(6))
+    }
+
+    fn with_version(buffer: &'a [u8], version: Option<u8>) -> Result<Self> {
         if buffer.len() < 8 {
             return Err(Error::Truncated);
         }
-        Ok(IcmpPdu { buffer })
+        Ok(IcmpPdu { buffer, version })
     }
 
     /// Returns a reference to the entire underlying buffer that was provided during construction
@@ -70,7 +548,22 @@ impl<'a> IcmpPdu<'a> {
     /// Consumes this object and returns an object representing the inner payload of this PDU
     pub fn into_inner(self) -> Result<Icmp<'a>> {
         let rest = &self.buffer[4..];
-        Ok(Icmp::Raw(rest))
+        let parsed = match self.version {
+            Some(4) => Icmpv4::parse(self.buffer).map(Icmp::Icmpv4),
+            Some(6) => Icmpv6::parse(self.buffer).map(Icmp::Icmpv6),
+            _ => None,
+        };
+        Ok(parsed.unwrap_or(Icmp::Raw(rest)))
+    }
+
+    /// Returns true if this PDU is known to contain an ICMPv4 message
+    pub fn is_icmpv4(&'a self) -> bool {
+        self.version == Some(4)
+    }
+
+    /// Returns true if this PDU is known to contain an ICMPv6 message
+    pub fn is_icmpv6(&'a self) -> bool {
+        self.version == Some(6)
     }
 
     pub fn message_type(&'a self) -> u8 {
@@ -90,8 +583,8 @@ impl<'a> IcmpPdu<'a> {
             crate::Ip::Ipv4(_) => util::checksum(&[&self.buffer[0..=1], &self.buffer[4..]]),
             crate::Ip::Ipv6(ipv6) => util::checksum(&[
                 &ipv6.source_address().as_ref(),
-                &ipv6.destination_address().as_ref(),
-                &(ipv6.payload_length() as u32).to_be_bytes().as_ref(),
+                &ipv6.computed_final_destination().as_ref(),
+                &ipv6.computed_upper_layer_length().to_be_bytes().as_ref(),
                 &[0x0, 0x0, 0x0, ipv6.computed_protocol()].as_ref(),
                 &self.buffer[0..=1],
                 &self.buffer[4..],
@@ -99,6 +592,10 @@ impl<'a> IcmpPdu<'a> {
         }
     }
 
+    pub fn verify_checksum(&'a self, ip: &crate::Ip) -> ChecksumStatus {
+        ChecksumStatus::from_checksums(self.checksum(), self.computed_checksum(ip))
+    }
+
     #[deprecated(since = "1.3.0", note = "use IcmpPdu::inner()")]
     pub fn message(&'a self) -> &'a [u8] {
         &self.buffer[4..]
@@ -107,4 +604,199 @@ impl<'a> IcmpPdu<'a> {
     pub fn computed_data_offset(&'a self) -> usize {
         4
     }
+
+    /// Returns the first 8 bytes of the upper-layer header of the datagram quoted by an error message, which is as much
+    /// of it as an ICMPv4 error message is guaranteed to contain (rfc792)
+    ///
+    /// Returns `None` if this is not an error message of a known address family, or if its quote is too short.
+    pub fn computed_original_transport(&'a self) -> Option<&'a [u8]> {
+        let (ihl, _) = self.original_header()?;
+        self.buffer[8..].get(ihl..ihl + 8)
+    }
+
+    /// Returns the source and destination ports of the TCP, UDP, UDP-Lite, or SCTP datagram quoted by an error message
+    ///
+    /// Unlike parsing the upper-layer header of the `original` datagram, this only requires its first 8 bytes.
+    pub fn computed_original_ports(&'a self) -> Option<(u16, u16)> {
+        let (_, protocol) = self.original_header()?;
+        let transport = self.computed_original_transport()?;
+        match protocol {
+            IpProto::TCP | IpProto::UDP | IpProto::UDP_LITE | IpProto::SCTP => Some((
+                u16::from_be_bytes(transport[0..=1].try_into().unwrap()),
+                u16::from_be_bytes(transport[2..=3].try_into().unwrap()),
+            )),
+            _ => None,
+        }
+    }
+
+    // returns the header length and upper-layer protocol of the datagram quoted by an error message
+    fn original_header(&'a self) -> Option<(usize, u8)> {
+        let quote = &self.buffer[8..];
+        match (self.version, self.message_type()) {
+            (Some(4), IcmpType::DESTINATION_UNREACHABLE)
+            | (Some(4), IcmpType::REDIRECT)
+            | (Some(4), IcmpType::TIME_EXCEEDED)
+            | (Some(4), IcmpType::PARAMETER_PROBLEM) => {
+                let original = Ipv4Pdu::new(quote).ok()?;
+                Some((original.computed_ihl(), original.protocol()))
+            }
+            (Some(6), Icmpv6Type::DESTINATION_UNREACHABLE)
+            | (Some(6), Icmpv6Type::PACKET_TOO_BIG)
+            | (Some(6), Icmpv6Type::TIME_EXCEEDED)
+            | (Some(6), Icmpv6Type::PARAMETER_PROBLEM) => {
+                let original = Ipv6Pdu::new(quote).ok()?;
+                Some((original.computed_ihl(), original.computed_protocol()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Represents a mutable ICMP payload
+#[derive(Debug)]
+pub struct IcmpPduMut<'a> {
+    buffer: &'a mut [u8],
+    version: Option<u8>,
+}
+
+/// Contains the mutable inner payload of an [`IcmpPduMut`]
+#[derive(Debug)]
+pub enum IcmpMut<'a> {
+    Raw(&'a mut [u8]),
+}
+
+impl<'a> IcmpPduMut<'a> {
+    /// Constructs an [`IcmpPduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        IcmpPdu::new(buffer)?;
+        Ok(IcmpPduMut { buffer, version: None })
+    }
+
+    /// Constructs an [`IcmpPduMut`] containing an ICMPv4 message backed by the provided `buffer`
+    pub fn new_icmpv4(buffer: &'a mut [u8]) -> Result<Self> {
+        IcmpPdu::new_icmpv4(buffer)?;
+        Ok(IcmpPduMut { buffer, version: Some(4) })
+    }
+
+    /// Constructs an [`IcmpPduMut`] containing an ICMPv6 message backed by the provided `buffer`
+    pub fn new_icmpv6(buffer: &'a mut [u8]) -> Result<Self> {
+        IcmpPdu::new_icmpv6(buffer)?;
+        Ok(IcmpPduMut { buffer, version: Some(6) })
+    }
+
+    /// Returns a read-only [`IcmpPdu`] view of this PDU
+    pub fn as_pdu(&self) -> IcmpPdu<'_> {
+        IcmpPdu { buffer: self.buffer, version: self.version }
+    }
+
+    /// Consumes this object and returns a read-only [`IcmpPdu`] view of this PDU
+    pub fn into_pdu(self) -> IcmpPdu<'a> {
+        IcmpPdu { buffer: self.buffer, version: self.version }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<IcmpMut<'_>> {
+        IcmpPduMut { buffer: self.buffer, version: self.version }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<IcmpMut<'a>> {
+        Ok(IcmpMut::Raw(&mut self.buffer[4..]))
+    }
+
+    pub fn set_message_type(&mut self, message_type: u8) {
+        util::rewrite(self.buffer, 2, 0..2, |word| word[0] = message_type);
+    }
+
+    pub fn set_message_code(&mut self, message_code: u8) {
+        util::rewrite(self.buffer, 2, 0..2, |word| word[1] = message_code);
+    }
+
+    pub fn set_checksum(&mut self, checksum: u16) {
+        self.buffer[2..=3].copy_from_slice(&checksum.to_be_bytes());
+    }
+
+    pub(crate) fn update_pseudo_header(&mut self, old: &[u8], new: &[u8]) {
+        let checksum = util::update_checksum(self.as_pdu().checksum(), old, new);
+        self.set_checksum(checksum);
+    }
+}
+
+/// Builds an ICMP header in front of a message body
+#[derive(Debug, Copy, Clone, Default)]
+pub struct IcmpBuilder {
+    message_type: u8,
+    message_code: u8,
+}
+
+impl IcmpBuilder {
+    /// Constructs an [`IcmpBuilder`] with all fields zeroed
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn message_type(mut self, message_type: u8) -> Self {
+        self.message_type = message_type;
+        self
+    }
+
+    pub fn message_code(mut self, message_code: u8) -> Self {
+        self.message_code = message_code;
+        self
+    }
+
+    pub fn computed_data_offset(&self) -> usize {
+        4
+    }
+
+    /// Serializes this header followed by `message` into `buffer`, returning the number of bytes written
+    ///
+    /// The `message` begins immediately after the checksum field (e.g. with the identifier and sequence number of an
+    /// echo request). For ICMPv6, the checksum is computed using the pseudo-header of the provided `ip` PDU.
+    pub fn build(&self, buffer: &mut [u8], ip: &crate::Ip, message: &[u8]) -> Result<usize> {
+        let length = 4 + message.len();
+        if buffer.len() < length {
+            return Err(Error::Truncated);
+        }
+        buffer[4..length].copy_from_slice(message);
+        self.write(&mut buffer[..length], ip)?;
+        Ok(length)
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8], ip: &crate::Ip) -> Result<()> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        buffer[0] = self.message_type;
+        buffer[1] = self.message_code;
+        buffer[2..=3].copy_from_slice(&[0, 0]);
+        let checksum = match ip {
+            crate::Ip::Ipv4(_) => util::checksum([&buffer[..]]),
+            crate::Ip::Ipv6(ipv6) => util::checksum([
+                ipv6.source_address().as_ref(),
+                ipv6.computed_final_destination().as_ref(),
+                (buffer.len() as u32).to_be_bytes().as_ref(),
+                [0x0, 0x0, 0x0, crate::IpProto::ICMP6].as_ref(),
+                &buffer[..],
+            ]),
+        };
+        buffer[2..=3].copy_from_slice(&checksum.to_be_bytes());
+        Ok(())
+    }
 }
@@ -0,0 +1,216 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{util, ChecksumStatus, Error, Ipv4AddressIterator, Result};
+
+/// Provides constants representing various IGMP message types supported by this crate
+#[allow(non_snake_case)]
+pub mod IgmpType {
+    pub const MEMBERSHIP_QUERY: u8 = 0x11;
+    pub const MEMBERSHIP_REPORT_V1: u8 = 0x12;
+    pub const MEMBERSHIP_REPORT_V2: u8 = 0x16;
+    pub const LEAVE_GROUP: u8 = 0x17;
+    pub const MEMBERSHIP_REPORT_V3: u8 = 0x22;
+}
+
+/// Represents an Internet Group Management Protocol (IGMP) message
+#[derive(Debug, Copy, Clone)]
+pub struct IgmpPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the typed message of an [`IgmpPdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Igmp<'a> {
+    Raw(&'a [u8]),
+    /// IGMPv1 or IGMPv2 query (rfc2236); an IGMPv1 query has a maximum response time of zero
+    MembershipQuery {
+        max_response_time: u8,
+        group_address: [u8; 4],
+    },
+    /// IGMPv3 query (rfc3376), which is distinguished from older queries by its length
+    MembershipQueryV3 {
+        max_response_code: u8,
+        group_address: [u8; 4],
+        suppress_router_processing: bool,
+        robustness: u8,
+        query_interval_code: u8,
+        sources: Ipv4AddressIterator<'a>,
+    },
+    MembershipReportV1 {
+        group_address: [u8; 4],
+    },
+    MembershipReportV2 {
+        group_address: [u8; 4],
+    },
+    LeaveGroup {
+        group_address: [u8; 4],
+    },
+    MembershipReportV3 {
+        records: GroupRecordIterator<'a>,
+    },
+}
+
+impl<'a> IgmpPdu<'a> {
+    /// Constructs an [`IgmpPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        Ok(IgmpPdu { buffer })
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..8]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Igmp<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Igmp<'a>> {
+        let group_address = self.group_address();
+        Ok(match self.message_type() {
+            IgmpType::MEMBERSHIP_QUERY if self.buffer.len() >= 12 => {
+                let number_of_sources = u16::from_be_bytes(self.buffer[10..=11].try_into().unwrap()) as usize;
+                let sources = match self.buffer.get(12..12 + number_of_sources * 4) {
+                    Some(sources) => sources,
+                    None => return Ok(Igmp::Raw(&self.buffer[8..])),
+                };
+                Igmp::MembershipQueryV3 {
+                    max_response_code: self.max_response_time(),
+                    group_address,
+                    suppress_router_processing: self.buffer[8] & 0x08 != 0,
+                    robustness: self.buffer[8] & 0x07,
+                    query_interval_code: self.buffer[9],
+                    sources: Ipv4AddressIterator::new(sources),
+                }
+            }
+            IgmpType::MEMBERSHIP_QUERY => {
+                Igmp::MembershipQuery { max_response_time: self.max_response_time(), group_address }
+            }
+            IgmpType::MEMBERSHIP_REPORT_V1 => Igmp::MembershipReportV1 { group_address },
+            IgmpType::MEMBERSHIP_REPORT_V2 => Igmp::MembershipReportV2 { group_address },
+            IgmpType::LEAVE_GROUP => Igmp::LeaveGroup { group_address },
+            IgmpType::MEMBERSHIP_REPORT_V3 => Igmp::MembershipReportV3 {
+                records: GroupRecordIterator {
+                    buffer: &self.buffer[8..],
+                    pos: 0,
+                    remaining: u16::from_be_bytes(self.buffer[6..=7].try_into().unwrap()),
+                },
+            },
+            _ => Igmp::Raw(&self.buffer[8..]),
+        })
+    }
+
+    pub fn message_type(&'a self) -> u8 {
+        self.buffer[0]
+    }
+
+    /// Returns the maximum response time of a query in units of 1/10 second, or the maximum response code of an IGMPv3
+    /// query
+    pub fn max_response_time(&'a self) -> u8 {
+        self.buffer[1]
+    }
+
+    pub fn checksum(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[2..=3].try_into().unwrap())
+    }
+
+    pub fn computed_checksum(&'a self) -> u16 {
+        util::checksum([&self.buffer[0..=1], &self.buffer[4..]])
+    }
+
+    pub fn verify_checksum(&'a self) -> ChecksumStatus {
+        ChecksumStatus::from_checksums(self.checksum(), self.computed_checksum())
+    }
+
+    /// Returns the group address of a query, IGMPv1 or IGMPv2 report, or leave message
+    pub fn group_address(&'a self) -> [u8; 4] {
+        let mut group_address = [0u8; 4];
+        group_address.copy_from_slice(&self.buffer[4..8]);
+        group_address
+    }
+}
+
+/// Represents a group record of an IGMPv3 report (rfc3376)
+#[derive(Debug, Copy, Clone)]
+pub struct GroupRecord<'a> {
+    pub record_type: u8,
+    pub multicast_address: [u8; 4],
+    pub sources: Ipv4AddressIterator<'a>,
+    pub auxiliary_data: &'a [u8],
+}
+
+/// Iterator over the group records of an IGMPv3 report
+///
+/// Iteration stops after the number of records given in the report, or at the first record that extends beyond the
+/// end of the message.
+#[derive(Debug, Copy, Clone)]
+pub struct GroupRecordIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for GroupRecordIterator<'a> {
+    type Item = GroupRecord<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let pos = self.pos;
+        let header = self.buffer.get(pos..pos + 8)?;
+        let auxiliary_data_length = header[1] as usize * 4;
+        let sources_length = u16::from_be_bytes(header[2..=3].try_into().unwrap()) as usize * 4;
+        let end = pos + 8 + sources_length + auxiliary_data_length;
+        if self.buffer.len() < end {
+            return None;
+        }
+        self.pos = end;
+        self.remaining -= 1;
+        Some(GroupRecord {
+            record_type: header[0],
+            multicast_address: header[4..8].try_into().unwrap(),
+            sources: Ipv4AddressIterator::new(&self.buffer[pos + 8..pos + 8 + sources_length]),
+            auxiliary_data: &self.buffer[pos + 8 + sources_length..end],
+        })
+    }
+}
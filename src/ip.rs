@@ -17,8 +17,9 @@
 */
 
 use core::convert::TryInto;
+use core::ops::Range;
 
-use crate::{util, Error, Result};
+use crate::{util, ChecksumStatus, Error, Result};
 
 /// Provides constants representing various IP protocol numbers supported by this crate
 #[allow(non_snake_case)]
@@ -26,8 +27,13 @@ pub mod IpProto {
     pub const TCP: u8 = 6;
     pub const UDP: u8 = 17;
     pub const ICMP: u8 = 1;
+    pub const IGMP: u8 = 2;
     pub const ICMP6: u8 = 58;
     pub const GRE: u8 = 47;
+    pub const ESP: u8 = 50;
+    pub const AH: u8 = 51;
+    pub const SCTP: u8 = 132;
+    pub const UDP_LITE: u8 = 136;
 }
 
 /// Contains either an [`Ipv4Pdu`] or [`Ipv6Pdu`] depending on address family
@@ -49,6 +55,11 @@ impl<'a> Ip<'a> {
             _ => Err(Error::Malformed),
         }
     }
+
+    /// Returns an iterator over the [`ChecksumStatus`] of this PDU and each of its inner payloads
+    pub fn verify_checksums(&self) -> crate::ChecksumIterator<'a> {
+        crate::ChecksumIterator::from_ip(*self)
+    }
 }
 
 /// Represents an IPv4 header and payload
@@ -65,6 +76,11 @@ pub enum Ipv4<'a> {
     Udp(super::UdpPdu<'a>),
     Icmp(super::IcmpPdu<'a>),
     Gre(super::GrePdu<'a>),
+    Esp(super::EspPdu<'a>),
+    Ah(super::AhPdu<'a>),
+    Igmp(super::IgmpPdu<'a>),
+    Sctp(super::SctpPdu<'a>),
+    UdpLite(super::UdpLitePdu<'a>),
 }
 
 impl<'a> Ipv4Pdu<'a> {
@@ -112,6 +128,8 @@ impl<'a> Ipv4Pdu<'a> {
     /// Consumes this object and returns an object representing the inner payload of this PDU
     pub fn into_inner(self) -> Result<Ipv4<'a>> {
         let rest = &self.buffer[self.computed_ihl()..];
+        // protocols without a length field of their own must not see any link-layer padding after the datagram
+        let payload = rest.get(..(self.total_length() as usize).saturating_sub(self.computed_ihl())).unwrap_or(rest);
 
         if self.fragment_offset() > 0 {
             Ok(Ipv4::Raw(rest))
@@ -119,14 +137,19 @@ impl<'a> Ipv4Pdu<'a> {
             Ok(match self.protocol() {
                 IpProto::TCP => Ipv4::Tcp(super::TcpPdu::new(rest)?),
                 IpProto::UDP => Ipv4::Udp(super::UdpPdu::new(rest)?),
-                IpProto::ICMP => Ipv4::Icmp(super::IcmpPdu::new(rest)?),
+                IpProto::ICMP => Ipv4::Icmp(super::IcmpPdu::new_icmpv4(payload)?),
                 IpProto::GRE => {
-                    if rest.len() > 1 && (rest[1] & 0x07) == 0 {
+                    if rest.len() > 1 && (rest[1] & 0x07) <= 1 {
                         Ipv4::Gre(super::GrePdu::new(rest)?)
                     } else {
                         Ipv4::Raw(rest)
                     }
                 }
+                IpProto::ESP => Ipv4::Esp(super::EspPdu::new(rest)?),
+                IpProto::AH => Ipv4::Ah(super::AhPdu::new(rest)?),
+                IpProto::IGMP => Ipv4::Igmp(super::IgmpPdu::new(payload)?),
+                IpProto::SCTP => Ipv4::Sctp(super::SctpPdu::new(payload)?),
+                IpProto::UDP_LITE => Ipv4::UdpLite(super::UdpLitePdu::new(payload)?),
                 _ => Ipv4::Raw(rest),
             })
         }
@@ -144,6 +167,19 @@ impl<'a> Ipv4Pdu<'a> {
         self.ihl() as usize * 4
     }
 
+    /// Returns the length of the upper-layer payload of this PDU, excluding any authentication header, as used in the
+    /// pseudo-header of transport checksums
+    pub fn computed_upper_layer_length(&'a self) -> u16 {
+        let length = self.total_length().saturating_sub(self.computed_ihl() as u16);
+        match self.protocol() {
+            IpProto::AH => match super::AhPdu::new(&self.buffer[self.computed_ihl()..]) {
+                Ok(ah) => length.saturating_sub(ah.computed_ihl() as u16),
+                Err(_) => length,
+            },
+            _ => length,
+        }
+    }
+
     pub fn dscp(&'a self) -> u8 {
         self.buffer[1] >> 2
     }
@@ -192,6 +228,10 @@ impl<'a> Ipv4Pdu<'a> {
         util::checksum(&[&self.buffer[0..=9], &self.buffer[12..self.computed_ihl()]])
     }
 
+    pub fn verify_checksum(&'a self) -> ChecksumStatus {
+        ChecksumStatus::from_checksums(self.checksum(), self.computed_checksum())
+    }
+
     pub fn source_address(&'a self) -> [u8; 4] {
         let mut source_address = [0u8; 4];
         source_address.copy_from_slice(&self.buffer[12..16]);
@@ -213,6 +253,74 @@ impl<'a> Ipv4Pdu<'a> {
 #[derive(Debug, Copy, Clone)]
 pub enum Ipv4Option<'a> {
     Raw { option: u8, data: &'a [u8] },
+    End,
+    NoOp,
+    RecordRoute { pointer: u8, addresses: Ipv4AddressIterator<'a> },
+    LooseSourceRoute { pointer: u8, addresses: Ipv4AddressIterator<'a> },
+    StrictSourceRoute { pointer: u8, addresses: Ipv4AddressIterator<'a> },
+    Timestamp { pointer: u8, overflow: u8, flag: u8, timestamps: Ipv4TimestampIterator<'a> },
+    RouterAlert { value: u16 },
+    Security { classification: u8, protection_authority: &'a [u8] },
+    Cipso { doi: u32, tags: &'a [u8] },
+    StreamId { id: u16 },
+}
+
+impl<'a> Ipv4Option<'a> {
+    pub(crate) fn computed_length(&self) -> usize {
+        match self {
+            Ipv4Option::Raw { data, .. } => data.len(),
+            Ipv4Option::End | Ipv4Option::NoOp => 1,
+            Ipv4Option::RecordRoute { addresses, .. }
+            | Ipv4Option::LooseSourceRoute { addresses, .. }
+            | Ipv4Option::StrictSourceRoute { addresses, .. } => 3 + addresses.buffer.len(),
+            Ipv4Option::Timestamp { timestamps, .. } => 4 + timestamps.buffer.len(),
+            Ipv4Option::RouterAlert { .. } | Ipv4Option::StreamId { .. } => 4,
+            Ipv4Option::Security { protection_authority, .. } => 3 + protection_authority.len(),
+            Ipv4Option::Cipso { tags, .. } => 6 + tags.len(),
+        }
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8]) {
+        let length = self.computed_length();
+        match self {
+            Ipv4Option::Raw { data, .. } => buffer[..data.len()].copy_from_slice(data),
+            Ipv4Option::End => buffer[0] = 0,
+            Ipv4Option::NoOp => buffer[0] = 1,
+            Ipv4Option::RecordRoute { pointer, addresses } => {
+                buffer[0..=2].copy_from_slice(&[7, length as u8, *pointer]);
+                buffer[3..length].copy_from_slice(addresses.buffer);
+            }
+            Ipv4Option::LooseSourceRoute { pointer, addresses } => {
+                buffer[0..=2].copy_from_slice(&[131, length as u8, *pointer]);
+                buffer[3..length].copy_from_slice(addresses.buffer);
+            }
+            Ipv4Option::StrictSourceRoute { pointer, addresses } => {
+                buffer[0..=2].copy_from_slice(&[137, length as u8, *pointer]);
+                buffer[3..length].copy_from_slice(addresses.buffer);
+            }
+            Ipv4Option::Timestamp { pointer, overflow, flag, timestamps } => {
+                buffer[0..=3].copy_from_slice(&[68, length as u8, *pointer, (overflow << 4) | (flag & 0x0F)]);
+                buffer[4..length].copy_from_slice(timestamps.buffer);
+            }
+            Ipv4Option::RouterAlert { value } => {
+                buffer[0..=1].copy_from_slice(&[148, 4]);
+                buffer[2..=3].copy_from_slice(&value.to_be_bytes());
+            }
+            Ipv4Option::Security { classification, protection_authority } => {
+                buffer[0..=2].copy_from_slice(&[130, length as u8, *classification]);
+                buffer[3..length].copy_from_slice(protection_authority);
+            }
+            Ipv4Option::Cipso { doi, tags } => {
+                buffer[0..=1].copy_from_slice(&[134, length as u8]);
+                buffer[2..=5].copy_from_slice(&doi.to_be_bytes());
+                buffer[6..length].copy_from_slice(tags);
+            }
+            Ipv4Option::StreamId { id } => {
+                buffer[0..=1].copy_from_slice(&[136, 4]);
+                buffer[2..=3].copy_from_slice(&id.to_be_bytes());
+            }
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -246,13 +354,444 @@ impl<'a> Iterator for Ipv4OptionIterator<'a> {
                 return None;
             }
             self.pos += len;
-            Some(Ipv4Option::Raw { option, data: &self.buffer[pos..(pos + len)] })
+            let data = &self.buffer[pos..(pos + len)];
+            match option {
+                0 => Some(Ipv4Option::End),
+                1 => Some(Ipv4Option::NoOp),
+                7 if len >= 3 && (len - 3) % 4 == 0 => {
+                    Some(Ipv4Option::RecordRoute { pointer: data[2], addresses: Ipv4AddressIterator::new(&data[3..]) })
+                }
+                131 if len >= 3 && (len - 3) % 4 == 0 => Some(Ipv4Option::LooseSourceRoute {
+                    pointer: data[2],
+                    addresses: Ipv4AddressIterator::new(&data[3..]),
+                }),
+                137 if len >= 3 && (len - 3) % 4 == 0 => Some(Ipv4Option::StrictSourceRoute {
+                    pointer: data[2],
+                    addresses: Ipv4AddressIterator::new(&data[3..]),
+                }),
+                68 if len >= 4
+                    && matches!(Ipv4TimestampIterator::entry_length(data[3] & 0x0F), Some(l) if (len - 4) % l == 0) =>
+                {
+                    Some(Ipv4Option::Timestamp {
+                        pointer: data[2],
+                        overflow: data[3] >> 4,
+                        flag: data[3] & 0x0F,
+                        timestamps: Ipv4TimestampIterator { buffer: &data[4..], flag: data[3] & 0x0F, pos: 0 },
+                    })
+                }
+                148 if len == 4 => {
+                    Some(Ipv4Option::RouterAlert { value: u16::from_be_bytes(data[2..=3].try_into().unwrap()) })
+                }
+                130 if len >= 3 => {
+                    Some(Ipv4Option::Security { classification: data[2], protection_authority: &data[3..] })
+                }
+                134 if len >= 6 => Some(Ipv4Option::Cipso {
+                    doi: u32::from_be_bytes(data[2..=5].try_into().unwrap()),
+                    tags: &data[6..],
+                }),
+                136 if len == 4 => {
+                    Some(Ipv4Option::StreamId { id: u16::from_be_bytes(data[2..=3].try_into().unwrap()) })
+                }
+                _ => Some(Ipv4Option::Raw { option, data }),
+            }
         } else {
             None
         }
     }
 }
 
+/// Iterator over the addresses of an IPv4 route recording or source routing option
+#[derive(Debug, Copy, Clone)]
+pub struct Ipv4AddressIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Ipv4AddressIterator<'a> {
+    pub(crate) fn new(buffer: &'a [u8]) -> Self {
+        Ipv4AddressIterator { buffer, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Ipv4AddressIterator<'a> {
+    type Item = [u8; 4];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let address = self.buffer.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(address.try_into().unwrap())
+    }
+}
+
+/// Represents an entry of an IPv4 timestamp option
+///
+/// The `address` is only present when the option's flag is `1` (timestamp and address) or `3` (prespecified
+/// addresses).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Ipv4Timestamp {
+    pub address: Option<[u8; 4]>,
+    pub timestamp: u32,
+}
+
+/// Iterator over the entries of an IPv4 timestamp option
+#[derive(Debug, Copy, Clone)]
+pub struct Ipv4TimestampIterator<'a> {
+    buffer: &'a [u8],
+    flag: u8,
+    pos: usize,
+}
+
+impl<'a> Ipv4TimestampIterator<'a> {
+    fn entry_length(flag: u8) -> Option<usize> {
+        match flag {
+            0 => Some(4),
+            1 | 3 => Some(8),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Iterator for Ipv4TimestampIterator<'a> {
+    type Item = Ipv4Timestamp;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = Ipv4TimestampIterator::entry_length(self.flag)?;
+        let entry = self.buffer.get(self.pos..self.pos + length)?;
+        self.pos += length;
+        Some(match length {
+            4 => Ipv4Timestamp { address: None, timestamp: u32::from_be_bytes(entry.try_into().unwrap()) },
+            _ => Ipv4Timestamp {
+                address: Some(entry[0..4].try_into().unwrap()),
+                timestamp: u32::from_be_bytes(entry[4..8].try_into().unwrap()),
+            },
+        })
+    }
+}
+
+/// Represents a mutable IPv4 header and payload
+#[derive(Debug)]
+pub struct Ipv4PduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+/// Contains the mutable inner payload of an [`Ipv4PduMut`]
+#[derive(Debug)]
+pub enum Ipv4Mut<'a> {
+    Raw(&'a mut [u8]),
+    Tcp(super::TcpPduMut<'a>),
+    Udp(super::UdpPduMut<'a>),
+    Icmp(super::IcmpPduMut<'a>),
+    Gre(super::GrePduMut<'a>),
+}
+
+impl<'a> Ipv4PduMut<'a> {
+    /// Constructs an [`Ipv4PduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        Ipv4Pdu::new(buffer)?;
+        Ok(Ipv4PduMut { buffer })
+    }
+
+    /// Returns a read-only [`Ipv4Pdu`] view of this PDU
+    pub fn as_pdu(&self) -> Ipv4Pdu<'_> {
+        Ipv4Pdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`Ipv4Pdu`] view of this PDU
+    pub fn into_pdu(self) -> Ipv4Pdu<'a> {
+        Ipv4Pdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<Ipv4Mut<'_>> {
+        Ipv4PduMut { buffer: self.buffer }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<Ipv4Mut<'a>> {
+        let (ihl, fragment_offset, protocol) = {
+            let pdu = self.as_pdu();
+            (pdu.computed_ihl(), pdu.fragment_offset(), pdu.protocol())
+        };
+        let rest = &mut self.buffer[ihl..];
+
+        if fragment_offset > 0 {
+            Ok(Ipv4Mut::Raw(rest))
+        } else {
+            Ok(match protocol {
+                IpProto::TCP => Ipv4Mut::Tcp(super::TcpPduMut::new(rest)?),
+                IpProto::UDP => Ipv4Mut::Udp(super::UdpPduMut::new(rest)?),
+                IpProto::ICMP => Ipv4Mut::Icmp(super::IcmpPduMut::new_icmpv4(rest)?),
+                IpProto::GRE => {
+                    if rest.len() > 1 && (rest[1] & 0x07) <= 1 {
+                        Ipv4Mut::Gre(super::GrePduMut::new(rest)?)
+                    } else {
+                        Ipv4Mut::Raw(rest)
+                    }
+                }
+                _ => Ipv4Mut::Raw(rest),
+            })
+        }
+    }
+
+    pub fn set_ihl(&mut self, ihl: u8) -> Result<()> {
+        let previous = self.buffer[0];
+        let old_ihl = self.as_pdu().computed_ihl();
+        self.buffer[0] = (previous & 0xF0) | (ihl & 0x0F);
+        let new_ihl = Ipv4Pdu::new(self.buffer).map(|pdu| pdu.computed_ihl());
+        self.buffer[0] = previous;
+        let new_ihl = new_ihl?;
+        self.rewrite(0..2, |word| word[0] = (word[0] & 0xF0) | (ihl & 0x0F));
+        // the bytes that join or leave the header are added to or removed from the header checksum
+        let zeros = [0u8; 40];
+        let checksum = self.as_pdu().checksum();
+        let checksum = if new_ihl > old_ihl {
+            util::update_checksum(checksum, &zeros[..new_ihl - old_ihl], &self.buffer[old_ihl..new_ihl])
+        } else {
+            util::update_checksum(checksum, &self.buffer[new_ihl..old_ihl], &zeros[..old_ihl - new_ihl])
+        };
+        self.set_checksum(checksum);
+        Ok(())
+    }
+
+    pub fn set_dscp(&mut self, dscp: u8) {
+        self.rewrite(0..2, |word| word[1] = (word[1] & 0x03) | (dscp << 2));
+    }
+
+    pub fn set_ecn(&mut self, ecn: u8) {
+        self.rewrite(0..2, |word| word[1] = (word[1] & 0xFC) | (ecn & 0x03));
+    }
+
+    /// Sets the total length and incrementally updates the header checksum to match
+    ///
+    /// The checksum of a TCP or UDP-Lite payload is not updated: the length is part of its pseudo-header and also
+    /// determines how much of the payload is checksummed, so it must be recomputed by the caller.
+    pub fn set_total_length(&mut self, total_length: u16) -> Result<()> {
+        if (total_length as usize) < self.as_pdu().computed_ihl() {
+            return Err(Error::Malformed);
+        }
+        self.rewrite(2..4, |word| word.copy_from_slice(&total_length.to_be_bytes()));
+        Ok(())
+    }
+
+    pub fn set_identification(&mut self, identification: u16) {
+        self.rewrite(4..6, |word| word.copy_from_slice(&identification.to_be_bytes()));
+    }
+
+    pub fn set_dont_fragment(&mut self, dont_fragment: bool) {
+        self.rewrite(6..8, |word| word[0] = (word[0] & 0xBF) | ((dont_fragment as u8) << 6));
+    }
+
+    pub fn set_more_fragments(&mut self, more_fragments: bool) {
+        self.rewrite(6..8, |word| word[0] = (word[0] & 0xDF) | ((more_fragments as u8) << 5));
+    }
+
+    pub fn set_fragment_offset(&mut self, fragment_offset: u16) {
+        let fragment_offset = fragment_offset.to_be_bytes();
+        self.rewrite(6..8, |word| {
+            word[0] = (word[0] & 0xE0) | (fragment_offset[0] & 0x1F);
+            word[1] = fragment_offset[1];
+        });
+    }
+
+    pub fn set_ttl(&mut self, ttl: u8) {
+        self.rewrite(8..10, |word| word[0] = ttl);
+    }
+
+    pub fn set_protocol(&mut self, protocol: u8) {
+        self.rewrite(8..10, |word| word[1] = protocol);
+    }
+
+    pub fn set_checksum(&mut self, checksum: u16) {
+        self.buffer[10..=11].copy_from_slice(&checksum.to_be_bytes());
+    }
+
+    pub fn set_source_address(&mut self, source_address: [u8; 4]) {
+        let previous = self.as_pdu().source_address();
+        self.rewrite(12..16, |address| address.copy_from_slice(&source_address));
+        self.update_pseudo_header(&previous, &source_address);
+    }
+
+    pub fn set_destination_address(&mut self, destination_address: [u8; 4]) {
+        let previous = self.as_pdu().destination_address();
+        self.rewrite(16..20, |address| address.copy_from_slice(&destination_address));
+        self.update_pseudo_header(&previous, &destination_address);
+    }
+
+    // rewrites part of the fixed header and incrementally updates the header checksum to match
+    fn rewrite<F: FnOnce(&mut [u8])>(&mut self, range: Range<usize>, write: F) {
+        util::rewrite(self.buffer, 10, range, write);
+    }
+
+    // incrementally updates the checksum of a tcp or udp payload to match a change to the pseudo-header
+    fn update_pseudo_header(&mut self, old: &[u8], new: &[u8]) {
+        match self.inner() {
+            Ok(Ipv4Mut::Tcp(mut tcp)) => tcp.update_pseudo_header(old, new),
+            Ok(Ipv4Mut::Udp(mut udp)) => udp.update_pseudo_header(old, new),
+            _ => {}
+        }
+    }
+}
+
+/// Builds an IPv4 header in front of a payload
+#[derive(Debug, Copy, Clone)]
+pub struct Ipv4Builder<'a> {
+    dscp: u8,
+    ecn: u8,
+    identification: u16,
+    dont_fragment: bool,
+    more_fragments: bool,
+    fragment_offset: u16,
+    ttl: u8,
+    protocol: u8,
+    source_address: [u8; 4],
+    destination_address: [u8; 4],
+    options: &'a [Ipv4Option<'a>],
+}
+
+impl<'a> Default for Ipv4Builder<'a> {
+    fn default() -> Self {
+        Ipv4Builder {
+            dscp: 0,
+            ecn: 0,
+            identification: 0,
+            dont_fragment: false,
+            more_fragments: false,
+            fragment_offset: 0,
+            ttl: 64,
+            protocol: 0,
+            source_address: [0; 4],
+            destination_address: [0; 4],
+            options: &[],
+        }
+    }
+}
+
+impl<'a> Ipv4Builder<'a> {
+    /// Constructs an [`Ipv4Builder`] with a TTL of 64 and all other fields zeroed
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn dscp(mut self, dscp: u8) -> Self {
+        self.dscp = dscp;
+        self
+    }
+
+    pub fn ecn(mut self, ecn: u8) -> Self {
+        self.ecn = ecn;
+        self
+    }
+
+    pub fn identification(mut self, identification: u16) -> Self {
+        self.identification = identification;
+        self
+    }
+
+    pub fn dont_fragment(mut self, dont_fragment: bool) -> Self {
+        self.dont_fragment = dont_fragment;
+        self
+    }
+
+    pub fn more_fragments(mut self, more_fragments: bool) -> Self {
+        self.more_fragments = more_fragments;
+        self
+    }
+
+    pub fn fragment_offset(mut self, fragment_offset: u16) -> Self {
+        self.fragment_offset = fragment_offset;
+        self
+    }
+
+    pub fn ttl(mut self, ttl: u8) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn protocol(mut self, protocol: u8) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    pub fn source_address(mut self, source_address: [u8; 4]) -> Self {
+        self.source_address = source_address;
+        self
+    }
+
+    pub fn destination_address(mut self, destination_address: [u8; 4]) -> Self {
+        self.destination_address = destination_address;
+        self
+    }
+
+    /// Sets the options to serialize after the fixed header, which will be padded to a multiple of 4 bytes
+    pub fn options(mut self, options: &'a [Ipv4Option<'a>]) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn computed_ihl(&self) -> usize {
+        let options_length: usize = self.options.iter().map(Ipv4Option::computed_length).sum();
+        20 + ((options_length + 3) & !3)
+    }
+
+    /// Serializes this header followed by `payload` into `buffer`, returning the number of bytes written
+    pub fn build(&self, buffer: &mut [u8], payload: &[u8]) -> Result<usize> {
+        let ihl = self.computed_ihl();
+        if buffer.len() < ihl + payload.len() {
+            return Err(Error::Truncated);
+        }
+        buffer[ihl..ihl + payload.len()].copy_from_slice(payload);
+        self.write_header(&mut buffer[..ihl], payload.len())?;
+        Ok(ihl + payload.len())
+    }
+
+    pub(crate) fn write_header(&self, buffer: &mut [u8], payload_length: usize) -> Result<()> {
+        let ihl = self.computed_ihl();
+        if ihl > 60 || ihl + payload_length > 0xFFFF {
+            return Err(Error::Malformed);
+        }
+        let fragment_offset = ((self.dont_fragment as u16) << 14)
+            | ((self.more_fragments as u16) << 13)
+            | (self.fragment_offset & 0x1FFF);
+        buffer[0] = 0x40 | (ihl / 4) as u8;
+        buffer[1] = (self.dscp << 2) | (self.ecn & 0x03);
+        buffer[2..=3].copy_from_slice(&((ihl + payload_length) as u16).to_be_bytes());
+        buffer[4..=5].copy_from_slice(&self.identification.to_be_bytes());
+        buffer[6..=7].copy_from_slice(&fragment_offset.to_be_bytes());
+        buffer[8] = self.ttl;
+        buffer[9] = self.protocol;
+        buffer[10..=11].copy_from_slice(&[0, 0]);
+        buffer[12..16].copy_from_slice(&self.source_address);
+        buffer[16..20].copy_from_slice(&self.destination_address);
+        let mut position = 20;
+        for option in self.options {
+            option.write(&mut buffer[position..]);
+            position += option.computed_length();
+        }
+        for byte in buffer[position..ihl].iter_mut() {
+            *byte = 0;
+        }
+        let checksum = util::checksum([&buffer[0..ihl]]);
+        buffer[10..=11].copy_from_slice(&checksum.to_be_bytes());
+        Ok(())
+    }
+}
+
 /// Represents an IPv6 header and payload
 #[derive(Debug, Copy, Clone)]
 pub struct Ipv6Pdu<'a> {
@@ -267,6 +806,18 @@ pub enum Ipv6<'a> {
     Udp(super::UdpPdu<'a>),
     Icmp(super::IcmpPdu<'a>),
     Gre(super::GrePdu<'a>),
+    Esp(super::EspPdu<'a>),
+    Sctp(super::SctpPdu<'a>),
+    UdpLite(super::UdpLitePdu<'a>),
+}
+
+// returns the length of the ipv6 extension header with the given type and header extension length field
+pub(crate) fn extension_header_length(header: u8, length: u8) -> usize {
+    match header {
+        // the authentication header length is expressed in 32-bit words, minus two (rfc4302)
+        IpProto::AH => ((length as usize) + 2) * 4,
+        _ => ((length as usize) + 1) * 8,
+    }
 }
 
 impl<'a> Ipv6Pdu<'a> {
@@ -281,12 +832,16 @@ impl<'a> Ipv6Pdu<'a> {
         }
         let mut position = 40;
         let mut next_header = buffer[6];
-        while let 0 | 43 | 44 | 59 | 60 = next_header {
+        while let 0 | 43 | 44 | 51 | 59 | 60 = next_header {
             if buffer.len() <= (position + 1) {
                 return Err(Error::Truncated);
             }
+            if next_header == IpProto::AH && buffer[position + 1] == 0 {
+                return Err(Error::Malformed);
+            }
+            let header = next_header;
             next_header = buffer[position];
-            position += ((buffer[position + 1] as usize) + 1) * 8;
+            position += extension_header_length(header, buffer[position + 1]);
         }
         if buffer.len() < position {
             return Err(Error::Truncated);
@@ -326,6 +881,8 @@ impl<'a> Ipv6Pdu<'a> {
     /// Consumes this object and returns an object representing the inner payload of this PDU
     pub fn into_inner(self) -> Result<Ipv6<'a>> {
         let rest = &self.buffer[self.computed_ihl()..];
+        // protocols without a length field of their own must not see any link-layer padding after the packet
+        let payload = rest.get(..self.computed_upper_layer_length() as usize).unwrap_or(rest);
 
         if self.computed_fragment_offset().unwrap_or_default() > 0 {
             Ok(Ipv6::Raw(rest))
@@ -333,14 +890,17 @@ impl<'a> Ipv6Pdu<'a> {
             Ok(match self.computed_protocol() {
                 IpProto::TCP => Ipv6::Tcp(super::TcpPdu::new(rest)?),
                 IpProto::UDP => Ipv6::Udp(super::UdpPdu::new(rest)?),
-                IpProto::ICMP6 => Ipv6::Icmp(super::IcmpPdu::new(rest)?),
+                IpProto::ICMP6 => Ipv6::Icmp(super::IcmpPdu::new_icmpv6(payload)?),
                 IpProto::GRE => {
-                    if rest.len() > 1 && (rest[1] & 0x07) == 0 {
+                    if rest.len() > 1 && (rest[1] & 0x07) <= 1 {
                         Ipv6::Gre(super::GrePdu::new(rest)?)
                     } else {
                         Ipv6::Raw(rest)
                     }
                 }
+                IpProto::ESP => Ipv6::Esp(super::EspPdu::new(rest)?),
+                IpProto::SCTP => Ipv6::Sctp(super::SctpPdu::new(payload)?),
+                IpProto::UDP_LITE => Ipv6::UdpLite(super::UdpLitePdu::new(payload)?),
                 _ => Ipv6::Raw(rest),
             })
         }
@@ -373,9 +933,10 @@ impl<'a> Ipv6Pdu<'a> {
     pub fn computed_ihl(&'a self) -> usize {
         let mut position = 40;
         let mut next_header = self.next_header();
-        while let 0 | 43 | 44 | 59 | 60 = next_header {
+        while let 0 | 43 | 44 | 51 | 59 | 60 = next_header {
+            let header = next_header;
             next_header = self.buffer[position];
-            position += ((self.buffer[position + 1] as usize) + 1) * 8;
+            position += extension_header_length(header, self.buffer[position + 1]);
         }
         position
     }
@@ -383,13 +944,34 @@ impl<'a> Ipv6Pdu<'a> {
     pub fn computed_protocol(&'a self) -> u8 {
         let mut position = 40;
         let mut next_header = self.next_header();
-        while let 0 | 43 | 44 | 59 | 60 = next_header {
+        while let 0 | 43 | 44 | 51 | 59 | 60 = next_header {
+            let header = next_header;
             next_header = self.buffer[position];
-            position += ((self.buffer[position + 1] as usize) + 1) * 8;
+            position += extension_header_length(header, self.buffer[position + 1]);
         }
         next_header
     }
 
+    /// Returns the length of the upper-layer payload of this PDU, excluding any extension headers, as used in the
+    /// pseudo-header of transport checksums (rfc8200)
+    ///
+    /// The length of a jumbogram is taken from its Jumbo Payload option (rfc2675).
+    pub fn computed_upper_layer_length(&'a self) -> u32 {
+        let mut payload_length = self.payload_length() as u32;
+        if payload_length == 0 {
+            for header in self.extension_headers() {
+                if let Ipv6ExtensionHeader::HopByHop { options } = header {
+                    for option in options {
+                        if let Ipv6Option::JumboPayload { length } = option {
+                            payload_length = length;
+                        }
+                    }
+                }
+            }
+        }
+        payload_length.saturating_sub((self.computed_ihl() - 40) as u32)
+    }
+
     pub fn computed_identification(&'a self) -> Option<u32> {
         for header in self.extension_headers() {
             if let Ipv6ExtensionHeader::Fragment { identification, .. } = header {
@@ -433,6 +1015,28 @@ impl<'a> Ipv6Pdu<'a> {
         destination_address
     }
 
+    /// Returns the address of the final recipient of this PDU, which is the last address of the routing header if
+    /// segments remain to be visited (rfc8200), or the destination address otherwise
+    pub fn computed_final_destination(&'a self) -> [u8; 16] {
+        for header in self.extension_headers() {
+            match header {
+                Ipv6ExtensionHeader::SegmentRouting { segments_left, mut segments, .. } if segments_left > 0 => {
+                    if let Some(segment) = segments.next() {
+                        return segment;
+                    }
+                }
+                Ipv6ExtensionHeader::Routing { routing_type: 0, segments_left, data }
+                | Ipv6ExtensionHeader::Routing { routing_type: 2, segments_left, data }
+                    if segments_left > 0 && data.len() >= 20 =>
+                {
+                    return data[data.len() - 16..].try_into().unwrap();
+                }
+                _ => {}
+            }
+        }
+        self.destination_address()
+    }
+
     pub fn extension_headers(&'a self) -> Ipv6ExtensionHeaderIterator<'a> {
         Ipv6ExtensionHeaderIterator { buffer: self.buffer, pos: 40, next_header: self.next_header() }
     }
@@ -441,8 +1045,119 @@ impl<'a> Ipv6Pdu<'a> {
 /// Represents an IPv6 extension header
 #[derive(Debug, Copy, Clone)]
 pub enum Ipv6ExtensionHeader<'a> {
-    Raw { header: u8, data: &'a [u8] },
-    Fragment { identification: u32, offset: u16, more_fragments: bool },
+    Raw {
+        header: u8,
+        data: &'a [u8],
+    },
+    Fragment {
+        identification: u32,
+        offset: u16,
+        more_fragments: bool,
+    },
+    Ah {
+        spi: u32,
+        sequence_number: u32,
+        icv: &'a [u8],
+    },
+    HopByHop {
+        options: Ipv6OptionIterator<'a>,
+    },
+    DestinationOptions {
+        options: Ipv6OptionIterator<'a>,
+    },
+    Routing {
+        routing_type: u8,
+        segments_left: u8,
+        data: &'a [u8],
+    },
+    SegmentRouting {
+        segments_left: u8,
+        last_entry: u8,
+        flags: u8,
+        tag: u16,
+        segments: Ipv6SegmentIterator<'a>,
+        tlvs: SrhTlvIterator<'a>,
+    },
+}
+
+impl<'a> Ipv6ExtensionHeader<'a> {
+    pub(crate) fn header(&self) -> u8 {
+        match self {
+            Ipv6ExtensionHeader::Raw { header, .. } => *header,
+            Ipv6ExtensionHeader::Fragment { .. } => 44,
+            Ipv6ExtensionHeader::Ah { .. } => IpProto::AH,
+            Ipv6ExtensionHeader::HopByHop { .. } => 0,
+            Ipv6ExtensionHeader::DestinationOptions { .. } => 60,
+            Ipv6ExtensionHeader::Routing { .. } | Ipv6ExtensionHeader::SegmentRouting { .. } => 43,
+        }
+    }
+
+    pub(crate) fn computed_length(&self) -> usize {
+        match self {
+            Ipv6ExtensionHeader::Raw { data, .. } => data.len(),
+            Ipv6ExtensionHeader::Fragment { .. } => 8,
+            Ipv6ExtensionHeader::Ah { icv, .. } => 12 + icv.len(),
+            Ipv6ExtensionHeader::HopByHop { options } | Ipv6ExtensionHeader::DestinationOptions { options } => {
+                2 + options.buffer.len()
+            }
+            Ipv6ExtensionHeader::Routing { data, .. } => 4 + data.len(),
+            Ipv6ExtensionHeader::SegmentRouting { segments, tlvs, .. } => 8 + segments.buffer.len() + tlvs.buffer.len(),
+        }
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8], next_header: u8) -> Result<()> {
+        match self {
+            Ipv6ExtensionHeader::Raw { data, .. } => {
+                if data.len() < 8 || data.len() % 8 != 0 || (data[1] as usize + 1) * 8 != data.len() {
+                    return Err(Error::Malformed);
+                }
+                buffer[..data.len()].copy_from_slice(data);
+            }
+            Ipv6ExtensionHeader::Fragment { identification, offset, more_fragments } => {
+                buffer[1] = 0;
+                buffer[2..=3].copy_from_slice(&((offset << 3) | (*more_fragments as u16)).to_be_bytes());
+                buffer[4..=7].copy_from_slice(&identification.to_be_bytes());
+            }
+            Ipv6ExtensionHeader::Ah { spi, sequence_number, icv } => {
+                if icv.is_empty() || (12 + icv.len()) % 8 != 0 {
+                    return Err(Error::Malformed);
+                }
+                buffer[1] = ((12 + icv.len()) / 4 - 2) as u8;
+                buffer[2..=3].copy_from_slice(&[0, 0]);
+                buffer[4..=7].copy_from_slice(&spi.to_be_bytes());
+                buffer[8..=11].copy_from_slice(&sequence_number.to_be_bytes());
+                buffer[12..12 + icv.len()].copy_from_slice(icv);
+            }
+            Ipv6ExtensionHeader::HopByHop { options } | Ipv6ExtensionHeader::DestinationOptions { options } => {
+                let length = 2 + options.buffer.len();
+                if length % 8 != 0 {
+                    return Err(Error::Malformed);
+                }
+                buffer[1] = (length / 8 - 1) as u8;
+                buffer[2..length].copy_from_slice(options.buffer);
+            }
+            Ipv6ExtensionHeader::Routing { routing_type, segments_left, data } => {
+                let length = self.computed_length();
+                if length % 8 != 0 {
+                    return Err(Error::Malformed);
+                }
+                buffer[1..=3].copy_from_slice(&[(length / 8 - 1) as u8, *routing_type, *segments_left]);
+                buffer[4..length].copy_from_slice(data);
+            }
+            Ipv6ExtensionHeader::SegmentRouting { segments_left, last_entry, flags, tag, segments, tlvs } => {
+                let length = self.computed_length();
+                if length % 8 != 0 || segments.buffer.len() != (*last_entry as usize + 1) * 16 {
+                    return Err(Error::Malformed);
+                }
+                buffer[1..=5].copy_from_slice(&[(length / 8 - 1) as u8, 4, *segments_left, *last_entry, *flags]);
+                buffer[6..=7].copy_from_slice(&tag.to_be_bytes());
+                buffer[8..8 + segments.buffer.len()].copy_from_slice(segments.buffer);
+                buffer[8 + segments.buffer.len()..length].copy_from_slice(tlvs.buffer);
+            }
+        }
+        buffer[0] = next_header;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -456,10 +1171,10 @@ impl<'a> Iterator for Ipv6ExtensionHeaderIterator<'a> {
     type Item = Ipv6ExtensionHeader<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let 0 | 43 | 44 | 59 | 60 = self.next_header {
+        if let 0 | 43 | 44 | 51 | 59 | 60 = self.next_header {
             let header = self.next_header;
             self.next_header = self.buffer[self.pos];
-            let header_length = ((self.buffer[self.pos + 1] as usize) + 1) * 8;
+            let header_length = extension_header_length(header, self.buffer[self.pos + 1]);
             let pos = self.pos;
             self.pos += header_length;
             if header == 44 && header_length == 8 {
@@ -472,6 +1187,37 @@ impl<'a> Iterator for Ipv6ExtensionHeaderIterator<'a> {
                 let offset = u16::from_be_bytes([self.buffer[pos + 2], self.buffer[pos + 3]]) >> 3;
                 let more_fragments = self.buffer[pos + 3] & 0x1 > 0;
                 Some(Ipv6ExtensionHeader::Fragment { identification, offset, more_fragments })
+            } else if header == IpProto::AH {
+                let spi = u32::from_be_bytes(self.buffer[pos + 4..pos + 8].try_into().unwrap());
+                let sequence_number = u32::from_be_bytes(self.buffer[pos + 8..pos + 12].try_into().unwrap());
+                let icv = &self.buffer[(pos + 12)..(pos + header_length)];
+                Some(Ipv6ExtensionHeader::Ah { spi, sequence_number, icv })
+            } else if header == 0 || header == 60 {
+                let options = Ipv6OptionIterator { buffer: &self.buffer[(pos + 2)..(pos + header_length)], pos: 0 };
+                if header == 0 {
+                    Some(Ipv6ExtensionHeader::HopByHop { options })
+                } else {
+                    Some(Ipv6ExtensionHeader::DestinationOptions { options })
+                }
+            } else if header == 43 {
+                let data = &self.buffer[pos..(pos + header_length)];
+                let segments_length = (data[4] as usize + 1) * 16;
+                if data[2] == 4 && 8 + segments_length <= header_length {
+                    Some(Ipv6ExtensionHeader::SegmentRouting {
+                        segments_left: data[3],
+                        last_entry: data[4],
+                        flags: data[5],
+                        tag: u16::from_be_bytes(data[6..=7].try_into().unwrap()),
+                        segments: Ipv6SegmentIterator::new(&data[8..8 + segments_length]),
+                        tlvs: SrhTlvIterator { buffer: &data[8 + segments_length..], pos: 0 },
+                    })
+                } else {
+                    Some(Ipv6ExtensionHeader::Routing {
+                        routing_type: data[2],
+                        segments_left: data[3],
+                        data: &data[4..],
+                    })
+                }
             } else {
                 Some(Ipv6ExtensionHeader::Raw { header, data: &self.buffer[pos..(pos + header_length)] })
             }
@@ -480,3 +1226,385 @@ impl<'a> Iterator for Ipv6ExtensionHeaderIterator<'a> {
         }
     }
 }
+
+/// Represents an option of an IPv6 Hop-by-Hop or Destination Options extension header
+///
+/// The `action` of a `Raw` option is the high two bits of its type, which specify how a node that does not recognize
+/// the option must process the packet (rfc8200).
+#[derive(Debug, Copy, Clone)]
+pub enum Ipv6Option<'a> {
+    Raw { option: u8, action: u8, data: &'a [u8] },
+    Pad1,
+    PadN { length: u8 },
+    RouterAlert { value: u16 },
+    JumboPayload { length: u32 },
+    TunnelEncapsulationLimit { limit: u8 },
+    Ioam { option_type: u8, data: &'a [u8] },
+}
+
+/// Iterator over the options of an IPv6 Hop-by-Hop or Destination Options extension header
+#[derive(Debug, Copy, Clone)]
+pub struct Ipv6OptionIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Ipv6OptionIterator<'a> {
+    type Item = Ipv6Option<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        let option = *self.buffer.get(pos)?;
+        if option == 0 {
+            self.pos += 1;
+            return Some(Ipv6Option::Pad1);
+        }
+        let len = *self.buffer.get(pos + 1)? as usize;
+        if self.buffer.len() < (pos + 2 + len) {
+            return None;
+        }
+        self.pos += 2 + len;
+        let data = &self.buffer[(pos + 2)..(pos + 2 + len)];
+        match option {
+            1 => Some(Ipv6Option::PadN { length: len as u8 }),
+            4 if len == 1 => Some(Ipv6Option::TunnelEncapsulationLimit { limit: data[0] }),
+            5 if len == 2 => Some(Ipv6Option::RouterAlert { value: u16::from_be_bytes(data.try_into().unwrap()) }),
+            0x31 if len >= 2 => Some(Ipv6Option::Ioam { option_type: data[1], data: &data[2..] }),
+            0xC2 if len == 4 => Some(Ipv6Option::JumboPayload { length: u32::from_be_bytes(data.try_into().unwrap()) }),
+            _ => Some(Ipv6Option::Raw { option, action: option >> 6, data: &self.buffer[pos..(pos + 2 + len)] }),
+        }
+    }
+}
+
+/// Iterator over the segment list of an IPv6 Segment Routing Header, or any other list of IPv6 addresses
+///
+/// Segments are encoded in reverse order, so the first item is the final segment of the path (rfc8754).
+#[derive(Debug, Copy, Clone)]
+pub struct Ipv6SegmentIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Ipv6SegmentIterator<'a> {
+    pub(crate) fn new(buffer: &'a [u8]) -> Self {
+        Ipv6SegmentIterator { buffer, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Ipv6SegmentIterator<'a> {
+    type Item = [u8; 16];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let segment = self.buffer.get(self.pos..self.pos + 16)?;
+        self.pos += 16;
+        Some(segment.try_into().unwrap())
+    }
+}
+
+/// Iterator over a list of IPv6 addresses, such as the sources of an MLDv2 query
+pub type Ipv6AddressIterator<'a> = Ipv6SegmentIterator<'a>;
+
+/// Represents a TLV of an IPv6 Segment Routing Header
+#[derive(Debug, Copy, Clone)]
+pub enum SrhTlv<'a> {
+    Raw { tlv_type: u8, data: &'a [u8] },
+    Pad1,
+    PadN { length: u8 },
+    Hmac { destination_verification_disabled: bool, key_id: u32, hmac: &'a [u8] },
+}
+
+/// Iterator over the TLVs of an IPv6 Segment Routing Header
+#[derive(Debug, Copy, Clone)]
+pub struct SrhTlvIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for SrhTlvIterator<'a> {
+    type Item = SrhTlv<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        let tlv_type = *self.buffer.get(pos)?;
+        if tlv_type == 0 {
+            self.pos += 1;
+            return Some(SrhTlv::Pad1);
+        }
+        let len = *self.buffer.get(pos + 1)? as usize;
+        if self.buffer.len() < (pos + 2 + len) {
+            return None;
+        }
+        self.pos += 2 + len;
+        let data = &self.buffer[(pos + 2)..(pos + 2 + len)];
+        match tlv_type {
+            4 => Some(SrhTlv::PadN { length: len as u8 }),
+            5 if len >= 6 => Some(SrhTlv::Hmac {
+                destination_verification_disabled: data[0] & 0x80 != 0,
+                key_id: u32::from_be_bytes(data[2..=5].try_into().unwrap()),
+                hmac: &data[6..],
+            }),
+            _ => Some(SrhTlv::Raw { tlv_type, data: &self.buffer[pos..(pos + 2 + len)] }),
+        }
+    }
+}
+
+/// Represents a mutable IPv6 header and payload
+#[derive(Debug)]
+pub struct Ipv6PduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+/// Contains the mutable inner payload of an [`Ipv6PduMut`]
+#[derive(Debug)]
+pub enum Ipv6Mut<'a> {
+    Raw(&'a mut [u8]),
+    Tcp(super::TcpPduMut<'a>),
+    Udp(super::UdpPduMut<'a>),
+    Icmp(super::IcmpPduMut<'a>),
+    Gre(super::GrePduMut<'a>),
+}
+
+impl<'a> Ipv6PduMut<'a> {
+    /// Constructs an [`Ipv6PduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        Ipv6Pdu::new(buffer)?;
+        Ok(Ipv6PduMut { buffer })
+    }
+
+    /// Returns a read-only [`Ipv6Pdu`] view of this PDU
+    pub fn as_pdu(&self) -> Ipv6Pdu<'_> {
+        Ipv6Pdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`Ipv6Pdu`] view of this PDU
+    pub fn into_pdu(self) -> Ipv6Pdu<'a> {
+        Ipv6Pdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<Ipv6Mut<'_>> {
+        Ipv6PduMut { buffer: self.buffer }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<Ipv6Mut<'a>> {
+        let (ihl, fragment_offset, protocol) = {
+            let pdu = self.as_pdu();
+            (pdu.computed_ihl(), pdu.computed_fragment_offset(), pdu.computed_protocol())
+        };
+        let rest = &mut self.buffer[ihl..];
+
+        if fragment_offset.unwrap_or_default() > 0 {
+            Ok(Ipv6Mut::Raw(rest))
+        } else {
+            Ok(match protocol {
+                IpProto::TCP => Ipv6Mut::Tcp(super::TcpPduMut::new(rest)?),
+                IpProto::UDP => Ipv6Mut::Udp(super::UdpPduMut::new(rest)?),
+                IpProto::ICMP6 => Ipv6Mut::Icmp(super::IcmpPduMut::new_icmpv6(rest)?),
+                IpProto::GRE => {
+                    if rest.len() > 1 && (rest[1] & 0x07) <= 1 {
+                        Ipv6Mut::Gre(super::GrePduMut::new(rest)?)
+                    } else {
+                        Ipv6Mut::Raw(rest)
+                    }
+                }
+                _ => Ipv6Mut::Raw(rest),
+            })
+        }
+    }
+
+    pub fn set_dscp(&mut self, dscp: u8) {
+        self.buffer[0] = (self.buffer[0] & 0xF0) | ((dscp >> 2) & 0x0F);
+        self.buffer[1] = (self.buffer[1] & 0x3F) | (dscp << 6);
+    }
+
+    pub fn set_ecn(&mut self, ecn: u8) {
+        self.buffer[1] = (self.buffer[1] & 0xCF) | ((ecn & 0x03) << 4);
+    }
+
+    pub fn set_flow_label(&mut self, flow_label: u32) {
+        let flow_label = flow_label.to_be_bytes();
+        self.buffer[1] = (self.buffer[1] & 0xF0) | (flow_label[1] & 0x0F);
+        self.buffer[2] = flow_label[2];
+        self.buffer[3] = flow_label[3];
+    }
+
+    /// Sets the payload length
+    ///
+    /// The checksum of a TCP, UDP-Lite, or ICMPv6 payload is not updated: the length is part of its pseudo-header and
+    /// also determines how much of the payload is checksummed, so it must be recomputed by the caller.
+    pub fn set_payload_length(&mut self, payload_length: u16) {
+        self.buffer[4..=5].copy_from_slice(&payload_length.to_be_bytes());
+    }
+
+    pub fn set_next_header(&mut self, next_header: u8) -> Result<()> {
+        let previous = self.buffer[6];
+        self.buffer[6] = next_header;
+        if let Err(e) = Ipv6Pdu::new(self.buffer) {
+            self.buffer[6] = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn set_hop_limit(&mut self, hop_limit: u8) {
+        self.buffer[7] = hop_limit;
+    }
+
+    pub fn set_source_address(&mut self, source_address: [u8; 16]) {
+        let previous = self.as_pdu().source_address();
+        self.buffer[8..24].copy_from_slice(&source_address);
+        self.update_pseudo_header(&previous, &source_address);
+    }
+
+    pub fn set_destination_address(&mut self, destination_address: [u8; 16]) {
+        let previous = self.as_pdu().computed_final_destination();
+        self.buffer[24..40].copy_from_slice(&destination_address);
+        // the pseudo-header is unaffected while a routing header determines the final destination
+        let current = self.as_pdu().computed_final_destination();
+        self.update_pseudo_header(&previous, &current);
+    }
+
+    // incrementally updates the checksum of a tcp, udp, or icmpv6 payload to match a change to the pseudo-header
+    fn update_pseudo_header(&mut self, old: &[u8], new: &[u8]) {
+        match self.inner() {
+            Ok(Ipv6Mut::Tcp(mut tcp)) => tcp.update_pseudo_header(old, new),
+            Ok(Ipv6Mut::Udp(mut udp)) => udp.update_pseudo_header(old, new),
+            Ok(Ipv6Mut::Icmp(mut icmp)) => icmp.update_pseudo_header(old, new),
+            _ => {}
+        }
+    }
+}
+
+/// Builds an IPv6 header and extension headers in front of a payload
+#[derive(Debug, Copy, Clone)]
+pub struct Ipv6Builder<'a> {
+    dscp: u8,
+    ecn: u8,
+    flow_label: u32,
+    next_header: u8,
+    hop_limit: u8,
+    source_address: [u8; 16],
+    destination_address: [u8; 16],
+    extension_headers: &'a [Ipv6ExtensionHeader<'a>],
+}
+
+impl<'a> Default for Ipv6Builder<'a> {
+    fn default() -> Self {
+        Ipv6Builder {
+            dscp: 0,
+            ecn: 0,
+            flow_label: 0,
+            next_header: 0,
+            hop_limit: 64,
+            source_address: [0; 16],
+            destination_address: [0; 16],
+            extension_headers: &[],
+        }
+    }
+}
+
+impl<'a> Ipv6Builder<'a> {
+    /// Constructs an [`Ipv6Builder`] with a hop limit of 64 and all other fields zeroed
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn dscp(mut self, dscp: u8) -> Self {
+        self.dscp = dscp;
+        self
+    }
+
+    pub fn ecn(mut self, ecn: u8) -> Self {
+        self.ecn = ecn;
+        self
+    }
+
+    pub fn flow_label(mut self, flow_label: u32) -> Self {
+        self.flow_label = flow_label;
+        self
+    }
+
+    /// Sets the protocol of the payload, which follows the last extension header
+    pub fn next_header(mut self, next_header: u8) -> Self {
+        self.next_header = next_header;
+        self
+    }
+
+    pub fn hop_limit(mut self, hop_limit: u8) -> Self {
+        self.hop_limit = hop_limit;
+        self
+    }
+
+    pub fn source_address(mut self, source_address: [u8; 16]) -> Self {
+        self.source_address = source_address;
+        self
+    }
+
+    pub fn destination_address(mut self, destination_address: [u8; 16]) -> Self {
+        self.destination_address = destination_address;
+        self
+    }
+
+    /// Sets the extension headers to serialize after the fixed header; their next header fields are chained
+    /// automatically
+    pub fn extension_headers(mut self, extension_headers: &'a [Ipv6ExtensionHeader<'a>]) -> Self {
+        self.extension_headers = extension_headers;
+        self
+    }
+
+    pub fn computed_ihl(&self) -> usize {
+        40 + self.extension_headers.iter().map(Ipv6ExtensionHeader::computed_length).sum::<usize>()
+    }
+
+    /// Serializes this header followed by `payload` into `buffer`, returning the number of bytes written
+    pub fn build(&self, buffer: &mut [u8], payload: &[u8]) -> Result<usize> {
+        let ihl = self.computed_ihl();
+        if buffer.len() < ihl + payload.len() {
+            return Err(Error::Truncated);
+        }
+        buffer[ihl..ihl + payload.len()].copy_from_slice(payload);
+        self.write_header(&mut buffer[..ihl], payload.len())?;
+        Ok(ihl + payload.len())
+    }
+
+    pub(crate) fn write_header(&self, buffer: &mut [u8], payload_length: usize) -> Result<()> {
+        let ihl = self.computed_ihl();
+        if ihl - 40 + payload_length > 0xFFFF {
+            return Err(Error::Malformed);
+        }
+        let traffic_class = (self.dscp << 2) | (self.ecn & 0x03);
+        buffer[0] = 0x60 | (traffic_class >> 4);
+        buffer[1] = (traffic_class << 4) | ((self.flow_label >> 16) as u8 & 0x0F);
+        buffer[2..=3].copy_from_slice(&(self.flow_label as u16).to_be_bytes());
+        buffer[4..=5].copy_from_slice(&((ihl - 40 + payload_length) as u16).to_be_bytes());
+        buffer[6] = self.extension_headers.first().map(Ipv6ExtensionHeader::header).unwrap_or(self.next_header);
+        buffer[7] = self.hop_limit;
+        buffer[8..24].copy_from_slice(&self.source_address);
+        buffer[24..40].copy_from_slice(&self.destination_address);
+        let mut position = 40;
+        for (i, extension_header) in self.extension_headers.iter().enumerate() {
+            let next_header =
+                self.extension_headers.get(i + 1).map(Ipv6ExtensionHeader::header).unwrap_or(self.next_header);
+            extension_header.write(&mut buffer[position..], next_header)?;
+            position += extension_header.computed_length();
+        }
+        Ok(())
+    }
+}
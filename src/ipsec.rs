@@ -0,0 +1,246 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::{TryFrom, TryInto};
+
+use crate::{Error, IpProto, Result};
+
+/// Represents an IPsec Encapsulating Security Payload (ESP) header and encrypted payload
+#[derive(Debug, Copy, Clone)]
+pub struct EspPdu<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> EspPdu<'a> {
+    /// Constructs an [`EspPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        Ok(EspPdu { buffer })
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..8]
+    }
+
+    /// Returns the encrypted payload of this PDU, including its padding, trailer and integrity check value
+    pub fn payload(&'a self) -> &'a [u8] {
+        &self.buffer[8..]
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        8
+    }
+
+    pub fn spi(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[0..=3].try_into().unwrap())
+    }
+
+    pub fn sequence_number(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap())
+    }
+}
+
+/// Represents an IPsec Authentication Header (AH) and payload
+#[derive(Debug, Copy, Clone)]
+pub struct AhPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of an [`AhPdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Ah<'a> {
+    Raw(&'a [u8]),
+    Tcp(super::TcpPdu<'a>),
+    Udp(super::UdpPdu<'a>),
+    Icmp(super::IcmpPdu<'a>),
+    Gre(super::GrePdu<'a>),
+    Esp(EspPdu<'a>),
+}
+
+impl<'a> AhPdu<'a> {
+    /// Constructs an [`AhPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 12 {
+            return Err(Error::Truncated);
+        }
+        let pdu = AhPdu { buffer };
+        if pdu.computed_ihl() < 12 {
+            return Err(Error::Malformed);
+        }
+        if buffer.len() < pdu.computed_ihl() {
+            return Err(Error::Truncated);
+        }
+        Ok(pdu)
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..self.computed_ihl()]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Ah<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Ah<'a>> {
+        let rest = &self.buffer[self.computed_ihl()..];
+        Ok(match self.next_header() {
+            IpProto::TCP => Ah::Tcp(super::TcpPdu::new(rest)?),
+            IpProto::UDP => Ah::Udp(super::UdpPdu::new(rest)?),
+            IpProto::ICMP => Ah::Icmp(super::IcmpPdu::new_icmpv4(rest)?),
+            IpProto::ICMP6 => Ah::Icmp(super::IcmpPdu::new_icmpv6(rest)?),
+            IpProto::GRE => {
+                if rest.len() > 1 && (rest[1] & 0x07) <= 1 {
+                    Ah::Gre(super::GrePdu::new(rest)?)
+                } else {
+                    Ah::Raw(rest)
+                }
+            }
+            IpProto::ESP => Ah::Esp(EspPdu::new(rest)?),
+            _ => Ah::Raw(rest),
+        })
+    }
+
+    pub fn next_header(&'a self) -> u8 {
+        self.buffer[0]
+    }
+
+    pub fn payload_length(&'a self) -> u8 {
+        self.buffer[1]
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        // the payload length is expressed in 32-bit words, minus two (rfc4302)
+        (self.payload_length() as usize + 2) * 4
+    }
+
+    pub fn spi(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap())
+    }
+
+    pub fn sequence_number(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[8..=11].try_into().unwrap())
+    }
+
+    pub fn icv(&'a self) -> &'a [u8] {
+        &self.buffer[12..self.computed_ihl()]
+    }
+}
+
+/// Builds an IPsec Authentication Header (AH) in front of a payload
+#[derive(Debug, Copy, Clone, Default)]
+pub struct AhBuilder<'a> {
+    next_header: u8,
+    spi: u32,
+    sequence_number: u32,
+    icv: &'a [u8],
+}
+
+impl<'a> AhBuilder<'a> {
+    /// Constructs an [`AhBuilder`] with all fields zeroed and an empty integrity check value
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn next_header(mut self, next_header: u8) -> Self {
+        self.next_header = next_header;
+        self
+    }
+
+    pub fn spi(mut self, spi: u32) -> Self {
+        self.spi = spi;
+        self
+    }
+
+    pub fn sequence_number(mut self, sequence_number: u32) -> Self {
+        self.sequence_number = sequence_number;
+        self
+    }
+
+    pub fn icv(mut self, icv: &'a [u8]) -> Self {
+        self.icv = icv;
+        self
+    }
+
+    pub fn computed_ihl(&self) -> usize {
+        12 + self.icv.len()
+    }
+
+    /// Serializes this header followed by `payload` into `buffer`, returning the number of bytes written
+    pub fn build(&self, buffer: &mut [u8], payload: &[u8]) -> Result<usize> {
+        let ihl = self.computed_ihl();
+        let length = ihl + payload.len();
+        if buffer.len() < length {
+            return Err(Error::Truncated);
+        }
+        self.write_header(&mut buffer[..ihl])?;
+        buffer[ihl..length].copy_from_slice(payload);
+        Ok(length)
+    }
+
+    pub(crate) fn write_header(&self, buffer: &mut [u8]) -> Result<()> {
+        let ihl = self.computed_ihl();
+        // the header must be a multiple of 32 bits long (rfc4302)
+        if ihl % 4 != 0 {
+            return Err(Error::Malformed);
+        }
+        let payload_length = u8::try_from(ihl / 4 - 2).map_err(|_| Error::Malformed)?;
+        buffer[0..=3].copy_from_slice(&[self.next_header, payload_length, 0, 0]);
+        buffer[4..=7].copy_from_slice(&self.spi.to_be_bytes());
+        buffer[8..=11].copy_from_slice(&self.sequence_number.to_be_bytes());
+        buffer[12..ihl].copy_from_slice(self.icv);
+        Ok(())
+    }
+}
@@ -20,28 +20,81 @@
 
 #![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 mod ethernet;
-pub use ethernet::{EtherType, Ethernet, EthernetPdu};
+pub use ethernet::{
+    EtherType, Ethernet, EthernetBuilder, EthernetMut, EthernetPdu, EthernetPduMut, VlanTag, VlanTagIterator,
+};
 
 mod arp;
-pub use arp::ArpPdu;
+pub use arp::{ArpPdu, ArpPduMut};
 
 mod ip;
-pub use ip::{Ip, IpProto, Ipv4, Ipv4Option, Ipv4Pdu, Ipv6, Ipv6ExtensionHeader, Ipv6Pdu};
+pub use ip::{
+    Ip, IpProto, Ipv4, Ipv4AddressIterator, Ipv4Builder, Ipv4Mut, Ipv4Option, Ipv4Pdu, Ipv4PduMut, Ipv4Timestamp,
+    Ipv4TimestampIterator, Ipv6, Ipv6AddressIterator, Ipv6Builder, Ipv6ExtensionHeader, Ipv6Mut, Ipv6Option,
+    Ipv6OptionIterator, Ipv6Pdu, Ipv6PduMut, Ipv6SegmentIterator, SrhTlv, SrhTlvIterator,
+};
+
+mod erspan;
+pub use erspan::{Erspan, ErspanPdu};
+
+mod ipsec;
+pub use ipsec::{Ah, AhBuilder, AhPdu, EspPdu};
+
+mod mpls;
+pub use mpls::{Mpls, MplsLabel, MplsLabelIterator, MplsPdu};
+
+mod ppp;
+pub use ppp::{Ppp, PppPdu, PppProtocol};
+
+mod sctp;
+pub use sctp::{
+    SctpChunk, SctpChunkIterator, SctpChunkType, SctpGapAckBlockIterator, SctpParameter, SctpParameterIterator,
+    SctpPdu, SctpTsnIterator,
+};
 
 mod tcp;
-pub use tcp::{Tcp, TcpFlag, TcpOption, TcpPdu};
+pub use tcp::{DssMapping, MptcpOption, Tcp, TcpBuilder, TcpFlag, TcpMut, TcpOption, TcpPdu, TcpPduMut};
 
 mod udp;
-pub use udp::{Udp, UdpPdu};
+pub use udp::{Udp, UdpBuilder, UdpLite, UdpLitePdu, UdpMut, UdpPdu, UdpPduMut, UdpPort};
+
+mod geneve;
+pub use geneve::{Geneve, GeneveOption, GeneveOptionIterator, GenevePdu};
+
+mod gtpu;
+pub use gtpu::{Gtpu, GtpuExtensionHeader, GtpuExtensionHeaderIterator, GtpuMessageType, GtpuPdu};
+
+mod vxlan;
+pub use vxlan::{Vxlan, VxlanPdu};
 
 mod icmp;
-pub use icmp::{Icmp, IcmpPdu};
+pub use icmp::{
+    DomainLabelIterator, DomainNameIterator, Icmp, IcmpBuilder, IcmpMut, IcmpPdu, IcmpPduMut, IcmpType, Icmpv4, Icmpv6,
+    Icmpv6Type, MulticastAddressRecord, MulticastAddressRecordIterator, NdpOption, NdpOptionIterator,
+};
+
+mod igmp;
+pub use igmp::{GroupRecord, GroupRecordIterator, Igmp, IgmpPdu, IgmpType};
 
 mod gre;
-pub use gre::{Gre, GrePdu};
+pub use gre::{Gre, GreMut, GrePdu, GrePduMut};
+
+#[cfg(feature = "alloc")]
+mod reassembly;
+#[cfg(feature = "alloc")]
+pub use reassembly::{DropReason, Ipv4Reassembler, Ipv6Reassembler, OverlapPolicy, Reassembly};
+
+mod builder;
+pub use builder::PacketBuilder;
+
+mod checksum;
+pub use checksum::{ChecksumIterator, ChecksumStatus, Layer};
 
-mod util;
+pub mod util;
 
 /// Defines the set of possible errors returned by packet parsers in this crate
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -0,0 +1,169 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{Error, Result};
+
+/// Represents an MPLS label stack and payload
+#[derive(Debug, Copy, Clone)]
+pub struct MplsPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of an [`MplsPdu`]
+///
+/// MPLS does not identify its payload, so the payload type is inferred from the first nibble following the bottom
+/// of the label stack: `4` indicates IPv4, `6` indicates IPv6, and `0` indicates a pseudowire control word followed by
+/// an Ethernet frame (rfc4448). Payloads that do not parse as the inferred type are returned as `Raw`.
+#[derive(Debug, Copy, Clone)]
+pub enum Mpls<'a> {
+    Raw(&'a [u8]),
+    Ethernet(super::EthernetPdu<'a>),
+    Ipv4(super::Ipv4Pdu<'a>),
+    Ipv6(super::Ipv6Pdu<'a>),
+}
+
+impl<'a> MplsPdu<'a> {
+    /// Constructs an [`MplsPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        let mut ihl = 0;
+        loop {
+            if buffer.len() < ihl + 4 {
+                return Err(Error::Truncated);
+            }
+            ihl += 4;
+            if buffer[ihl - 2] & 0x01 != 0 {
+                break;
+            }
+        }
+        Ok(MplsPdu { buffer })
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the label stack of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the label stack of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..self.computed_ihl()]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Mpls<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Mpls<'a>> {
+        let rest = &self.buffer[self.computed_ihl()..];
+        Ok(match rest.first().map(|b| b >> 4) {
+            Some(4) => super::Ipv4Pdu::new(rest).map(Mpls::Ipv4).unwrap_or(Mpls::Raw(rest)),
+            Some(6) => super::Ipv6Pdu::new(rest).map(Mpls::Ipv6).unwrap_or(Mpls::Raw(rest)),
+            Some(0) if rest.len() >= 4 => {
+                super::EthernetPdu::new(&rest[4..]).map(Mpls::Ethernet).unwrap_or(Mpls::Raw(rest))
+            }
+            _ => Mpls::Raw(rest),
+        })
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        self.labels().count() * 4
+    }
+
+    /// Returns an iterator over the label stack entries of this PDU, starting with the top of the stack
+    pub fn labels(&'a self) -> MplsLabelIterator<'a> {
+        MplsLabelIterator { buffer: self.buffer, pos: 0, done: false }
+    }
+
+    pub fn label(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[0..=3].try_into().unwrap()) >> 12
+    }
+
+    pub fn traffic_class(&'a self) -> u8 {
+        (self.buffer[2] >> 1) & 0x07
+    }
+
+    pub fn bottom_of_stack(&'a self) -> bool {
+        self.buffer[2] & 0x01 != 0
+    }
+
+    pub fn ttl(&'a self) -> u8 {
+        self.buffer[3]
+    }
+
+    /// Returns the pseudowire control word following the bottom of the label stack, if one is present
+    pub fn control_word(&'a self) -> Option<u32> {
+        let ihl = self.computed_ihl();
+        match self.buffer.get(ihl..ihl + 4) {
+            Some(control_word) if control_word[0] >> 4 == 0 => {
+                Some(u32::from_be_bytes(control_word.try_into().unwrap()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Represents an MPLS label stack entry
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MplsLabel {
+    pub label: u32,
+    pub traffic_class: u8,
+    pub bottom_of_stack: bool,
+    pub ttl: u8,
+}
+
+/// Iterator over the label stack entries of an [`MplsPdu`]
+#[derive(Debug, Copy, Clone)]
+pub struct MplsLabelIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    done: bool,
+}
+
+impl<'a> Iterator for MplsLabelIterator<'a> {
+    type Item = MplsLabel;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.buffer.len() < self.pos + 4 {
+            return None;
+        }
+        let entry = u32::from_be_bytes(self.buffer[self.pos..self.pos + 4].try_into().unwrap());
+        self.pos += 4;
+        let bottom_of_stack = entry & 0x100 != 0;
+        self.done = bottom_of_stack;
+        Some(MplsLabel {
+            label: entry >> 12,
+            traffic_class: ((entry >> 9) & 0x07) as u8,
+            bottom_of_stack,
+            ttl: entry as u8,
+        })
+    }
+}
@@ -0,0 +1,136 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{Error, Result};
+
+/// Provides constants representing various PPP protocol numbers supported by this crate
+#[allow(non_snake_case)]
+pub mod PppProtocol {
+    pub const IPV4: u16 = 0x0021;
+    pub const IPV6: u16 = 0x0057;
+    pub const IPCP: u16 = 0x8021;
+    pub const IPV6CP: u16 = 0x8057;
+    pub const CCP: u16 = 0x80FD;
+    pub const LCP: u16 = 0xC021;
+    pub const PAP: u16 = 0xC023;
+    pub const CHAP: u16 = 0xC223;
+}
+
+/// Represents a PPP header and payload
+#[derive(Debug, Copy, Clone)]
+pub struct PppPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of a [`PppPdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Ppp<'a> {
+    Raw(&'a [u8]),
+    Ipv4(super::Ipv4Pdu<'a>),
+    Ipv6(super::Ipv6Pdu<'a>),
+}
+
+impl<'a> PppPdu<'a> {
+    /// Constructs a [`PppPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        let pdu = PppPdu { buffer };
+        if buffer.len() < pdu.computed_ihl() {
+            return Err(Error::Truncated);
+        }
+        if pdu.protocol() & 0x0001 == 0 {
+            // protocol numbers are always odd (rfc1661)
+            return Err(Error::Malformed);
+        }
+        Ok(pdu)
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..self.computed_ihl()]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Ppp<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Ppp<'a>> {
+        let rest = &self.buffer[self.computed_ihl()..];
+        Ok(match self.protocol() {
+            PppProtocol::IPV4 => Ppp::Ipv4(super::Ipv4Pdu::new(rest)?),
+            PppProtocol::IPV6 => Ppp::Ipv6(super::Ipv6Pdu::new(rest)?),
+            _ => Ppp::Raw(rest),
+        })
+    }
+
+    pub fn computed_ihl(&'a self) -> usize {
+        let offset = if self.has_address_control() { 2 } else { 0 };
+        match self.buffer.get(offset) {
+            // the protocol field is compressed to a single byte (rfc1661)
+            Some(protocol) if protocol & 0x01 != 0 => offset + 1,
+            _ => offset + 2,
+        }
+    }
+
+    pub fn has_address_control(&'a self) -> bool {
+        self.buffer.len() >= 2 && self.buffer[0..=1] == [0xFF, 0x03]
+    }
+
+    pub fn address(&'a self) -> Option<u8> {
+        if self.has_address_control() {
+            Some(self.buffer[0])
+        } else {
+            None
+        }
+    }
+
+    pub fn control(&'a self) -> Option<u8> {
+        if self.has_address_control() {
+            Some(self.buffer[1])
+        } else {
+            None
+        }
+    }
+
+    pub fn protocol(&'a self) -> u16 {
+        let offset = if self.has_address_control() { 2 } else { 0 };
+        match self.computed_ihl() - offset {
+            1 => self.buffer[offset] as u16,
+            _ => u16::from_be_bytes(self.buffer[offset..=offset + 1].try_into().unwrap()),
+        }
+    }
+}
@@ -0,0 +1,502 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::ip::extension_header_length;
+use crate::{util, IpProto, Ipv4Pdu, Ipv6Pdu};
+
+/// Selects how a reassembler treats fragments whose data overlaps data that has already been received
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OverlapPolicy {
+    /// Keeps the data from the fragment that arrived first
+    FirstWins,
+    /// Keeps the data from the fragment that arrived last
+    LastWins,
+    /// Discards the entire datagram, as required for IPv6 by RFC 5722
+    Discard,
+}
+
+/// Describes why a reassembler dropped a fragment
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DropReason {
+    /// The fragment is empty, not a multiple of 8 bytes long, or has inconsistent headers
+    Malformed,
+    /// The fragment ends before its headers or its payload do
+    Truncated,
+    /// The fragment is too small to contain the upper-layer header (RFC 1858, RFC 7112)
+    TinyFragment,
+    /// The fragment overlaps data that has already been received, and the datagram was discarded
+    Overlap,
+    /// The fragment disagrees with the length of the datagram established by other fragments, and the datagram was
+    /// discarded
+    Inconsistent,
+    /// The reassembled datagram would exceed the maximum datagram length
+    Oversized,
+    /// Buffering the fragment would exceed the configured memory limits
+    MemoryLimit,
+}
+
+/// Describes the outcome of passing a packet to a reassembler
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Reassembly {
+    /// The packet is not a fragment and does not need to be reassembled
+    Unfragmented,
+    /// The fragment was buffered while the rest of its datagram is outstanding
+    Pending,
+    /// The fragment completed its datagram, which is returned as a contiguous buffer
+    Complete(Vec<u8>),
+    /// The fragment was dropped
+    Dropped(DropReason),
+}
+
+#[derive(Debug, Clone)]
+struct Datagram {
+    started: Duration,
+    header: Option<Vec<u8>>,
+    payload: Vec<u8>,
+    // sorted, disjoint, and non-adjacent byte ranges of the payload that have been received
+    ranges: Vec<(usize, usize)>,
+    length: Option<usize>,
+}
+
+impl Datagram {
+    fn footprint(&self) -> usize {
+        self.header.as_ref().map_or(0, Vec::len) + self.payload.len()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.header.is_some() && matches!(self.length, Some(length) if self.ranges[..] == [(0, length)])
+    }
+
+    fn insert(&mut self, offset: usize, data: &[u8], last: bool, policy: OverlapPolicy) -> Result<(), DropReason> {
+        let end = offset + data.len();
+        // teardrop protection: every fragment must agree on where the datagram ends
+        match self.length {
+            Some(length) if end > length || (last && end != length) => return Err(DropReason::Inconsistent),
+            None if last && matches!(self.ranges.last(), Some(&(_, e)) if e > end) => {
+                return Err(DropReason::Inconsistent)
+            }
+            _ => {}
+        }
+        if last {
+            self.length = Some(end);
+        }
+
+        let overlapping = self.ranges.iter().any(|&(s, e)| s < end && offset < e);
+        if overlapping {
+            // exact duplicates are dropped without discarding the datagram (rfc8200 section 4.5)
+            let duplicate =
+                self.ranges.iter().any(|&(s, e)| s <= offset && end <= e) && self.payload[offset..end] == data[..];
+            if duplicate {
+                return Ok(());
+            }
+            if policy == OverlapPolicy::Discard {
+                return Err(DropReason::Overlap);
+            }
+        }
+
+        if self.payload.len() < end {
+            self.payload.resize(end, 0);
+        }
+        if overlapping && policy == OverlapPolicy::FirstWins {
+            let mut position = offset;
+            for &(s, e) in self.ranges.iter().filter(|&&(s, e)| s < end && offset < e) {
+                if position < s {
+                    self.payload[position..s].copy_from_slice(&data[position - offset..s - offset]);
+                }
+                position = position.max(e);
+            }
+            if position < end {
+                self.payload[position..end].copy_from_slice(&data[position - offset..]);
+            }
+        } else {
+            self.payload[offset..end].copy_from_slice(data);
+        }
+
+        self.ranges.push((offset, end));
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(self.ranges.len());
+        for &(s, e) in self.ranges.iter() {
+            match merged.last_mut() {
+                Some(previous) if s <= previous.1 => previous.1 = previous.1.max(e),
+                _ => merged.push((s, e)),
+            }
+        }
+        self.ranges = merged;
+        Ok(())
+    }
+}
+
+// buffers the fragments of datagrams that share a key until they can be reassembled, independent of ip version
+#[derive(Debug, Clone)]
+struct Fragments<K> {
+    datagrams: BTreeMap<K, Datagram>,
+    bytes: usize,
+    timeout: Duration,
+    max_datagrams: usize,
+    max_bytes: usize,
+    overlap_policy: OverlapPolicy,
+}
+
+impl<K: Ord + Copy> Fragments<K> {
+    fn new(timeout: Duration, overlap_policy: OverlapPolicy) -> Self {
+        Fragments {
+            datagrams: BTreeMap::new(),
+            bytes: 0,
+            timeout,
+            max_datagrams: 1024,
+            max_bytes: 4 * 1024 * 1024,
+            overlap_policy,
+        }
+    }
+
+    fn expire(&mut self, now: Duration) -> usize {
+        let timeout = self.timeout;
+        let before = self.datagrams.len();
+        self.datagrams.retain(|_, datagram| !matches!(now.checked_sub(datagram.started), Some(age) if age >= timeout));
+        self.bytes = self.datagrams.values().map(Datagram::footprint).sum();
+        before - self.datagrams.len()
+    }
+
+    // returns the datagram once the fragment completes it
+    fn insert(
+        &mut self, key: K, now: Duration, header: Option<&[u8]>, offset: usize, data: &[u8], last: bool,
+    ) -> Result<Option<Datagram>, DropReason> {
+        self.expire(now);
+
+        if !self.datagrams.contains_key(&key) && self.datagrams.len() >= self.max_datagrams {
+            return Err(DropReason::MemoryLimit);
+        }
+        let datagram = self.datagrams.entry(key).or_insert_with(|| Datagram {
+            started: now,
+            header: None,
+            payload: Vec::new(),
+            ranges: Vec::new(),
+            length: None,
+        });
+
+        let before = datagram.footprint();
+        let header_growth = match (&datagram.header, header) {
+            (None, Some(header)) => header.len(),
+            _ => 0,
+        };
+        let growth = (offset + data.len()).saturating_sub(datagram.payload.len()) + header_growth;
+        if self.bytes + growth > self.max_bytes {
+            if datagram.ranges.is_empty() {
+                self.datagrams.remove(&key);
+            }
+            return Err(DropReason::MemoryLimit);
+        }
+
+        if let Err(reason) = datagram.insert(offset, data, last, self.overlap_policy) {
+            self.bytes -= before;
+            self.datagrams.remove(&key);
+            return Err(reason);
+        }
+        if let (None, Some(header)) = (&datagram.header, header) {
+            datagram.header = Some(header.to_vec());
+        }
+        self.bytes = self.bytes - before + datagram.footprint();
+
+        if !datagram.is_complete() {
+            return Ok(None);
+        }
+        match self.datagrams.remove(&key) {
+            Some(datagram) => {
+                self.bytes -= datagram.footprint();
+                Ok(Some(datagram))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Reassembles fragmented IPv4 datagrams (RFC 791)
+///
+/// Fragments are keyed by source address, destination address, protocol, and identification. The caller supplies a
+/// monotonic timestamp with each fragment, which is used to expire datagrams that are not completed within the
+/// configured timeout.
+#[derive(Debug, Clone)]
+pub struct Ipv4Reassembler {
+    fragments: Fragments<([u8; 4], [u8; 4], u8, u16)>,
+}
+
+impl Default for Ipv4Reassembler {
+    fn default() -> Self {
+        Ipv4Reassembler { fragments: Fragments::new(Duration::from_secs(30), OverlapPolicy::FirstWins) }
+    }
+}
+
+impl Ipv4Reassembler {
+    /// Constructs an [`Ipv4Reassembler`] with a 30 second timeout, room for 1024 datagrams or 4 MiB of buffered data,
+    /// and the [`OverlapPolicy::FirstWins`] overlap policy
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.fragments.timeout = timeout;
+        self
+    }
+
+    pub fn max_datagrams(mut self, max_datagrams: usize) -> Self {
+        self.fragments.max_datagrams = max_datagrams;
+        self
+    }
+
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.fragments.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn overlap_policy(mut self, overlap_policy: OverlapPolicy) -> Self {
+        self.fragments.overlap_policy = overlap_policy;
+        self
+    }
+
+    /// Returns the number of datagrams that are awaiting fragments
+    pub fn len(&self) -> usize {
+        self.fragments.datagrams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragments.datagrams.is_empty()
+    }
+
+    /// Returns the number of bytes that are buffered for incomplete datagrams
+    pub fn computed_bytes(&self) -> usize {
+        self.fragments.bytes
+    }
+
+    /// Discards incomplete datagrams whose first fragment arrived at least `timeout` before `now`, returning the number
+    /// of datagrams that were discarded
+    pub fn expire(&mut self, now: Duration) -> usize {
+        self.fragments.expire(now)
+    }
+
+    /// Buffers the fragment contained in `pdu`, which arrived at `now`, returning the reassembled datagram once all of
+    /// its fragments have arrived
+    pub fn reassemble(&mut self, pdu: &Ipv4Pdu, now: Duration) -> Reassembly {
+        if !pdu.more_fragments() && pdu.fragment_offset() == 0 {
+            return Reassembly::Unfragmented;
+        }
+
+        let ihl = pdu.computed_ihl();
+        let total_length = pdu.total_length() as usize;
+        if pdu.buffer().len() < total_length {
+            return Reassembly::Dropped(DropReason::Truncated);
+        }
+        let data = &pdu.buffer()[ihl..total_length];
+        let offset = pdu.computed_fragment_offset() as usize;
+        // every fragment but the last must carry a non-zero multiple of 8 bytes
+        if pdu.more_fragments() && (data.is_empty() || data.len() % 8 != 0) {
+            return Reassembly::Dropped(DropReason::Malformed);
+        }
+        if ihl + offset + data.len() > 0xFFFF {
+            return Reassembly::Dropped(DropReason::Oversized);
+        }
+        // tiny fragment attacks split or overwrite the tcp header to evade filtering (rfc1858 section 3.2)
+        if pdu.protocol() == IpProto::TCP
+            && (pdu.fragment_offset() == 1 || (offset == 0 && pdu.more_fragments() && data.len() < 20))
+        {
+            return Reassembly::Dropped(DropReason::TinyFragment);
+        }
+
+        let key = (pdu.source_address(), pdu.destination_address(), pdu.protocol(), pdu.identification());
+        let header = if offset == 0 { Some(pdu.as_bytes()) } else { None };
+        match self.fragments.insert(key, now, header, offset, data, !pdu.more_fragments()) {
+            Ok(Some(Datagram { header: Some(header), payload, .. })) => {
+                if header.len() + payload.len() > 0xFFFF {
+                    return Reassembly::Dropped(DropReason::Oversized);
+                }
+                let mut buffer = header;
+                let ihl = buffer.len();
+                buffer[2..=3].copy_from_slice(&((ihl + payload.len()) as u16).to_be_bytes());
+                // preserve the don't fragment flag, but clear the more fragments flag and the fragment offset
+                buffer[6] &= 0x40;
+                buffer[7] = 0;
+                buffer[10..=11].copy_from_slice(&[0, 0]);
+                let checksum = util::checksum([&buffer[..]]);
+                buffer[10..=11].copy_from_slice(&checksum.to_be_bytes());
+                buffer.extend_from_slice(&payload);
+                Reassembly::Complete(buffer)
+            }
+            Ok(_) => Reassembly::Pending,
+            Err(reason) => Reassembly::Dropped(reason),
+        }
+    }
+}
+
+/// Reassembles fragmented IPv6 packets (RFC 8200)
+///
+/// Fragments are keyed by source address, destination address, and identification. Overlapping fragments always
+/// discard the entire packet (RFC 5722), and atomic fragments are never buffered (RFC 6946). The reassembled packet
+/// consists of the unfragmentable part of the first fragment followed by the fragmentable part of every fragment.
+#[derive(Debug, Clone)]
+pub struct Ipv6Reassembler {
+    fragments: Fragments<([u8; 16], [u8; 16], u32)>,
+}
+
+impl Default for Ipv6Reassembler {
+    fn default() -> Self {
+        Ipv6Reassembler { fragments: Fragments::new(Duration::from_secs(60), OverlapPolicy::Discard) }
+    }
+}
+
+impl Ipv6Reassembler {
+    /// Constructs an [`Ipv6Reassembler`] with a 60 second timeout and room for 1024 packets or 4 MiB of buffered data
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.fragments.timeout = timeout;
+        self
+    }
+
+    pub fn max_datagrams(mut self, max_datagrams: usize) -> Self {
+        self.fragments.max_datagrams = max_datagrams;
+        self
+    }
+
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.fragments.max_bytes = max_bytes;
+        self
+    }
+
+    /// Returns the number of packets that are awaiting fragments
+    pub fn len(&self) -> usize {
+        self.fragments.datagrams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragments.datagrams.is_empty()
+    }
+
+    /// Returns the number of bytes that are buffered for incomplete packets
+    pub fn computed_bytes(&self) -> usize {
+        self.fragments.bytes
+    }
+
+    /// Discards incomplete packets whose first fragment arrived at least `timeout` before `now`, returning the number
+    /// of packets that were discarded
+    pub fn expire(&mut self, now: Duration) -> usize {
+        self.fragments.expire(now)
+    }
+
+    /// Buffers the fragment contained in `pdu`, which arrived at `now`, returning the reassembled packet once all of
+    /// its fragments have arrived
+    pub fn reassemble(&mut self, pdu: &Ipv6Pdu, now: Duration) -> Reassembly {
+        let (identification, offset, more_fragments) =
+            match (pdu.computed_identification(), pdu.computed_fragment_offset(), pdu.computed_more_fragments()) {
+                (Some(identification), Some(offset), Some(more_fragments)) => {
+                    (identification, offset as usize, more_fragments)
+                }
+                _ => return Reassembly::Unfragmented,
+            };
+        // atomic fragments are processed in isolation from any other fragments (rfc6946)
+        if offset == 0 && !more_fragments {
+            return Reassembly::Unfragmented;
+        }
+
+        // locate the fragment header and the next header field that refers to it
+        let buffer = pdu.buffer();
+        let mut field = 6;
+        let mut position = 40;
+        loop {
+            // the type of the header at `position` is stored in the next header field of the previous header
+            let header = match buffer.get(field) {
+                Some(&header) => header,
+                None => return Reassembly::Dropped(DropReason::Truncated),
+            };
+            match header {
+                44 => break,
+                0 | 43 | 51 | 60 => {}
+                _ => return Reassembly::Dropped(DropReason::Malformed),
+            }
+            let length = match buffer.get(position + 1) {
+                Some(&length) => extension_header_length(header, length),
+                None => return Reassembly::Dropped(DropReason::Truncated),
+            };
+            field = position;
+            position += length;
+        }
+        // jumbograms cannot be fragmented (rfc2675)
+        if pdu.payload_length() == 0 {
+            return Reassembly::Dropped(DropReason::Malformed);
+        }
+        let end = 40 + pdu.payload_length() as usize;
+        if buffer.len() < end || end < position + 8 {
+            return Reassembly::Dropped(DropReason::Truncated);
+        }
+        let data = &buffer[position + 8..end];
+        // every fragment but the last must carry a non-zero multiple of 8 bytes
+        if more_fragments && (data.is_empty() || data.len() % 8 != 0) {
+            return Reassembly::Dropped(DropReason::Malformed);
+        }
+        if position - 40 + offset + data.len() > 0xFFFF {
+            return Reassembly::Dropped(DropReason::Oversized);
+        }
+
+        let mut unfragmentable = None;
+        if offset == 0 {
+            // the first fragment must contain the entire header chain, including the upper-layer header (rfc7112)
+            let mut next_header = buffer[position];
+            let mut chain = 0;
+            while let 0 | 43 | 44 | 51 | 60 = next_header {
+                if data.len() <= chain + 1 {
+                    return Reassembly::Dropped(DropReason::TinyFragment);
+                }
+                let length = extension_header_length(next_header, data[chain + 1]);
+                next_header = data[chain];
+                chain += length;
+            }
+            let minimum = match next_header {
+                IpProto::TCP => 20,
+                IpProto::UDP | IpProto::ICMP6 => 8,
+                _ => 0,
+            };
+            if data.len() < chain + minimum {
+                return Reassembly::Dropped(DropReason::TinyFragment);
+            }
+            // the unfragmentable part is followed directly by the header that followed the fragment header
+            let mut header = buffer[..position].to_vec();
+            header[field] = buffer[position];
+            unfragmentable = Some(header);
+        }
+
+        let key = (pdu.source_address(), pdu.destination_address(), identification);
+        match self.fragments.insert(key, now, unfragmentable.as_deref(), offset, data, !more_fragments) {
+            Ok(Some(Datagram { header: Some(header), payload, .. })) => {
+                if header.len() - 40 + payload.len() > 0xFFFF {
+                    return Reassembly::Dropped(DropReason::Oversized);
+                }
+                let mut buffer = header;
+                let payload_length = buffer.len() - 40 + payload.len();
+                buffer[4..=5].copy_from_slice(&(payload_length as u16).to_be_bytes());
+                buffer.extend_from_slice(&payload);
+                Reassembly::Complete(buffer)
+            }
+            Ok(_) => Reassembly::Pending,
+            Err(reason) => Reassembly::Dropped(reason),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{util, ChecksumStatus, Error, Result};
+
+/// Provides constants representing various SCTP chunk types supported by this crate
+#[allow(non_snake_case)]
+pub mod SctpChunkType {
+    pub const DATA: u8 = 0;
+    pub const INIT: u8 = 1;
+    pub const INIT_ACK: u8 = 2;
+    pub const SACK: u8 = 3;
+    pub const HEARTBEAT: u8 = 4;
+    pub const HEARTBEAT_ACK: u8 = 5;
+    pub const ABORT: u8 = 6;
+    pub const SHUTDOWN: u8 = 7;
+    pub const SHUTDOWN_ACK: u8 = 8;
+    pub const COOKIE_ECHO: u8 = 10;
+    pub const COOKIE_ACK: u8 = 11;
+    pub const SHUTDOWN_COMPLETE: u8 = 14;
+}
+
+/// Represents an SCTP common header and its chunks
+#[derive(Debug, Copy, Clone)]
+pub struct SctpPdu<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> SctpPdu<'a> {
+    /// Constructs an [`SctpPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 12 {
+            return Err(Error::Truncated);
+        }
+        Ok(SctpPdu { buffer })
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..12]
+    }
+
+    pub fn source_port(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[0..=1].try_into().unwrap())
+    }
+
+    pub fn destination_port(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[2..=3].try_into().unwrap())
+    }
+
+    pub fn verification_tag(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[4..8].try_into().unwrap())
+    }
+
+    pub fn checksum(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[8..12].try_into().unwrap())
+    }
+
+    /// Returns the CRC32c checksum of this PDU in the same byte order as [`SctpPdu::checksum`]
+    ///
+    /// SCTP does not use a pseudo-header, and transmits the CRC32c checksum least significant byte first (rfc9260
+    /// appendix A).
+    pub fn computed_checksum(&'a self) -> u32 {
+        util::crc32c([&self.buffer[0..8], &[0u8; 4][..], &self.buffer[12..]]).swap_bytes()
+    }
+
+    pub fn verify_checksum(&'a self) -> ChecksumStatus {
+        if self.checksum() == self.computed_checksum() {
+            ChecksumStatus::Good
+        } else {
+            ChecksumStatus::Bad
+        }
+    }
+
+    pub fn chunks(&'a self) -> SctpChunkIterator<'a> {
+        SctpChunkIterator { buffer: self.buffer, pos: 12 }
+    }
+}
+
+/// Represents a chunk of an SCTP packet (rfc9260)
+#[derive(Debug, Copy, Clone)]
+pub enum SctpChunk<'a> {
+    Raw {
+        chunk_type: u8,
+        flags: u8,
+        data: &'a [u8],
+    },
+    Data {
+        immediate: bool,
+        unordered: bool,
+        beginning: bool,
+        ending: bool,
+        tsn: u32,
+        stream_identifier: u16,
+        stream_sequence_number: u16,
+        payload_protocol_identifier: u32,
+        data: &'a [u8],
+    },
+    Init {
+        initiate_tag: u32,
+        advertised_receiver_window_credit: u32,
+        outbound_streams: u16,
+        inbound_streams: u16,
+        initial_tsn: u32,
+        parameters: SctpParameterIterator<'a>,
+    },
+    InitAck {
+        initiate_tag: u32,
+        advertised_receiver_window_credit: u32,
+        outbound_streams: u16,
+        inbound_streams: u16,
+        initial_tsn: u32,
+        parameters: SctpParameterIterator<'a>,
+    },
+    Sack {
+        cumulative_tsn_ack: u32,
+        advertised_receiver_window_credit: u32,
+        gap_ack_blocks: SctpGapAckBlockIterator<'a>,
+        duplicate_tsns: SctpTsnIterator<'a>,
+    },
+    Heartbeat {
+        info: &'a [u8],
+    },
+    HeartbeatAck {
+        info: &'a [u8],
+    },
+    Abort {
+        /// The T bit, which is set when the verification tag is reflected from the packet being aborted
+        reflected: bool,
+        causes: &'a [u8],
+    },
+    Shutdown {
+        cumulative_tsn_ack: u32,
+    },
+    ShutdownAck,
+    ShutdownComplete {
+        reflected: bool,
+    },
+    CookieEcho {
+        cookie: &'a [u8],
+    },
+    CookieAck,
+}
+
+/// Iterator over the chunks of an [`SctpPdu`]
+///
+/// Iteration stops at the first chunk whose length is shorter than its header or that extends beyond the end of the
+/// packet.
+#[derive(Debug, Copy, Clone)]
+pub struct SctpChunkIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for SctpChunkIterator<'a> {
+    type Item = SctpChunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        let header = self.buffer.get(pos..pos + 4)?;
+        let (chunk_type, flags) = (header[0], header[1]);
+        let len = u16::from_be_bytes(header[2..=3].try_into().unwrap()) as usize;
+        if len < 4 || self.buffer.len() < (pos + len) {
+            return None;
+        }
+        // chunks are padded to a multiple of 4 bytes, but the padding of the last chunk may be omitted
+        self.pos += (len + 3) & !3;
+        let data = &self.buffer[(pos + 4)..(pos + len)];
+        let u16_at = |i: usize| u16::from_be_bytes(data[i..i + 2].try_into().unwrap());
+        let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        Some(match chunk_type {
+            SctpChunkType::DATA if data.len() >= 12 => SctpChunk::Data {
+                immediate: flags & 0x08 != 0,
+                unordered: flags & 0x04 != 0,
+                beginning: flags & 0x02 != 0,
+                ending: flags & 0x01 != 0,
+                tsn: u32_at(0),
+                stream_identifier: u16_at(4),
+                stream_sequence_number: u16_at(6),
+                payload_protocol_identifier: u32_at(8),
+                data: &data[12..],
+            },
+            SctpChunkType::INIT if data.len() >= 16 => SctpChunk::Init {
+                initiate_tag: u32_at(0),
+                advertised_receiver_window_credit: u32_at(4),
+                outbound_streams: u16_at(8),
+                inbound_streams: u16_at(10),
+                initial_tsn: u32_at(12),
+                parameters: SctpParameterIterator { buffer: &data[16..], pos: 0 },
+            },
+            SctpChunkType::INIT_ACK if data.len() >= 16 => SctpChunk::InitAck {
+                initiate_tag: u32_at(0),
+                advertised_receiver_window_credit: u32_at(4),
+                outbound_streams: u16_at(8),
+                inbound_streams: u16_at(10),
+                initial_tsn: u32_at(12),
+                parameters: SctpParameterIterator { buffer: &data[16..], pos: 0 },
+            },
+            SctpChunkType::SACK if data.len() >= 12 => {
+                let gap_ack_blocks_length = u16_at(8) as usize * 4;
+                let duplicate_tsns_length = u16_at(10) as usize * 4;
+                match data.get(12 + gap_ack_blocks_length..12 + gap_ack_blocks_length + duplicate_tsns_length) {
+                    Some(duplicate_tsns) => SctpChunk::Sack {
+                        cumulative_tsn_ack: u32_at(0),
+                        advertised_receiver_window_credit: u32_at(4),
+                        gap_ack_blocks: SctpGapAckBlockIterator {
+                            buffer: &data[12..12 + gap_ack_blocks_length],
+                            pos: 0,
+                        },
+                        duplicate_tsns: SctpTsnIterator { buffer: duplicate_tsns, pos: 0 },
+                    },
+                    None => SctpChunk::Raw { chunk_type, flags, data },
+                }
+            }
+            SctpChunkType::HEARTBEAT => SctpChunk::Heartbeat { info: data },
+            SctpChunkType::HEARTBEAT_ACK => SctpChunk::HeartbeatAck { info: data },
+            SctpChunkType::ABORT => SctpChunk::Abort { reflected: flags & 0x01 != 0, causes: data },
+            SctpChunkType::SHUTDOWN if data.len() >= 4 => SctpChunk::Shutdown { cumulative_tsn_ack: u32_at(0) },
+            SctpChunkType::SHUTDOWN_ACK => SctpChunk::ShutdownAck,
+            SctpChunkType::COOKIE_ECHO => SctpChunk::CookieEcho { cookie: data },
+            SctpChunkType::COOKIE_ACK => SctpChunk::CookieAck,
+            SctpChunkType::SHUTDOWN_COMPLETE => SctpChunk::ShutdownComplete { reflected: flags & 0x01 != 0 },
+            _ => SctpChunk::Raw { chunk_type, flags, data },
+        })
+    }
+}
+
+/// Represents a parameter of an SCTP INIT or INIT ACK chunk
+#[derive(Debug, Copy, Clone)]
+pub enum SctpParameter<'a> {
+    Raw { parameter_type: u16, data: &'a [u8] },
+    Ipv4Address { address: [u8; 4] },
+    Ipv6Address { address: [u8; 16] },
+    StateCookie { cookie: &'a [u8] },
+    CookiePreservative { increment: u32 },
+    HostName { name: &'a [u8] },
+    SupportedAddressTypes { types: &'a [u8] },
+}
+
+/// Iterator over the parameters of an SCTP INIT or INIT ACK chunk
+#[derive(Debug, Copy, Clone)]
+pub struct SctpParameterIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for SctpParameterIterator<'a> {
+    type Item = SctpParameter<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos;
+        let header = self.buffer.get(pos..pos + 4)?;
+        let parameter_type = u16::from_be_bytes(header[0..=1].try_into().unwrap());
+        let len = u16::from_be_bytes(header[2..=3].try_into().unwrap()) as usize;
+        if len < 4 || self.buffer.len() < (pos + len) {
+            return None;
+        }
+        self.pos += (len + 3) & !3;
+        let data = &self.buffer[(pos + 4)..(pos + len)];
+        Some(match parameter_type {
+            5 if data.len() == 4 => SctpParameter::Ipv4Address { address: data.try_into().unwrap() },
+            6 if data.len() == 16 => SctpParameter::Ipv6Address { address: data.try_into().unwrap() },
+            7 => SctpParameter::StateCookie { cookie: data },
+            9 if data.len() == 4 => {
+                SctpParameter::CookiePreservative { increment: u32::from_be_bytes(data.try_into().unwrap()) }
+            }
+            11 => SctpParameter::HostName { name: data },
+            12 => SctpParameter::SupportedAddressTypes { types: data },
+            _ => SctpParameter::Raw { parameter_type, data },
+        })
+    }
+}
+
+/// Iterator over the gap ack blocks of an SCTP SACK chunk, as (start, end) offsets from the cumulative TSN ack
+#[derive(Debug, Copy, Clone)]
+pub struct SctpGapAckBlockIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for SctpGapAckBlockIterator<'a> {
+    type Item = (u16, u16);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = self.buffer.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some((
+            u16::from_be_bytes(block[0..=1].try_into().unwrap()),
+            u16::from_be_bytes(block[2..=3].try_into().unwrap()),
+        ))
+    }
+}
+
+/// Iterator over the duplicate TSNs of an SCTP SACK chunk
+#[derive(Debug, Copy, Clone)]
+pub struct SctpTsnIterator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for SctpTsnIterator<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tsn = self.buffer.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(u32::from_be_bytes(tsn.try_into().unwrap()))
+    }
+}
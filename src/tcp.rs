@@ -17,8 +17,9 @@
 */
 
 use core::convert::TryInto;
+use core::ops::Range;
 
-use crate::{util, Error, Result};
+use crate::{util, ChecksumStatus, Error, Result};
 
 /// Provides constants representing the set of TCP bitflags
 #[allow(non_snake_case)]
@@ -160,26 +161,32 @@ impl<'a> TcpPdu<'a> {
     }
 
     pub fn computed_checksum(&'a self, ip: &crate::Ip) -> u16 {
+        // any link-layer padding after the segment is excluded from the checksum
+        let end = |length: usize| length.clamp(18, self.buffer.len());
         match ip {
             crate::Ip::Ipv4(ipv4) => util::checksum(&[
                 &ipv4.source_address().as_ref(),
                 &ipv4.destination_address().as_ref(),
-                &[0x00, ipv4.protocol()].as_ref(),
-                &(ipv4.total_length() as usize - ipv4.computed_ihl()).to_be_bytes().as_ref(),
+                &[0x00, crate::IpProto::TCP].as_ref(),
+                &ipv4.computed_upper_layer_length().to_be_bytes().as_ref(),
                 &self.buffer[0..=15],
-                &self.buffer[18..],
+                &self.buffer[18..end(ipv4.computed_upper_layer_length() as usize)],
             ]),
             crate::Ip::Ipv6(ipv6) => util::checksum(&[
                 &ipv6.source_address().as_ref(),
-                &ipv6.destination_address().as_ref(),
-                &(ipv6.payload_length() as u32).to_be_bytes().as_ref(),
+                &ipv6.computed_final_destination().as_ref(),
+                &ipv6.computed_upper_layer_length().to_be_bytes().as_ref(),
                 &[0x0, 0x0, 0x0, ipv6.computed_protocol()].as_ref(),
                 &self.buffer[0..=15],
-                &self.buffer[18..],
+                &self.buffer[18..end(ipv6.computed_upper_layer_length() as usize)],
             ]),
         }
     }
 
+    pub fn verify_checksum(&'a self, ip: &crate::Ip) -> ChecksumStatus {
+        ChecksumStatus::from_checksums(self.checksum(), self.computed_checksum(ip))
+    }
+
     pub fn urgent_pointer(&'a self) -> u16 {
         u16::from_be_bytes(self.buffer[18..=19].try_into().unwrap())
     }
@@ -189,16 +196,546 @@ impl<'a> TcpPdu<'a> {
     }
 }
 
+/// Represents a mutable TCP header and payload
+#[derive(Debug)]
+pub struct TcpPduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+/// Contains the mutable inner payload of a [`TcpPduMut`]
+#[derive(Debug)]
+pub enum TcpMut<'a> {
+    Raw(&'a mut [u8]),
+}
+
+impl<'a> TcpPduMut<'a> {
+    /// Constructs a [`TcpPduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        TcpPdu::new(buffer)?;
+        Ok(TcpPduMut { buffer })
+    }
+
+    /// Returns a read-only [`TcpPdu`] view of this PDU
+    pub fn as_pdu(&self) -> TcpPdu<'_> {
+        TcpPdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`TcpPdu`] view of this PDU
+    pub fn into_pdu(self) -> TcpPdu<'a> {
+        TcpPdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<TcpMut<'_>> {
+        TcpPduMut { buffer: self.buffer }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<TcpMut<'a>> {
+        let data_offset = self.as_pdu().computed_data_offset();
+        Ok(TcpMut::Raw(&mut self.buffer[data_offset..]))
+    }
+
+    pub fn set_source_port(&mut self, source_port: u16) {
+        self.rewrite(0..2, |word| word.copy_from_slice(&source_port.to_be_bytes()));
+    }
+
+    pub fn set_destination_port(&mut self, destination_port: u16) {
+        self.rewrite(2..4, |word| word.copy_from_slice(&destination_port.to_be_bytes()));
+    }
+
+    pub fn set_sequence_number(&mut self, sequence_number: u32) {
+        self.rewrite(4..8, |words| words.copy_from_slice(&sequence_number.to_be_bytes()));
+    }
+
+    pub fn set_acknowledgement_number(&mut self, acknowledgement_number: u32) {
+        self.rewrite(8..12, |words| words.copy_from_slice(&acknowledgement_number.to_be_bytes()));
+    }
+
+    pub fn set_data_offset(&mut self, data_offset: u8) -> Result<()> {
+        let previous = self.buffer[12];
+        self.buffer[12] = (self.buffer[12] & 0x0F) | (data_offset << 4);
+        if let Err(e) = TcpPdu::new(self.buffer) {
+            self.buffer[12] = previous;
+            return Err(e);
+        }
+        let current = self.buffer[12];
+        self.buffer[12] = previous;
+        self.rewrite(12..14, |word| word[0] = current);
+        Ok(())
+    }
+
+    pub fn set_flags(&mut self, flags: u8) {
+        self.rewrite(12..14, |word| word[1] = flags);
+    }
+
+    fn set_flag(&mut self, flag: u8, value: bool) {
+        self.rewrite(12..14, |word| {
+            if value {
+                word[1] |= flag;
+            } else {
+                word[1] &= !flag;
+            }
+        });
+    }
+
+    pub fn set_fin(&mut self, fin: bool) {
+        self.set_flag(TcpFlag::FIN, fin);
+    }
+
+    pub fn set_syn(&mut self, syn: bool) {
+        self.set_flag(TcpFlag::SYN, syn);
+    }
+
+    pub fn set_rst(&mut self, rst: bool) {
+        self.set_flag(TcpFlag::RST, rst);
+    }
+
+    pub fn set_psh(&mut self, psh: bool) {
+        self.set_flag(TcpFlag::PSH, psh);
+    }
+
+    pub fn set_ack(&mut self, ack: bool) {
+        self.set_flag(TcpFlag::ACK, ack);
+    }
+
+    pub fn set_urg(&mut self, urg: bool) {
+        self.set_flag(TcpFlag::URG, urg);
+    }
+
+    pub fn set_ecn(&mut self, ecn: bool) {
+        self.set_flag(TcpFlag::ECN, ecn);
+    }
+
+    pub fn set_cwr(&mut self, cwr: bool) {
+        self.set_flag(TcpFlag::CWR, cwr);
+    }
+
+    pub fn set_window_size(&mut self, window_size: u16) {
+        self.rewrite(14..16, |word| word.copy_from_slice(&window_size.to_be_bytes()));
+    }
+
+    pub fn set_checksum(&mut self, checksum: u16) {
+        self.buffer[16..=17].copy_from_slice(&checksum.to_be_bytes());
+    }
+
+    pub fn set_urgent_pointer(&mut self, urgent_pointer: u16) {
+        self.rewrite(18..20, |word| word.copy_from_slice(&urgent_pointer.to_be_bytes()));
+    }
+
+    // rewrites part of the header and incrementally updates the checksum to match
+    fn rewrite<F: FnOnce(&mut [u8])>(&mut self, range: Range<usize>, write: F) {
+        util::rewrite(self.buffer, 16, range, write);
+    }
+
+    pub(crate) fn update_pseudo_header(&mut self, old: &[u8], new: &[u8]) {
+        let checksum = util::update_checksum(self.as_pdu().checksum(), old, new);
+        self.set_checksum(checksum);
+    }
+}
+
 /// Represents a TCP option
 #[derive(Debug, Copy, Clone)]
 pub enum TcpOption<'a> {
-    Raw { option: u8, data: &'a [u8] },
+    Raw {
+        option: u8,
+        data: &'a [u8],
+    },
     NoOp,
-    Mss { size: u16 },
-    WindowScale { shift: u8 },
+    Mss {
+        size: u16,
+    },
+    WindowScale {
+        shift: u8,
+    },
     SackPermitted,
-    Sack { blocks: [Option<(u32, u32)>; 4] },
-    Timestamp { val: u32, ecr: u32 },
+    Sack {
+        blocks: [Option<(u32, u32)>; 4],
+    },
+    Timestamp {
+        val: u32,
+        ecr: u32,
+    },
+    Md5Signature {
+        digest: &'a [u8],
+    },
+    /// The user timeout in seconds, or in minutes when `granularity` is set (rfc5482)
+    UserTimeout {
+        granularity: bool,
+        timeout: u16,
+    },
+    Authentication {
+        key_id: u8,
+        rnext_key_id: u8,
+        mac: &'a [u8],
+    },
+    Mptcp(MptcpOption<'a>),
+    /// A TCP Fast Open cookie, or a cookie request when `cookie` is empty (rfc7413)
+    FastOpen {
+        cookie: &'a [u8],
+    },
+    /// A TCP Fast Open cookie carried in the shared experimental option with magic number 0xF989 (rfc6994)
+    FastOpenExperimental {
+        cookie: &'a [u8],
+    },
+}
+
+impl<'a> TcpOption<'a> {
+    pub(crate) fn computed_length(&self) -> usize {
+        match self {
+            TcpOption::Raw { data, .. } => data.len(),
+            TcpOption::NoOp => 1,
+            TcpOption::Mss { .. } => 4,
+            TcpOption::WindowScale { .. } => 3,
+            TcpOption::SackPermitted => 2,
+            TcpOption::Sack { blocks } => 2 + blocks.iter().filter(|block| block.is_some()).count() * 8,
+            TcpOption::Timestamp { .. } => 10,
+            TcpOption::Md5Signature { digest } => 2 + digest.len(),
+            TcpOption::UserTimeout { .. } => 4,
+            TcpOption::Authentication { mac, .. } => 4 + mac.len(),
+            TcpOption::Mptcp(option) => option.computed_length(),
+            TcpOption::FastOpen { cookie } => 2 + cookie.len(),
+            TcpOption::FastOpenExperimental { cookie } => 4 + cookie.len(),
+        }
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8]) {
+        match self {
+            TcpOption::Raw { data, .. } => buffer[..data.len()].copy_from_slice(data),
+            TcpOption::NoOp => buffer[0] = 1,
+            TcpOption::Mss { size } => {
+                buffer[0..=1].copy_from_slice(&[2, 4]);
+                buffer[2..=3].copy_from_slice(&size.to_be_bytes());
+            }
+            TcpOption::WindowScale { shift } => buffer[0..=2].copy_from_slice(&[3, 3, *shift]),
+            TcpOption::SackPermitted => buffer[0..=1].copy_from_slice(&[4, 2]),
+            TcpOption::Sack { blocks } => {
+                buffer[0..=1].copy_from_slice(&[5, self.computed_length() as u8]);
+                let mut position = 2;
+                for (left, right) in blocks.iter().flatten() {
+                    buffer[position..position + 4].copy_from_slice(&left.to_be_bytes());
+                    buffer[position + 4..position + 8].copy_from_slice(&right.to_be_bytes());
+                    position += 8;
+                }
+            }
+            TcpOption::Timestamp { val, ecr } => {
+                buffer[0..=1].copy_from_slice(&[8, 10]);
+                buffer[2..=5].copy_from_slice(&val.to_be_bytes());
+                buffer[6..=9].copy_from_slice(&ecr.to_be_bytes());
+            }
+            TcpOption::Md5Signature { digest } => {
+                buffer[0..=1].copy_from_slice(&[19, self.computed_length() as u8]);
+                buffer[2..2 + digest.len()].copy_from_slice(digest);
+            }
+            TcpOption::UserTimeout { granularity, timeout } => {
+                let value = (*timeout & 0x7FFF) | if *granularity { 0x8000 } else { 0 };
+                buffer[0..=1].copy_from_slice(&[28, 4]);
+                buffer[2..=3].copy_from_slice(&value.to_be_bytes());
+            }
+            TcpOption::Authentication { key_id, rnext_key_id, mac } => {
+                buffer[0..=3].copy_from_slice(&[29, self.computed_length() as u8, *key_id, *rnext_key_id]);
+                buffer[4..4 + mac.len()].copy_from_slice(mac);
+            }
+            TcpOption::Mptcp(option) => option.write(buffer),
+            TcpOption::FastOpen { cookie } => {
+                buffer[0..=1].copy_from_slice(&[34, self.computed_length() as u8]);
+                buffer[2..2 + cookie.len()].copy_from_slice(cookie);
+            }
+            TcpOption::FastOpenExperimental { cookie } => {
+                buffer[0..=3].copy_from_slice(&[254, self.computed_length() as u8, 0xF9, 0x89]);
+                buffer[4..4 + cookie.len()].copy_from_slice(cookie);
+            }
+        }
+    }
+}
+
+/// Represents a Multipath TCP option (rfc8684)
+#[derive(Debug, Copy, Clone)]
+pub enum MptcpOption<'a> {
+    Raw {
+        subtype: u8,
+        data: &'a [u8],
+    },
+    /// Sent in the SYN, SYN/ACK, and ACK of the initial subflow, where each message carries more of the optional
+    /// fields than the last
+    MpCapable {
+        version: u8,
+        flags: u8,
+        sender_key: Option<u64>,
+        receiver_key: Option<u64>,
+        data_level_length: Option<u16>,
+        checksum: Option<u16>,
+    },
+    MpJoinSyn {
+        backup: bool,
+        address_id: u8,
+        receiver_token: u32,
+        sender_random_number: u32,
+    },
+    MpJoinSynAck {
+        backup: bool,
+        address_id: u8,
+        sender_truncated_hmac: u64,
+        sender_random_number: u32,
+    },
+    MpJoinAck {
+        sender_hmac: &'a [u8],
+    },
+    /// A data sequence signal, where `flags` determines which fields are present and whether the data ACK and data
+    /// sequence number are 4 or 8 octets wide on the wire
+    Dss {
+        flags: u8,
+        data_ack: Option<u64>,
+        mapping: Option<DssMapping>,
+    },
+    /// Advertises an additional IPv4 or IPv6 `address` of the sender, which is 4 or 16 bytes long respectively
+    AddAddr {
+        echo: bool,
+        address_id: u8,
+        address: &'a [u8],
+        port: Option<u16>,
+        truncated_hmac: Option<u64>,
+    },
+    RemoveAddr {
+        address_ids: &'a [u8],
+    },
+    MpPrio {
+        backup: bool,
+        address_id: Option<u8>,
+    },
+    MpFail {
+        data_sequence_number: u64,
+    },
+    MpFastclose {
+        receiver_key: u64,
+    },
+}
+
+/// Represents the data sequence mapping of an [`MptcpOption::Dss`] option
+#[derive(Debug, Copy, Clone)]
+pub struct DssMapping {
+    pub data_sequence_number: u64,
+    pub subflow_sequence_number: u32,
+    pub data_level_length: u16,
+    pub checksum: Option<u16>,
+}
+
+impl<'a> MptcpOption<'a> {
+    // decodes a kind 30 option, including its kind and length octets
+    fn parse(data: &'a [u8]) -> Self {
+        let subtype = data[2] >> 4;
+        let u16_at = |i: usize| u16::from_be_bytes(data[i..i + 2].try_into().unwrap());
+        let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_be_bytes(data[i..i + 8].try_into().unwrap());
+        match (subtype, data.len()) {
+            (0, 4) | (0, 12) | (0, 20) | (0, 22) | (0, 24) => MptcpOption::MpCapable {
+                version: data[2] & 0x0F,
+                flags: data[3],
+                sender_key: if data.len() >= 12 { Some(u64_at(4)) } else { None },
+                receiver_key: if data.len() >= 20 { Some(u64_at(12)) } else { None },
+                data_level_length: if data.len() >= 22 { Some(u16_at(20)) } else { None },
+                checksum: if data.len() >= 24 { Some(u16_at(22)) } else { None },
+            },
+            (1, 12) => MptcpOption::MpJoinSyn {
+                backup: data[2] & 0x01 != 0,
+                address_id: data[3],
+                receiver_token: u32_at(4),
+                sender_random_number: u32_at(8),
+            },
+            (1, 16) => MptcpOption::MpJoinSynAck {
+                backup: data[2] & 0x01 != 0,
+                address_id: data[3],
+                sender_truncated_hmac: u64_at(4),
+                sender_random_number: u32_at(12),
+            },
+            (1, 24) => MptcpOption::MpJoinAck { sender_hmac: &data[4..] },
+            (2, len) if len >= 4 => {
+                let flags = data[3];
+                let data_ack_length = match flags & 0x03 {
+                    0x01 => 4,
+                    0x03 => 8,
+                    _ => 0,
+                };
+                let sequence_number_length = match flags & 0x0C {
+                    0x04 => 4,
+                    0x0C => 8,
+                    _ => 0,
+                };
+                let mapping_length = if sequence_number_length > 0 { sequence_number_length + 6 } else { 0 };
+                let checksum = match len - 4 {
+                    rest if rest == data_ack_length + mapping_length => false,
+                    rest if rest == data_ack_length + mapping_length + 2 && mapping_length > 0 => true,
+                    _ => return MptcpOption::Raw { subtype, data },
+                };
+                let data_ack = match data_ack_length {
+                    4 => Some(u32_at(4) as u64),
+                    8 => Some(u64_at(4)),
+                    _ => None,
+                };
+                let pos = 4 + data_ack_length;
+                let mapping = match sequence_number_length {
+                    4 | 8 => Some(DssMapping {
+                        data_sequence_number: if sequence_number_length == 8 {
+                            u64_at(pos)
+                        } else {
+                            u32_at(pos) as u64
+                        },
+                        subflow_sequence_number: u32_at(pos + sequence_number_length),
+                        data_level_length: u16_at(pos + sequence_number_length + 4),
+                        checksum: if checksum { Some(u16_at(pos + sequence_number_length + 6)) } else { None },
+                    }),
+                    _ => None,
+                };
+                MptcpOption::Dss { flags, data_ack, mapping }
+            }
+            (3, len) if [8, 10, 16, 18, 20, 22, 28, 30].contains(&len) => {
+                let echo = data[2] & 0x01 != 0;
+                // the address family is implied by the length, as the port and truncated hmac add 2 and 8 bytes
+                let address_length = if len < 20 { 4 } else { 16 };
+                let rest = len - 4 - address_length;
+                let port_length = if rest == 2 || rest == 10 { 2 } else { 0 };
+                MptcpOption::AddAddr {
+                    echo,
+                    address_id: data[3],
+                    address: &data[4..4 + address_length],
+                    port: if port_length == 2 { Some(u16_at(4 + address_length)) } else { None },
+                    truncated_hmac: if rest >= 8 { Some(u64_at(4 + address_length + port_length)) } else { None },
+                }
+            }
+            (4, len) if len >= 4 => MptcpOption::RemoveAddr { address_ids: &data[3..] },
+            (5, 3) => MptcpOption::MpPrio { backup: data[2] & 0x01 != 0, address_id: None },
+            (5, 4) => MptcpOption::MpPrio { backup: data[2] & 0x01 != 0, address_id: Some(data[3]) },
+            (6, 12) => MptcpOption::MpFail { data_sequence_number: u64_at(4) },
+            (7, 12) => MptcpOption::MpFastclose { receiver_key: u64_at(4) },
+            _ => MptcpOption::Raw { subtype, data },
+        }
+    }
+
+    pub(crate) fn computed_length(&self) -> usize {
+        match self {
+            MptcpOption::Raw { data, .. } => data.len(),
+            MptcpOption::MpCapable { sender_key, receiver_key, data_level_length, checksum, .. } => {
+                4 + sender_key.map_or(0, |_| 8)
+                    + receiver_key.map_or(0, |_| 8)
+                    + data_level_length.map_or(0, |_| 2)
+                    + checksum.map_or(0, |_| 2)
+            }
+            MptcpOption::MpJoinSyn { .. } => 12,
+            MptcpOption::MpJoinSynAck { .. } => 16,
+            MptcpOption::MpJoinAck { sender_hmac } => 4 + sender_hmac.len(),
+            MptcpOption::Dss { flags, data_ack, mapping } => {
+                let width = |wide: bool| if wide { 8 } else { 4 };
+                4 + data_ack.map_or(0, |_| width(flags & 0x02 != 0))
+                    + mapping.map_or(0, |mapping| width(flags & 0x08 != 0) + 6 + mapping.checksum.map_or(0, |_| 2))
+            }
+            MptcpOption::AddAddr { address, port, truncated_hmac, .. } => {
+                4 + address.len() + port.map_or(0, |_| 2) + truncated_hmac.map_or(0, |_| 8)
+            }
+            MptcpOption::RemoveAddr { address_ids } => 3 + address_ids.len(),
+            MptcpOption::MpPrio { address_id, .. } => 3 + address_id.map_or(0, |_| 1),
+            MptcpOption::MpFail { .. } | MptcpOption::MpFastclose { .. } => 12,
+        }
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8]) {
+        let length = self.computed_length();
+        if let MptcpOption::Raw { data, .. } = self {
+            buffer[..length].copy_from_slice(data);
+            return;
+        }
+        buffer[0..=1].copy_from_slice(&[30, length as u8]);
+        let mut position = 4;
+        let mut put = |buffer: &mut [u8], bytes: &[u8]| {
+            buffer[position..position + bytes.len()].copy_from_slice(bytes);
+            position += bytes.len();
+        };
+        match self {
+            MptcpOption::Raw { .. } => {}
+            MptcpOption::MpCapable { version, flags, sender_key, receiver_key, data_level_length, checksum } => {
+                buffer[2..=3].copy_from_slice(&[*version & 0x0F, *flags]);
+                sender_key.iter().for_each(|key| put(buffer, &key.to_be_bytes()));
+                receiver_key.iter().for_each(|key| put(buffer, &key.to_be_bytes()));
+                data_level_length.iter().for_each(|length| put(buffer, &length.to_be_bytes()));
+                checksum.iter().for_each(|checksum| put(buffer, &checksum.to_be_bytes()));
+            }
+            MptcpOption::MpJoinSyn { backup, address_id, receiver_token, sender_random_number } => {
+                buffer[2..=3].copy_from_slice(&[0x10 | *backup as u8, *address_id]);
+                put(buffer, &receiver_token.to_be_bytes());
+                put(buffer, &sender_random_number.to_be_bytes());
+            }
+            MptcpOption::MpJoinSynAck { backup, address_id, sender_truncated_hmac, sender_random_number } => {
+                buffer[2..=3].copy_from_slice(&[0x10 | *backup as u8, *address_id]);
+                put(buffer, &sender_truncated_hmac.to_be_bytes());
+                put(buffer, &sender_random_number.to_be_bytes());
+            }
+            MptcpOption::MpJoinAck { sender_hmac } => {
+                buffer[2..=3].copy_from_slice(&[0x10, 0x00]);
+                put(buffer, sender_hmac);
+            }
+            MptcpOption::Dss { flags, data_ack, mapping } => {
+                // the presence bits are derived from the fields, and the width bits are kept from `flags`
+                let mut flags = *flags & 0x1A;
+                if let Some(data_ack) = data_ack {
+                    flags |= 0x01;
+                    match flags & 0x02 {
+                        0 => put(buffer, &(*data_ack as u32).to_be_bytes()),
+                        _ => put(buffer, &data_ack.to_be_bytes()),
+                    }
+                }
+                if let Some(mapping) = mapping {
+                    flags |= 0x04;
+                    match flags & 0x08 {
+                        0 => put(buffer, &(mapping.data_sequence_number as u32).to_be_bytes()),
+                        _ => put(buffer, &mapping.data_sequence_number.to_be_bytes()),
+                    }
+                    put(buffer, &mapping.subflow_sequence_number.to_be_bytes());
+                    put(buffer, &mapping.data_level_length.to_be_bytes());
+                    mapping.checksum.iter().for_each(|checksum| put(buffer, &checksum.to_be_bytes()));
+                }
+                buffer[2..=3].copy_from_slice(&[0x20, flags]);
+            }
+            MptcpOption::AddAddr { echo, address_id, address, port, truncated_hmac } => {
+                buffer[2..=3].copy_from_slice(&[0x30 | *echo as u8, *address_id]);
+                put(buffer, address);
+                port.iter().for_each(|port| put(buffer, &port.to_be_bytes()));
+                truncated_hmac.iter().for_each(|hmac| put(buffer, &hmac.to_be_bytes()));
+            }
+            MptcpOption::RemoveAddr { address_ids } => {
+                buffer[2] = 0x40;
+                buffer[3..3 + address_ids.len()].copy_from_slice(address_ids);
+            }
+            MptcpOption::MpPrio { backup, address_id } => {
+                buffer[2] = 0x50 | *backup as u8;
+                if let Some(address_id) = address_id {
+                    buffer[3] = *address_id;
+                }
+            }
+            MptcpOption::MpFail { data_sequence_number } => {
+                buffer[2..=3].copy_from_slice(&[0x60, 0x00]);
+                put(buffer, &data_sequence_number.to_be_bytes());
+            }
+            MptcpOption::MpFastclose { receiver_key } => {
+                buffer[2..=3].copy_from_slice(&[0x70, 0x00]);
+                put(buffer, &receiver_key.to_be_bytes());
+            }
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -306,6 +843,21 @@ impl<'a> Iterator for TcpOptionIterator<'a> {
                     val: u32::from_be_bytes(self.buffer[pos + 2..=pos + 5].try_into().unwrap()),
                     ecr: u32::from_be_bytes(self.buffer[pos + 6..=pos + 9].try_into().unwrap()),
                 }),
+                19 if len == 18 => Some(TcpOption::Md5Signature { digest: &self.buffer[pos + 2..pos + len] }),
+                28 if len == 4 => {
+                    let value = u16::from_be_bytes(self.buffer[pos + 2..=pos + 3].try_into().unwrap());
+                    Some(TcpOption::UserTimeout { granularity: value & 0x8000 != 0, timeout: value & 0x7FFF })
+                }
+                29 if len >= 4 => Some(TcpOption::Authentication {
+                    key_id: self.buffer[pos + 2],
+                    rnext_key_id: self.buffer[pos + 3],
+                    mac: &self.buffer[pos + 4..pos + len],
+                }),
+                30 if len >= 3 => Some(TcpOption::Mptcp(MptcpOption::parse(&self.buffer[pos..(pos + len)]))),
+                34 => Some(TcpOption::FastOpen { cookie: &self.buffer[pos + 2..pos + len] }),
+                254 if len >= 4 && self.buffer[pos + 2..=pos + 3] == [0xF9, 0x89] => {
+                    Some(TcpOption::FastOpenExperimental { cookie: &self.buffer[pos + 4..pos + len] })
+                }
                 _ => Some(TcpOption::Raw { option, data: &self.buffer[pos..(pos + len)] }),
             }
         } else {
@@ -313,3 +865,126 @@ impl<'a> Iterator for TcpOptionIterator<'a> {
         }
     }
 }
+
+/// Builds a TCP header in front of a payload
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TcpBuilder<'a> {
+    source_port: u16,
+    destination_port: u16,
+    sequence_number: u32,
+    acknowledgement_number: u32,
+    flags: u8,
+    window_size: u16,
+    urgent_pointer: u16,
+    options: &'a [TcpOption<'a>],
+}
+
+impl<'a> TcpBuilder<'a> {
+    /// Constructs a [`TcpBuilder`] with all fields zeroed
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn source_port(mut self, source_port: u16) -> Self {
+        self.source_port = source_port;
+        self
+    }
+
+    pub fn destination_port(mut self, destination_port: u16) -> Self {
+        self.destination_port = destination_port;
+        self
+    }
+
+    pub fn sequence_number(mut self, sequence_number: u32) -> Self {
+        self.sequence_number = sequence_number;
+        self
+    }
+
+    pub fn acknowledgement_number(mut self, acknowledgement_number: u32) -> Self {
+        self.acknowledgement_number = acknowledgement_number;
+        self
+    }
+
+    /// Sets the TCP bitflags (see [`TcpFlag`])
+    pub fn flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn window_size(mut self, window_size: u16) -> Self {
+        self.window_size = window_size;
+        self
+    }
+
+    pub fn urgent_pointer(mut self, urgent_pointer: u16) -> Self {
+        self.urgent_pointer = urgent_pointer;
+        self
+    }
+
+    /// Sets the options to serialize after the fixed header, which will be padded to a multiple of 4 bytes
+    pub fn options(mut self, options: &'a [TcpOption<'a>]) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn computed_data_offset(&self) -> usize {
+        let options_length: usize = self.options.iter().map(TcpOption::computed_length).sum();
+        20 + ((options_length + 3) & !3)
+    }
+
+    /// Serializes this header followed by `payload` into `buffer`, returning the number of bytes written
+    ///
+    /// The checksum is computed using the pseudo-header of the provided `ip` PDU.
+    pub fn build(&self, buffer: &mut [u8], ip: &crate::Ip, payload: &[u8]) -> Result<usize> {
+        let data_offset = self.computed_data_offset();
+        let length = data_offset + payload.len();
+        if buffer.len() < length {
+            return Err(Error::Truncated);
+        }
+        buffer[data_offset..length].copy_from_slice(payload);
+        self.write(&mut buffer[..length], ip)?;
+        Ok(length)
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8], ip: &crate::Ip) -> Result<()> {
+        let data_offset = self.computed_data_offset();
+        if data_offset > 60 {
+            return Err(Error::Malformed);
+        }
+        buffer[0..=1].copy_from_slice(&self.source_port.to_be_bytes());
+        buffer[2..=3].copy_from_slice(&self.destination_port.to_be_bytes());
+        buffer[4..=7].copy_from_slice(&self.sequence_number.to_be_bytes());
+        buffer[8..=11].copy_from_slice(&self.acknowledgement_number.to_be_bytes());
+        buffer[12] = ((data_offset / 4) as u8) << 4;
+        buffer[13] = self.flags;
+        buffer[14..=15].copy_from_slice(&self.window_size.to_be_bytes());
+        buffer[16..=17].copy_from_slice(&[0, 0]);
+        buffer[18..=19].copy_from_slice(&self.urgent_pointer.to_be_bytes());
+        let mut position = 20;
+        for option in self.options {
+            option.write(&mut buffer[position..]);
+            position += option.computed_length();
+        }
+        for byte in buffer[position..data_offset].iter_mut() {
+            *byte = 0;
+        }
+        let checksum = match ip {
+            crate::Ip::Ipv4(ipv4) => util::checksum([
+                ipv4.source_address().as_ref(),
+                ipv4.destination_address().as_ref(),
+                [0x00, crate::IpProto::TCP].as_ref(),
+                (buffer.len() as u16).to_be_bytes().as_ref(),
+                &buffer[..],
+            ]),
+            crate::Ip::Ipv6(ipv6) => util::checksum([
+                ipv6.source_address().as_ref(),
+                ipv6.computed_final_destination().as_ref(),
+                (buffer.len() as u32).to_be_bytes().as_ref(),
+                [0x0, 0x0, 0x0, crate::IpProto::TCP].as_ref(),
+                &buffer[..],
+            ]),
+        };
+        buffer[16..=17].copy_from_slice(&checksum.to_be_bytes());
+        Ok(())
+    }
+}
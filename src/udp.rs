@@ -17,8 +17,17 @@
 */
 
 use core::convert::TryInto;
+use core::ops::Range;
 
-use crate::{util, Error, Result};
+use crate::{util, ChecksumStatus, Error, Result};
+
+/// Provides constants representing the well-known UDP ports of tunneling protocols supported by this crate
+#[allow(non_snake_case)]
+pub mod UdpPort {
+    pub const VXLAN: u16 = 4789;
+    pub const GENEVE: u16 = 6081;
+    pub const GTPU: u16 = 2152;
+}
 
 /// Represents a UDP header and payload
 #[derive(Debug, Copy, Clone)]
@@ -30,6 +39,9 @@ pub struct UdpPdu<'a> {
 #[derive(Debug, Copy, Clone)]
 pub enum Udp<'a> {
     Raw(&'a [u8]),
+    Vxlan(super::VxlanPdu<'a>),
+    Geneve(super::GenevePdu<'a>),
+    Gtpu(super::GtpuPdu<'a>),
 }
 
 impl<'a> UdpPdu<'a> {
@@ -71,7 +83,22 @@ impl<'a> UdpPdu<'a> {
     /// Consumes this object and returns an object representing the inner payload of this PDU
     pub fn into_inner(self) -> Result<Udp<'a>> {
         let rest = &self.buffer[8..];
-        Ok(Udp::Raw(rest))
+        Ok(match self.destination_port() {
+            UdpPort::VXLAN => Udp::Vxlan(super::VxlanPdu::new(rest)?),
+            UdpPort::GENEVE => Udp::Geneve(super::GenevePdu::new(rest)?),
+            UdpPort::GTPU => Udp::Gtpu(super::GtpuPdu::new(rest)?),
+            _ => Udp::Raw(rest),
+        })
+    }
+
+    /// Returns the payload of this PDU as VXLAN regardless of its destination port
+    pub fn vxlan(&'a self) -> Result<super::VxlanPdu<'a>> {
+        (*self).into_vxlan()
+    }
+
+    /// Consumes this object and returns the payload of this PDU as VXLAN regardless of its destination port
+    pub fn into_vxlan(self) -> Result<super::VxlanPdu<'a>> {
+        super::VxlanPdu::new(&self.buffer[8..])
     }
 
     pub fn source_port(&'a self) -> u16 {
@@ -91,26 +118,323 @@ impl<'a> UdpPdu<'a> {
     }
 
     pub fn computed_checksum(&'a self, ip: &crate::Ip) -> u16 {
+        // any link-layer padding after the datagram is excluded from the checksum
+        let payload = &self.buffer[8..(self.length() as usize).clamp(8, self.buffer.len())];
         match ip {
             crate::Ip::Ipv4(ipv4) => util::checksum(&[
                 &ipv4.source_address().as_ref(),
                 &ipv4.destination_address().as_ref(),
-                &[0x00, ipv4.protocol()].as_ref(),
+                &[0x00, crate::IpProto::UDP].as_ref(),
                 &self.length().to_be_bytes().as_ref(),
                 &self.buffer[0..=5],
-                &self.buffer[8..],
+                payload,
             ]),
             crate::Ip::Ipv6(ipv6) => util::checksum(&[
                 &ipv6.source_address().as_ref(),
-                &ipv6.destination_address().as_ref(),
+                &ipv6.computed_final_destination().as_ref(),
                 &(self.length() as u32).to_be_bytes().as_ref(),
                 &[0x0, 0x0, 0x0, ipv6.computed_protocol()].as_ref(),
                 &self.buffer[0..=5],
-                &self.buffer[8..],
+                payload,
             ]),
         }
     }
 
+    pub fn verify_checksum(&'a self, ip: &crate::Ip) -> ChecksumStatus {
+        match (self.checksum(), ip) {
+            // a checksum of zero means that no checksum was computed (rfc768), which is only permitted over ipv4
+            (0, crate::Ip::Ipv4(_)) => ChecksumStatus::NotChecked,
+            (0, crate::Ip::Ipv6(_)) => ChecksumStatus::Bad,
+            (checksum, ip) => ChecksumStatus::from_checksums(checksum, self.computed_checksum(ip)),
+        }
+    }
+
+    pub fn computed_data_offset(&'a self) -> usize {
+        8
+    }
+}
+
+/// Represents a mutable UDP header and payload
+#[derive(Debug)]
+pub struct UdpPduMut<'a> {
+    buffer: &'a mut [u8],
+}
+
+/// Contains the mutable inner payload of a [`UdpPduMut`]
+#[derive(Debug)]
+pub enum UdpMut<'a> {
+    Raw(&'a mut [u8]),
+}
+
+impl<'a> UdpPduMut<'a> {
+    /// Constructs a [`UdpPduMut`] backed by the provided `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self> {
+        UdpPdu::new(buffer)?;
+        Ok(UdpPduMut { buffer })
+    }
+
+    /// Returns a read-only [`UdpPdu`] view of this PDU
+    pub fn as_pdu(&self) -> UdpPdu<'_> {
+        UdpPdu { buffer: self.buffer }
+    }
+
+    /// Consumes this object and returns a read-only [`UdpPdu`] view of this PDU
+    pub fn into_pdu(self) -> UdpPdu<'a> {
+        UdpPdu { buffer: self.buffer }
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// Returns a mutable reference to the entire underlying buffer that was provided during construction
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a mutable reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Returns an object representing the mutable inner payload of this PDU
+    pub fn inner(&mut self) -> Result<UdpMut<'_>> {
+        UdpPduMut { buffer: self.buffer }.into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the mutable inner payload of this PDU
+    pub fn into_inner(self) -> Result<UdpMut<'a>> {
+        Ok(UdpMut::Raw(&mut self.buffer[8..]))
+    }
+
+    pub fn set_source_port(&mut self, source_port: u16) {
+        self.rewrite(0..2, |word| word.copy_from_slice(&source_port.to_be_bytes()));
+    }
+
+    pub fn set_destination_port(&mut self, destination_port: u16) {
+        self.rewrite(2..4, |word| word.copy_from_slice(&destination_port.to_be_bytes()));
+    }
+
+    pub fn set_length(&mut self, length: u16) {
+        let previous = self.as_pdu().length();
+        self.rewrite(4..6, |word| word.copy_from_slice(&length.to_be_bytes()));
+        // the length is also part of the pseudo-header
+        self.update_pseudo_header(&previous.to_be_bytes(), &length.to_be_bytes());
+    }
+
+    pub fn set_checksum(&mut self, checksum: u16) {
+        self.buffer[6..=7].copy_from_slice(&checksum.to_be_bytes());
+    }
+
+    // rewrites part of the header and incrementally updates the checksum to match
+    fn rewrite<F: FnOnce(&mut [u8])>(&mut self, range: Range<usize>, write: F) {
+        if self.as_pdu().checksum() == 0 {
+            // no checksum was computed (rfc768)
+            write(&mut self.buffer[range]);
+        } else if util::rewrite(self.buffer, 6, range, write) == 0 {
+            self.set_checksum(0xFFFF);
+        }
+    }
+
+    pub(crate) fn update_pseudo_header(&mut self, old: &[u8], new: &[u8]) {
+        match self.as_pdu().checksum() {
+            0 => {}
+            checksum => match util::update_checksum(checksum, old, new) {
+                0 => self.set_checksum(0xFFFF),
+                checksum => self.set_checksum(checksum),
+            },
+        }
+    }
+}
+
+/// Builds a UDP header in front of a payload
+#[derive(Debug, Copy, Clone, Default)]
+pub struct UdpBuilder {
+    source_port: u16,
+    destination_port: u16,
+}
+
+impl UdpBuilder {
+    /// Constructs a [`UdpBuilder`] with all fields zeroed
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn source_port(mut self, source_port: u16) -> Self {
+        self.source_port = source_port;
+        self
+    }
+
+    pub fn destination_port(mut self, destination_port: u16) -> Self {
+        self.destination_port = destination_port;
+        self
+    }
+
+    pub fn computed_data_offset(&self) -> usize {
+        8
+    }
+
+    /// Serializes this header followed by `payload` into `buffer`, returning the number of bytes written
+    ///
+    /// The checksum is computed using the pseudo-header of the provided `ip` PDU.
+    pub fn build(&self, buffer: &mut [u8], ip: &crate::Ip, payload: &[u8]) -> Result<usize> {
+        let length = 8 + payload.len();
+        if buffer.len() < length {
+            return Err(Error::Truncated);
+        }
+        buffer[8..length].copy_from_slice(payload);
+        self.write(&mut buffer[..length], ip)?;
+        Ok(length)
+    }
+
+    pub(crate) fn write(&self, buffer: &mut [u8], ip: &crate::Ip) -> Result<()> {
+        if buffer.len() > 0xFFFF {
+            return Err(Error::Malformed);
+        }
+        let length = buffer.len() as u16;
+        buffer[0..=1].copy_from_slice(&self.source_port.to_be_bytes());
+        buffer[2..=3].copy_from_slice(&self.destination_port.to_be_bytes());
+        buffer[4..=5].copy_from_slice(&length.to_be_bytes());
+        buffer[6..=7].copy_from_slice(&[0, 0]);
+        let checksum = match ip {
+            crate::Ip::Ipv4(ipv4) => util::checksum([
+                ipv4.source_address().as_ref(),
+                ipv4.destination_address().as_ref(),
+                [0x00, crate::IpProto::UDP].as_ref(),
+                length.to_be_bytes().as_ref(),
+                &buffer[..],
+            ]),
+            crate::Ip::Ipv6(ipv6) => util::checksum([
+                ipv6.source_address().as_ref(),
+                ipv6.computed_final_destination().as_ref(),
+                (length as u32).to_be_bytes().as_ref(),
+                [0x0, 0x0, 0x0, crate::IpProto::UDP].as_ref(),
+                &buffer[..],
+            ]),
+        };
+        // a computed checksum of zero is transmitted as all ones (rfc768)
+        let checksum = if checksum == 0 { 0xFFFF } else { checksum };
+        buffer[6..=7].copy_from_slice(&checksum.to_be_bytes());
+        Ok(())
+    }
+}
+
+/// Represents a UDP-Lite header and payload (rfc3828)
+///
+/// UDP-Lite shares the UDP header layout, but its length field holds the number of bytes covered by the checksum.
+#[derive(Debug, Copy, Clone)]
+pub struct UdpLitePdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of a [`UdpLitePdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum UdpLite<'a> {
+    Raw(&'a [u8]),
+}
+
+impl<'a> UdpLitePdu<'a> {
+    /// Constructs a [`UdpLitePdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        Ok(UdpLitePdu { buffer })
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..8]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<UdpLite<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<UdpLite<'a>> {
+        Ok(UdpLite::Raw(&self.buffer[8..]))
+    }
+
+    pub fn source_port(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[0..=1].try_into().unwrap())
+    }
+
+    pub fn destination_port(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[2..=3].try_into().unwrap())
+    }
+
+    pub fn checksum_coverage(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[4..=5].try_into().unwrap())
+    }
+
+    pub fn checksum(&'a self) -> u16 {
+        u16::from_be_bytes(self.buffer[6..=7].try_into().unwrap())
+    }
+
+    /// Returns the number of bytes covered by the checksum, where a checksum coverage of zero means that the entire
+    /// packet is covered (rfc3828)
+    pub fn computed_checksum_coverage(&'a self) -> usize {
+        match self.checksum_coverage() {
+            0 => self.buffer.len(),
+            coverage => coverage as usize,
+        }
+    }
+
+    /// Returns the checksum of the pseudo-header and the covered bytes of this PDU
+    ///
+    /// The pseudo-header carries the length of the entire packet rather than the checksum coverage (rfc3828). A
+    /// checksum coverage that extends beyond the end of the packet is truncated to the packet.
+    pub fn computed_checksum(&'a self, ip: &crate::Ip) -> u16 {
+        let covered = &self.buffer[8..self.computed_checksum_coverage().clamp(8, self.buffer.len())];
+        match ip {
+            crate::Ip::Ipv4(ipv4) => util::checksum([
+                ipv4.source_address().as_ref(),
+                ipv4.destination_address().as_ref(),
+                [0x00, crate::IpProto::UDP_LITE].as_ref(),
+                ipv4.computed_upper_layer_length().to_be_bytes().as_ref(),
+                &self.buffer[0..=5],
+                covered,
+            ]),
+            crate::Ip::Ipv6(ipv6) => util::checksum([
+                ipv6.source_address().as_ref(),
+                ipv6.computed_final_destination().as_ref(),
+                ipv6.computed_upper_layer_length().to_be_bytes().as_ref(),
+                [0x0, 0x0, 0x0, crate::IpProto::UDP_LITE].as_ref(),
+                &self.buffer[0..=5],
+                covered,
+            ]),
+        }
+    }
+
+    pub fn verify_checksum(&'a self, ip: &crate::Ip) -> ChecksumStatus {
+        // the checksum is mandatory, and must at least cover the header but not extend beyond the packet (rfc3828)
+        let coverage = self.computed_checksum_coverage();
+        if self.checksum() == 0 || coverage < 8 || coverage > self.buffer.len() {
+            return ChecksumStatus::Bad;
+        }
+        ChecksumStatus::from_checksums(self.checksum(), self.computed_checksum(ip))
+    }
+
     pub fn computed_data_offset(&'a self) -> usize {
         8
     }
@@ -16,8 +16,12 @@
    SPDX-License-Identifier: Apache-2.0
 */
 
+//! Internet checksum helpers (RFC 1071, RFC 1624) and the CRC32c checksum used by SCTP (RFC 9260)
+
 use core::convert::TryInto;
+use core::ops::Range;
 
+/// Computes the Internet checksum of the concatenation of `spans`
 pub fn checksum<I, J>(spans: I) -> u16
 where
     I: IntoIterator<Item = J>,
@@ -33,6 +37,78 @@ where
     !(((accum >> 16) as u16) + (accum as u16))
 }
 
+/// Incrementally updates `checksum` to account for `old` bytes being replaced by `new` bytes (RFC 1624)
+///
+/// `old` and `new` must be the same length, and must begin at an even offset within the checksummed data.
+pub fn update_checksum(checksum: u16, old: &[u8], new: &[u8]) -> u16 {
+    debug_assert_eq!(old.len(), new.len());
+    // HC' = ~(~HC + ~m + m')
+    let mut accum = (!checksum) as u32 + (!sum(old)) as u32 + sum(new) as u32;
+    accum = (accum >> 16) + (accum & 0xffff);
+    !(((accum >> 16) as u16) + (accum as u16))
+}
+
+/// Incrementally updates `checksum` to account for a 16-bit field changing from `old` to `new` (RFC 1624)
+pub fn update_checksum_u16(checksum: u16, old: u16, new: u16) -> u16 {
+    update_checksum(checksum, &old.to_be_bytes(), &new.to_be_bytes())
+}
+
+/// Incrementally updates `checksum` to account for a 32-bit field changing from `old` to `new` (RFC 1624)
+pub fn update_checksum_u32(checksum: u16, old: u32, new: u32) -> u16 {
+    update_checksum(checksum, &old.to_be_bytes(), &new.to_be_bytes())
+}
+
+/// Rewrites `buffer[range]` using `write`, then incrementally updates the checksum stored at `buffer[offset..offset+2]`
+///
+/// Returns the updated checksum.
+pub(crate) fn rewrite<F>(buffer: &mut [u8], offset: usize, range: Range<usize>, write: F) -> u16
+where
+    F: FnOnce(&mut [u8]),
+{
+    let mut old = [0u8; 16];
+    let old = &mut old[..range.len()];
+    old.copy_from_slice(&buffer[range.clone()]);
+    write(&mut buffer[range.clone()]);
+    let checksum = u16::from_be_bytes(buffer[offset..offset + 2].try_into().unwrap());
+    let checksum = update_checksum(checksum, old, &buffer[range]);
+    buffer[offset..offset + 2].copy_from_slice(&checksum.to_be_bytes());
+    checksum
+}
+
+/// Computes the CRC32c (Castagnoli) checksum of the concatenation of `spans` (RFC 3309)
+pub fn crc32c<I, J>(spans: I) -> u32
+where
+    I: IntoIterator<Item = J>,
+    J: AsRef<[u8]>,
+{
+    let mut crc = !0u32;
+
+    for span in spans.into_iter() {
+        for &byte in span.as_ref() {
+            crc = CRC32C_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+    }
+
+    !crc
+}
+
+// lookup table for the reflected crc32c polynomial 0x82f63b78
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
 fn sum(mut buffer: &[u8]) -> u16 {
     let mut accum = 0;
 
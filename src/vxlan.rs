@@ -0,0 +1,91 @@
+/*
+   Copyright (c) 2019 Alex Forster <alex@alexforster.com>
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+
+   SPDX-License-Identifier: Apache-2.0
+*/
+
+use core::convert::TryInto;
+
+use crate::{Error, Result};
+
+/// Represents a VXLAN header and payload
+#[derive(Debug, Copy, Clone)]
+pub struct VxlanPdu<'a> {
+    buffer: &'a [u8],
+}
+
+/// Contains the inner payload of a [`VxlanPdu`]
+#[derive(Debug, Copy, Clone)]
+pub enum Vxlan<'a> {
+    Raw(&'a [u8]),
+    Ethernet(super::EthernetPdu<'a>),
+}
+
+impl<'a> VxlanPdu<'a> {
+    /// Constructs a [`VxlanPdu`] backed by the provided `buffer`
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        Ok(VxlanPdu { buffer })
+    }
+
+    /// Returns a reference to the entire underlying buffer that was provided during construction
+    pub fn buffer(&'a self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Consumes this object and returns a reference to the entire underlying buffer that was provided during
+    /// construction
+    pub fn into_buffer(self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn as_bytes(&'a self) -> &'a [u8] {
+        (*self).into_bytes()
+    }
+
+    /// Consumes this object and returns the slice of the underlying buffer that contains the header part of this PDU
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[0..8]
+    }
+
+    /// Returns an object representing the inner payload of this PDU
+    pub fn inner(&'a self) -> Result<Vxlan<'a>> {
+        (*self).into_inner()
+    }
+
+    /// Consumes this object and returns an object representing the inner payload of this PDU
+    pub fn into_inner(self) -> Result<Vxlan<'a>> {
+        let rest = &self.buffer[8..];
+        if rest.is_empty() {
+            return Ok(Vxlan::Raw(rest));
+        }
+        Ok(Vxlan::Ethernet(super::EthernetPdu::new(rest)?))
+    }
+
+    pub fn flags(&'a self) -> u8 {
+        self.buffer[0]
+    }
+
+    pub fn has_vni(&'a self) -> bool {
+        (self.buffer[0] & 0x08) != 0
+    }
+
+    pub fn vni(&'a self) -> u32 {
+        u32::from_be_bytes(self.buffer[4..=7].try_into().unwrap()) >> 8
+    }
+}
@@ -97,20 +97,27 @@ fn visit_ethernet_pdu(pdu: &EthernetPdu, mut nodes: VecDeque<xml::Node>) -> Resu
     assert_eq!(pdu.source_address().as_ref(), descendant_value(&node, "eth", "src", 6)?.as_slice());
     assert_eq!(&pdu.tpid().to_be_bytes(), descendant_value(&node, "eth", "type", 2)?.as_slice());
 
-    if node.next_sibling_element().and_then(|sibling| sibling.attribute("name")) == Some("vlan") {
+    let mut vlan_tags = pdu.vlan_tags().peekable();
+    if let Some(vlan_tag) = vlan_tags.peek() {
+        assert_eq!(pdu.vlan(), Some(vlan_tag.vid));
+        assert_eq!(pdu.vlan_pcp(), Some(vlan_tag.pcp));
+        assert_eq!(pdu.vlan_dei(), Some(vlan_tag.dei));
+    }
+    while nodes.front().and_then(|node| node.attribute("name")) == Some("vlan") {
         let node = nodes.pop_front().unwrap();
         assert_eq!(node.attribute("name"), Some("vlan"));
 
-        assert_eq!(&pdu.vlan().unwrap().to_be_bytes(), descendant_value(&node, "vlan", "id", 2)?.as_slice());
-        assert_eq!(&pdu.vlan_pcp().unwrap().to_be_bytes(), descendant_value(&node, "vlan", "priority", 1)?.as_slice());
-        assert_eq!(
-            (pdu.vlan_dei().unwrap() as u8).to_be_bytes(),
-            descendant_value(&node, "vlan", "dei", 1)?.as_slice()
-        );
-        assert_eq!(&pdu.ethertype().to_be_bytes(), descendant_value(&node, "vlan", "etype", 2)?.as_slice());
-    } else {
+        let vlan_tag = vlan_tags.next().unwrap();
+        let etype = vlan_tags.peek().map(|vlan_tag| vlan_tag.tpid).unwrap_or_else(|| pdu.ethertype());
+        assert_eq!(&vlan_tag.vid.to_be_bytes(), descendant_value(&node, "vlan", "id", 2)?.as_slice());
+        assert_eq!(&vlan_tag.pcp.to_be_bytes(), descendant_value(&node, "vlan", "priority", 1)?.as_slice());
+        assert_eq!((vlan_tag.dei as u8).to_be_bytes(), descendant_value(&node, "vlan", "dei", 1)?.as_slice());
+        assert_eq!(&etype.to_be_bytes(), descendant_value(&node, "vlan", "etype", 2)?.as_slice());
+    }
+    if pdu.vlan_tags().next().is_none() {
         assert_eq!(&pdu.ethertype().to_be_bytes(), descendant_value(&node, "eth", "type", 2)?.as_slice());
     }
+    assert!(vlan_tags.next().is_none());
 
     match pdu.inner() {
         Ok(ethernet) => match ethernet {
@@ -118,6 +125,40 @@ fn visit_ethernet_pdu(pdu: &EthernetPdu, mut nodes: VecDeque<xml::Node>) -> Resu
             Ethernet::Arp(arp_pdu) => visit_arp_pdu(&arp_pdu, nodes),
             Ethernet::Ipv4(ipv4_pdu) => visit_ipv4_pdu(&ipv4_pdu, nodes),
             Ethernet::Ipv6(ipv6_pdu) => visit_ipv6_pdu(&ipv6_pdu, nodes),
+            Ethernet::Mpls(mpls_pdu) => visit_mpls_pdu(&mpls_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_mpls_pdu(pdu: &MplsPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    for label in pdu.labels() {
+        let node = nodes.pop_front().unwrap();
+        if node.attribute("name") == Some("_ws.malformed") {
+            return Err("node: malformed".into());
+        }
+        assert_eq!(node.attribute("name"), Some("mpls"));
+
+        assert_eq!(&label.label.to_be_bytes(), descendant_value(&node, "mpls", "label", 4)?.as_slice());
+        assert_eq!(&label.traffic_class.to_be_bytes(), descendant_value(&node, "mpls", "exp", 1)?.as_slice());
+        assert_eq!(
+            &(label.bottom_of_stack as u8).to_be_bytes(),
+            descendant_value(&node, "mpls", "bottom", 1)?.as_slice()
+        );
+        assert_eq!(&label.ttl.to_be_bytes(), descendant_value(&node, "mpls", "ttl", 1)?.as_slice());
+    }
+
+    match pdu.inner() {
+        Ok(mpls) => match mpls {
+            Mpls::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
+            Mpls::Ethernet(ethernet_pdu) => {
+                if nodes.front().and_then(|node| node.attribute("name")) == Some("pwethcw") {
+                    nodes.pop_front();
+                }
+                visit_ethernet_pdu(&ethernet_pdu, nodes)
+            }
+            Mpls::Ipv4(ipv4_pdu) => visit_ipv4_pdu(&ipv4_pdu, nodes),
+            Mpls::Ipv6(ipv6_pdu) => visit_ipv6_pdu(&ipv6_pdu, nodes),
         },
         Err(e) => Err(e.into()),
     }
@@ -177,6 +218,42 @@ fn visit_ipv4_pdu(pdu: &Ipv4Pdu, mut nodes: VecDeque<xml::Node>) -> Result<(), B
                 Ipv4Option::Raw { option, .. } => {
                     assert_eq!(option.to_be_bytes(), descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
                 }
+                Ipv4Option::End => {
+                    assert_eq!(&[0], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                }
+                Ipv4Option::NoOp => {
+                    assert_eq!(&[1], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                }
+                Ipv4Option::RecordRoute { pointer, .. } => {
+                    assert_eq!(&[7], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                    assert_eq!(&[pointer], descendant_value(&node, "ip", "opt.ptr", 1)?.as_slice());
+                }
+                Ipv4Option::LooseSourceRoute { pointer, .. } => {
+                    assert_eq!(&[131], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                    assert_eq!(&[pointer], descendant_value(&node, "ip", "opt.ptr", 1)?.as_slice());
+                }
+                Ipv4Option::StrictSourceRoute { pointer, .. } => {
+                    assert_eq!(&[137], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                    assert_eq!(&[pointer], descendant_value(&node, "ip", "opt.ptr", 1)?.as_slice());
+                }
+                Ipv4Option::Timestamp { pointer, .. } => {
+                    assert_eq!(&[68], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                    assert_eq!(&[pointer], descendant_value(&node, "ip", "opt.ptr", 1)?.as_slice());
+                }
+                Ipv4Option::RouterAlert { value } => {
+                    assert_eq!(&[148], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                    assert_eq!(value.to_be_bytes(), descendant_value(&node, "ip", "opt.ra", 2)?.as_slice());
+                }
+                Ipv4Option::Security { .. } => {
+                    assert_eq!(&[130], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                }
+                Ipv4Option::Cipso { .. } => {
+                    assert_eq!(&[134], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                }
+                Ipv4Option::StreamId { id } => {
+                    assert_eq!(&[136], descendant_value(&node, "ip", "opt.type", 1)?.as_slice());
+                    assert_eq!(id.to_be_bytes(), descendant_value(&node, "ip", "opt.sid", 2)?.as_slice());
+                }
             }
         }
         while options.front().is_some() && options.front().unwrap().attribute("name") == Some("") {
@@ -192,6 +269,11 @@ fn visit_ipv4_pdu(pdu: &Ipv4Pdu, mut nodes: VecDeque<xml::Node>) -> Result<(), B
             Ipv4::Udp(udp_pdu) => visit_udp_pdu(&udp_pdu, &Ip::Ipv4(*pdu), nodes),
             Ipv4::Icmp(icmp_pdu) => visit_icmp_pdu(&icmp_pdu, &Ip::Ipv4(*pdu), nodes),
             Ipv4::Gre(gre_pdu) => visit_gre_pdu(&gre_pdu, nodes),
+            Ipv4::Esp(esp_pdu) => visit_esp_pdu(&esp_pdu, nodes),
+            Ipv4::Ah(ah_pdu) => visit_ah_pdu(&ah_pdu, &Ip::Ipv4(*pdu), nodes),
+            Ipv4::Igmp(igmp_pdu) => visit_igmp_pdu(&igmp_pdu, nodes),
+            Ipv4::Sctp(sctp_pdu) => visit_sctp_pdu(&sctp_pdu, nodes),
+            Ipv4::UdpLite(udplite_pdu) => visit_udplite_pdu(&udplite_pdu, &Ip::Ipv4(*pdu), nodes),
         },
         Err(e) => Err(e.into()),
     }
@@ -230,6 +312,43 @@ fn visit_ipv6_pdu(pdu: &Ipv6Pdu, mut nodes: VecDeque<xml::Node>) -> Result<(), B
         );
     }
 
+    for extension_header in pdu.extension_headers() {
+        match extension_header {
+            Ipv6ExtensionHeader::Ah { spi, sequence_number, .. } => {
+                // wireshark dissects the authentication header as a separate protocol
+                let node = nodes.pop_front().unwrap();
+                assert_eq!(node.attribute("name"), Some("ah"));
+                assert_eq!(spi.to_be_bytes(), descendant_value(&node, "ah", "spi", 4)?.as_slice());
+                assert_eq!(sequence_number.to_be_bytes(), descendant_value(&node, "ah", "sequence", 4)?.as_slice());
+            }
+            Ipv6ExtensionHeader::Routing { routing_type, segments_left, .. } => {
+                assert_eq!(&[routing_type], descendant_value(&node, "ipv6", "routing.type", 1)?.as_slice());
+                assert_eq!(&[segments_left], descendant_value(&node, "ipv6", "routing.segleft", 1)?.as_slice());
+            }
+            Ipv6ExtensionHeader::SegmentRouting { segments_left, last_entry, .. } => {
+                assert_eq!(&[4], descendant_value(&node, "ipv6", "routing.type", 1)?.as_slice());
+                assert_eq!(&[segments_left], descendant_value(&node, "ipv6", "routing.segleft", 1)?.as_slice());
+                assert_eq!(&[last_entry], descendant_value(&node, "ipv6", "routing.srh.last_entry", 1)?.as_slice());
+            }
+            Ipv6ExtensionHeader::HopByHop { options } | Ipv6ExtensionHeader::DestinationOptions { options } => {
+                for option in options {
+                    match option {
+                        Ipv6Option::RouterAlert { value } => assert_eq!(
+                            value.to_be_bytes(),
+                            descendant_value(&node, "ipv6", "opt.router_alert", 2)?.as_slice()
+                        ),
+                        Ipv6Option::JumboPayload { length } => assert_eq!(
+                            length.to_be_bytes(),
+                            descendant_value(&node, "ipv6", "opt.jumbo", 4)?.as_slice()
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     match pdu.inner() {
         Ok(ipv6) => match ipv6 {
             Ipv6::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
@@ -237,6 +356,9 @@ fn visit_ipv6_pdu(pdu: &Ipv6Pdu, mut nodes: VecDeque<xml::Node>) -> Result<(), B
             Ipv6::Udp(udp_pdu) => visit_udp_pdu(&udp_pdu, &Ip::Ipv6(*pdu), nodes),
             Ipv6::Icmp(icmp_pdu) => visit_icmp_pdu(&icmp_pdu, &Ip::Ipv6(*pdu), nodes),
             Ipv6::Gre(gre_pdu) => visit_gre_pdu(&gre_pdu, nodes),
+            Ipv6::Esp(esp_pdu) => visit_esp_pdu(&esp_pdu, nodes),
+            Ipv6::Sctp(sctp_pdu) => visit_sctp_pdu(&sctp_pdu, nodes),
+            Ipv6::UdpLite(udplite_pdu) => visit_udplite_pdu(&udplite_pdu, &Ip::Ipv6(*pdu), nodes),
         },
         Err(e) => Err(e.into()),
     }
@@ -376,9 +498,105 @@ fn visit_udp_pdu(pdu: &UdpPdu, ip_pdu: &Ip, mut nodes: VecDeque<xml::Node>) -> R
         );
     }
 
+    match pdu.inner() {
+        Ok(udp) => match udp {
+            Udp::Raw(_) => Ok(()),
+            Udp::Vxlan(vxlan_pdu) => visit_vxlan_pdu(&vxlan_pdu, nodes),
+            Udp::Geneve(geneve_pdu) => visit_geneve_pdu(&geneve_pdu, nodes),
+            Udp::Gtpu(gtpu_pdu) => visit_gtpu_pdu(&gtpu_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_udplite_pdu(pdu: &UdpLitePdu, ip_pdu: &Ip, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("udplite"));
+
+    assert_eq!(pdu.source_port().to_be_bytes(), descendant_value(&node, "udp", "srcport", 2)?.as_slice());
+    assert_eq!(pdu.destination_port().to_be_bytes(), descendant_value(&node, "udp", "dstport", 2)?.as_slice());
+    assert_eq!(
+        pdu.checksum_coverage().to_be_bytes(),
+        descendant_value(&node, "udp", "checksum_coverage", 2)?.as_slice()
+    );
+    assert_eq!(pdu.checksum().to_be_bytes(), descendant_value(&node, "udp", "checksum", 2)?.as_slice());
+    if descendant_show(&node, "udp", "checksum.status", 1)?.eq(&[0x01]) {
+        assert_eq!(
+            pdu.computed_checksum(&ip_pdu).to_be_bytes(),
+            descendant_value(&node, "udp", "checksum", 2)?.as_slice()
+        );
+    }
+
     Ok(())
 }
 
+fn visit_vxlan_pdu(pdu: &VxlanPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("vxlan"));
+
+    assert_eq!(&pdu.vni().to_be_bytes(), descendant_value(&node, "vxlan", "vni", 4)?.as_slice());
+
+    match pdu.inner() {
+        Ok(vxlan) => match vxlan {
+            Vxlan::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[8..], raw)),
+            Vxlan::Ethernet(ethernet_pdu) => visit_ethernet_pdu(&ethernet_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_geneve_pdu(pdu: &GenevePdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("geneve"));
+
+    assert_eq!(pdu.version().to_be_bytes(), descendant_value(&node, "geneve", "version", 1)?.as_slice());
+    assert_eq!(pdu.protocol_type().to_be_bytes(), descendant_value(&node, "geneve", "proto_type", 2)?.as_slice());
+    assert_eq!(&pdu.vni().to_be_bytes(), descendant_value(&node, "geneve", "vni", 4)?.as_slice());
+
+    match pdu.inner() {
+        Ok(geneve) => match geneve {
+            Geneve::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
+            Geneve::Ethernet(ethernet_pdu) => visit_ethernet_pdu(&ethernet_pdu, nodes),
+            Geneve::Ipv4(ipv4_pdu) => visit_ipv4_pdu(&ipv4_pdu, nodes),
+            Geneve::Ipv6(ipv6_pdu) => visit_ipv6_pdu(&ipv6_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_gtpu_pdu(pdu: &GtpuPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("gtp"));
+
+    assert_eq!(pdu.message_type().to_be_bytes(), descendant_value(&node, "gtp", "message", 1)?.as_slice());
+    assert_eq!(pdu.length().to_be_bytes(), descendant_value(&node, "gtp", "length", 2)?.as_slice());
+    assert_eq!(pdu.teid().to_be_bytes(), descendant_value(&node, "gtp", "teid", 4)?.as_slice());
+    if let Some(sequence_number) = pdu.sequence_number() {
+        assert_eq!(sequence_number.to_be_bytes(), descendant_value(&node, "gtp", "seq_number", 2)?.as_slice());
+    }
+
+    match pdu.inner() {
+        Ok(gtpu) => match gtpu {
+            Gtpu::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
+            Gtpu::Ipv4(ipv4_pdu) => visit_ipv4_pdu(&ipv4_pdu, nodes),
+            Gtpu::Ipv6(ipv6_pdu) => visit_ipv6_pdu(&ipv6_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
 fn visit_icmp_pdu(pdu: &IcmpPdu, ip_pdu: &Ip, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
     let node = nodes.pop_front().unwrap();
     if node.attribute("name") == Some("_ws.malformed") {
@@ -401,6 +619,101 @@ fn visit_icmp_pdu(pdu: &IcmpPdu, ip_pdu: &Ip, mut nodes: VecDeque<xml::Node>) ->
         );
     }
 
+    match pdu.inner()? {
+        Icmp::Icmpv4(Icmpv4::EchoRequest { identifier, sequence_number, .. })
+        | Icmp::Icmpv4(Icmpv4::EchoReply { identifier, sequence_number, .. }) => {
+            assert_eq!(identifier.to_be_bytes(), descendant_value(&node, proto, "ident", 2)?.as_slice());
+            assert_eq!(sequence_number.to_be_bytes(), descendant_value(&node, proto, "seq", 2)?.as_slice());
+        }
+        Icmp::Icmpv4(Icmpv4::DestinationUnreachable { next_hop_mtu: Some(next_hop_mtu), .. }) => {
+            assert_eq!(next_hop_mtu.to_be_bytes(), descendant_value(&node, proto, "mtu", 2)?.as_slice());
+        }
+        Icmp::Icmpv4(Icmpv4::Redirect { gateway, .. }) => {
+            assert_eq!(gateway, descendant_value(&node, proto, "redir_gw", 4)?.as_slice());
+        }
+        Icmp::Icmpv6(Icmpv6::EchoRequest { identifier, sequence_number, .. })
+        | Icmp::Icmpv6(Icmpv6::EchoReply { identifier, sequence_number, .. }) => {
+            assert_eq!(identifier.to_be_bytes(), descendant_value(&node, proto, "echo.identifier", 2)?.as_slice());
+            assert_eq!(
+                sequence_number.to_be_bytes(),
+                descendant_value(&node, proto, "echo.sequence_number", 2)?.as_slice()
+            );
+        }
+        Icmp::Icmpv6(Icmpv6::PacketTooBig { mtu, .. }) => {
+            assert_eq!(mtu.to_be_bytes(), descendant_value(&node, proto, "mtu", 4)?.as_slice());
+        }
+        Icmp::Icmpv6(Icmpv6::RouterAdvertisement { current_hop_limit, router_lifetime, .. }) => {
+            assert_eq!(
+                current_hop_limit.to_be_bytes(),
+                descendant_value(&node, proto, "nd.ra.cur_hop_limit", 1)?.as_slice()
+            );
+            assert_eq!(
+                router_lifetime.to_be_bytes(),
+                descendant_value(&node, proto, "nd.ra.router_lifetime", 2)?.as_slice()
+            );
+        }
+        Icmp::Icmpv6(Icmpv6::NeighborSolicitation { target_address, .. }) => {
+            assert_eq!(target_address, descendant_value(&node, proto, "nd.ns.target_address", 16)?.as_slice());
+        }
+        Icmp::Icmpv6(Icmpv6::NeighborAdvertisement { target_address, .. }) => {
+            assert_eq!(target_address, descendant_value(&node, proto, "nd.na.target_address", 16)?.as_slice());
+        }
+        Icmp::Icmpv6(Icmpv6::MulticastListenerReport { multicast_address })
+        | Icmp::Icmpv6(Icmpv6::MulticastListenerDone { multicast_address }) => {
+            assert_eq!(multicast_address, descendant_value(&node, proto, "mld.multicast_address", 16)?.as_slice());
+        }
+        Icmp::Icmpv6(Icmpv6::MulticastListenerReportV2 { records }) => {
+            assert_eq!(
+                (records.count() as u16).to_be_bytes(),
+                descendant_value(&node, proto, "mldr.nb_mcast_records", 2)?.as_slice()
+            );
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn visit_igmp_pdu(pdu: &IgmpPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("igmp"));
+
+    assert_eq!(pdu.message_type().to_be_bytes(), descendant_value(&node, "igmp", "type", 1)?.as_slice());
+    assert_eq!(pdu.checksum().to_be_bytes(), descendant_value(&node, "igmp", "checksum", 2)?.as_slice());
+    match pdu.inner()? {
+        Igmp::MembershipReportV3 { records } => {
+            assert_eq!(
+                (records.count() as u16).to_be_bytes(),
+                descendant_value(&node, "igmp", "num_grp_recs", 2)?.as_slice()
+            );
+        }
+        Igmp::Raw(_) => {}
+        _ => {
+            assert_eq!(pdu.group_address(), descendant_value(&node, "igmp", "maddr", 4)?.as_slice());
+        }
+    }
+
+    Ok(())
+}
+
+fn visit_sctp_pdu(pdu: &SctpPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("sctp"));
+
+    assert_eq!(pdu.source_port().to_be_bytes(), descendant_value(&node, "sctp", "srcport", 2)?.as_slice());
+    assert_eq!(pdu.destination_port().to_be_bytes(), descendant_value(&node, "sctp", "dstport", 2)?.as_slice());
+    assert_eq!(
+        pdu.verification_tag().to_be_bytes(),
+        descendant_value(&node, "sctp", "verification_tag", 4)?.as_slice()
+    );
+    assert_eq!(pdu.checksum().to_be_bytes(), descendant_value(&node, "sctp", "checksum", 4)?.as_slice());
+
     Ok(())
 }
 
@@ -435,12 +748,123 @@ fn visit_gre_pdu(pdu: &GrePdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box
         );
     }
 
+    if node.descendants().any(|n| n.attribute("name") == Some("gre.call_id")) {
+        assert_eq!(
+            pdu.payload_length().unwrap().to_be_bytes(),
+            descendant_value(&node, "gre", "payload_length", 2)?.as_slice()
+        );
+        assert_eq!(pdu.call_id().unwrap().to_be_bytes(), descendant_value(&node, "gre", "call_id", 2)?.as_slice());
+    }
+
+    if node.descendants().any(|n| n.attribute("name") == Some("gre.ack_number")) {
+        assert_eq!(
+            pdu.acknowledgement_number().unwrap().to_be_bytes(),
+            descendant_value(&node, "gre", "ack_number", 4)?.as_slice()
+        );
+    }
+
     match pdu.inner() {
         Ok(gre) => match gre {
             Gre::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
-            Gre::Ethernet(ethernet_pdu) => visit_ethernet_pdu(&ethernet_pdu, nodes),
+            Gre::Ethernet(ethernet_pdu) => {
+                if pdu.ethertype() == EtherType::ERSPAN_TYPE_II {
+                    // erspan type I has no header of its own
+                    let node = nodes.pop_front().unwrap();
+                    assert_eq!(node.attribute("name"), Some("erspan"));
+                }
+                visit_ethernet_pdu(&ethernet_pdu, nodes)
+            }
             Gre::Ipv4(ipv4_pdu) => visit_ipv4_pdu(&ipv4_pdu, nodes),
             Gre::Ipv6(ipv6_pdu) => visit_ipv6_pdu(&ipv6_pdu, nodes),
+            Gre::Mpls(mpls_pdu) => visit_mpls_pdu(&mpls_pdu, nodes),
+            Gre::Ppp(ppp_pdu) => visit_ppp_pdu(&ppp_pdu, nodes),
+            Gre::Erspan(erspan_pdu) => visit_erspan_pdu(&erspan_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_ppp_pdu(pdu: &PppPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("ppp"));
+
+    if let (Some(address), Some(control)) = (pdu.address(), pdu.control()) {
+        assert_eq!(address.to_be_bytes(), descendant_value(&node, "ppp", "address", 1)?.as_slice());
+        assert_eq!(control.to_be_bytes(), descendant_value(&node, "ppp", "control", 1)?.as_slice());
+    }
+    assert_eq!(pdu.protocol().to_be_bytes(), descendant_value(&node, "ppp", "protocol", 2)?.as_slice());
+
+    match pdu.inner() {
+        Ok(ppp) => match ppp {
+            Ppp::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
+            Ppp::Ipv4(ipv4_pdu) => visit_ipv4_pdu(&ipv4_pdu, nodes),
+            Ppp::Ipv6(ipv6_pdu) => visit_ipv6_pdu(&ipv6_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_erspan_pdu(pdu: &ErspanPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("erspan"));
+
+    assert_eq!(pdu.version().to_be_bytes(), descendant_value(&node, "erspan", "version", 1)?.as_slice());
+    assert_eq!(pdu.vlan().to_be_bytes(), descendant_value(&node, "erspan", "vlan", 2)?.as_slice());
+    assert_eq!(pdu.cos().to_be_bytes(), descendant_value(&node, "erspan", "cos", 1)?.as_slice());
+    assert_eq!(pdu.session_id().to_be_bytes(), descendant_value(&node, "erspan", "spanid", 2)?.as_slice());
+    if let Some(index) = pdu.index() {
+        assert_eq!(index.to_be_bytes(), descendant_value(&node, "erspan", "index", 4)?.as_slice());
+    }
+    if let Some(timestamp) = pdu.timestamp() {
+        assert_eq!(timestamp.to_be_bytes(), descendant_value(&node, "erspan", "timestamp", 4)?.as_slice());
+    }
+
+    match pdu.inner() {
+        Ok(erspan) => match erspan {
+            Erspan::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
+            Erspan::Ethernet(ethernet_pdu) => visit_ethernet_pdu(&ethernet_pdu, nodes),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn visit_esp_pdu(pdu: &EspPdu, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("esp"));
+
+    assert_eq!(pdu.spi().to_be_bytes(), descendant_value(&node, "esp", "spi", 4)?.as_slice());
+    assert_eq!(pdu.sequence_number().to_be_bytes(), descendant_value(&node, "esp", "sequence", 4)?.as_slice());
+
+    Ok(())
+}
+
+fn visit_ah_pdu(pdu: &AhPdu, ip_pdu: &Ip, mut nodes: VecDeque<xml::Node>) -> Result<(), Box<dyn Error>> {
+    let node = nodes.pop_front().unwrap();
+    if node.attribute("name") == Some("_ws.malformed") {
+        return Err("node: malformed".into());
+    }
+    assert_eq!(node.attribute("name"), Some("ah"));
+
+    assert_eq!(pdu.spi().to_be_bytes(), descendant_value(&node, "ah", "spi", 4)?.as_slice());
+    assert_eq!(pdu.sequence_number().to_be_bytes(), descendant_value(&node, "ah", "sequence", 4)?.as_slice());
+
+    match pdu.inner() {
+        Ok(ah) => match ah {
+            Ah::Raw(raw) => Ok(assert_eq!(&pdu.buffer()[pdu.computed_ihl()..], raw)),
+            Ah::Tcp(tcp_pdu) => visit_tcp_pdu(&tcp_pdu, ip_pdu, nodes),
+            Ah::Udp(udp_pdu) => visit_udp_pdu(&udp_pdu, ip_pdu, nodes),
+            Ah::Icmp(icmp_pdu) => visit_icmp_pdu(&icmp_pdu, ip_pdu, nodes),
+            Ah::Gre(gre_pdu) => visit_gre_pdu(&gre_pdu, nodes),
+            Ah::Esp(esp_pdu) => visit_esp_pdu(&esp_pdu, nodes),
         },
         Err(e) => Err(e.into()),
     }
@@ -556,3 +980,2417 @@ fn test_pcaps() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_mutation() -> Result<(), Box<dyn Error>> {
+    let mut packet = [
+        0x68u8, 0x5b, 0x35, 0xc0, 0x61, 0xb6, 0x00, 0x1d, 0x09, 0x94, 0x65, 0x38, 0x08, 0x00, 0x45, 0x00, 0x00, 0x3b,
+        0x2d, 0xfd, 0x00, 0x00, 0x40, 0x11, 0xbc, 0x43, 0x83, 0xb3, 0xc4, 0x2e, 0x83, 0xb3, 0xc4, 0xdc, 0x18, 0xdb,
+        0x18, 0xdb, 0x00, 0x27, 0xe0, 0x3e, 0x05, 0x1d, 0x07, 0x15, 0x08, 0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c,
+        0x65, 0x08, 0x07, 0x74, 0x65, 0x73, 0x74, 0x41, 0x70, 0x70, 0x08, 0x01, 0x31, 0x0a, 0x04, 0x1e, 0xcc, 0xe2,
+        0x51,
+    ];
+
+    let mut ethernet_pdu = EthernetPduMut::new(&mut packet)?;
+    ethernet_pdu.set_source_address([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    assert_eq!(ethernet_pdu.set_vlan(100), Err(pdu::Error::Malformed));
+    assert_eq!(ethernet_pdu.set_ethertype(0x05dc), Err(pdu::Error::Malformed));
+    match ethernet_pdu.inner()? {
+        EthernetMut::Ipv4(mut ipv4_pdu) => {
+            ipv4_pdu.set_ttl(1);
+            ipv4_pdu.set_destination_address([10, 0, 0, 1]);
+            assert_eq!(ipv4_pdu.set_ihl(4), Err(pdu::Error::Truncated));
+            assert_eq!(ipv4_pdu.as_pdu().ihl(), 5);
+            match ipv4_pdu.inner()? {
+                Ipv4Mut::Udp(mut udp_pdu) => {
+                    udp_pdu.set_destination_port(5353);
+                    let UdpMut::Raw(payload) = udp_pdu.inner()?;
+                    payload[0] = 0xff;
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let ethernet_pdu = EthernetPdu::new(&packet)?;
+    assert_eq!(ethernet_pdu.source_address(), [0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    match ethernet_pdu.inner()? {
+        Ethernet::Ipv4(ipv4_pdu) => {
+            assert_eq!(ipv4_pdu.ttl(), 1);
+            assert_eq!(ipv4_pdu.destination_address(), [10, 0, 0, 1]);
+            match ipv4_pdu.inner()? {
+                Ipv4::Udp(udp_pdu) => {
+                    assert_eq!(udp_pdu.destination_port(), 5353);
+                    assert_eq!(udp_pdu.buffer()[8], 0xff);
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_tcp_options() -> Result<(), Box<dyn Error>> {
+    let mut ip_buffer = [0u8; 20];
+    let length = Ipv4Builder::new().protocol(IpProto::TCP).build(&mut ip_buffer, &[])?;
+    let ip = Ip::Ipv4(Ipv4Pdu::new(&ip_buffer[..length])?);
+    let segment = |options: &[u8]| {
+        let mut segment = vec![0u8; 20];
+        segment[12] = ((20 + options.len()) / 4 << 4) as u8;
+        segment.extend_from_slice(options);
+        segment
+    };
+    // decoded options must be written back byte-for-byte by the builder
+    let roundtrip = |segment: &[u8]| -> Result<(), Box<dyn Error>> {
+        let tcp_pdu = TcpPdu::new(segment)?;
+        let options = tcp_pdu.options().collect::<Vec<_>>();
+        let mut buffer = [0u8; 60];
+        let length = TcpBuilder::new().options(&options).build(&mut buffer, &ip, &[])?;
+        assert_eq!(&buffer[20..length], &segment[20..]);
+        Ok(())
+    };
+
+    #[rustfmt::skip]
+    let first = segment(&[
+        // MP_CAPABLE (v1, third ACK with data)
+        30, 24, 0x01, 0x81, 1, 2, 3, 4, 5, 6, 7, 8, 8, 7, 6, 5, 4, 3, 2, 1, 0x05, 0xdc, 0xbe, 0xef,
+        // user timeout of 5 minutes
+        28, 4, 0x80, 0x05,
+        // tcp fast open cookie
+        34, 10, 1, 2, 3, 4, 5, 6, 7, 8,
+        1, 1,
+    ]);
+    let tcp_pdu = TcpPdu::new(&first)?;
+    let mut options = tcp_pdu.options();
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::MpCapable {
+            version,
+            flags,
+            sender_key,
+            receiver_key,
+            data_level_length,
+            checksum,
+        })) => {
+            assert_eq!((version, flags), (1, 0x81));
+            assert_eq!(sender_key, Some(0x0102_0304_0506_0708));
+            assert_eq!(receiver_key, Some(0x0807_0605_0403_0201));
+            assert_eq!(data_level_length, Some(1500));
+            assert_eq!(checksum, Some(0xbeef));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::UserTimeout { granularity, timeout }) => assert_eq!((granularity, timeout), (true, 5)),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::FastOpen { cookie }) => assert_eq!(cookie, &[1, 2, 3, 4, 5, 6, 7, 8]),
+        other => panic!("unexpected {:?}", other),
+    }
+    roundtrip(&first)?;
+
+    #[rustfmt::skip]
+    let second = segment(&[
+        // md5 signature
+        19, 18, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+        // tcp-ao with a 12 byte mac
+        29, 16, 3, 4, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa, 0xa,
+        1, 1,
+    ]);
+    let tcp_pdu = TcpPdu::new(&second)?;
+    let mut options = tcp_pdu.options();
+    match options.next() {
+        Some(TcpOption::Md5Signature { digest }) => assert_eq!(digest, &second[22..38]),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Authentication { key_id, rnext_key_id, mac }) => {
+            assert_eq!((key_id, rnext_key_id), (3, 4));
+            assert_eq!(mac, &[0xa; 12]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    roundtrip(&second)?;
+
+    #[rustfmt::skip]
+    let third = segment(&[
+        // DSS with an 8 octet data ack and a 4 octet data sequence number with a checksum
+        30, 24, 0x20, 0x07, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0x05, 0xdc, 0x12, 0x34,
+        // MP_JOIN (SYN, backup)
+        30, 12, 0x11, 2, 0xde, 0xad, 0xbe, 0xef, 0xca, 0xfe, 0xf0, 0x0d,
+    ]);
+    let tcp_pdu = TcpPdu::new(&third)?;
+    let mut options = tcp_pdu.options();
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::Dss { flags, data_ack, mapping: Some(mapping) })) => {
+            assert_eq!(flags, 0x07);
+            assert_eq!(data_ack, Some(0x0000_0001_0000_0002));
+            assert_eq!(mapping.data_sequence_number, 3);
+            assert_eq!(mapping.subflow_sequence_number, 4);
+            assert_eq!(mapping.data_level_length, 1500);
+            assert_eq!(mapping.checksum, Some(0x1234));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::MpJoinSyn {
+            backup,
+            address_id,
+            receiver_token,
+            sender_random_number,
+        })) => {
+            assert_eq!((backup, address_id), (true, 2));
+            assert_eq!(receiver_token, 0xdead_beef);
+            assert_eq!(sender_random_number, 0xcafe_f00d);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    roundtrip(&third)?;
+
+    #[rustfmt::skip]
+    let fourth = segment(&[
+        // ADD_ADDR (echo) of an ipv6 address and port
+        30, 22, 0x31, 5, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0x1f, 0x90,
+        // MP_PRIO
+        30, 3, 0x51,
+        // REMOVE_ADDR of two address ids
+        30, 5, 0x40, 5, 6,
+        1, 1,
+    ]);
+    let tcp_pdu = TcpPdu::new(&fourth)?;
+    let mut options = tcp_pdu.options();
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::AddAddr { echo, address_id, address, port, truncated_hmac })) => {
+            assert_eq!((echo, address_id), (true, 5));
+            assert_eq!(address, &fourth[24..40]);
+            assert_eq!(port, Some(8080));
+            assert_eq!(truncated_hmac, None);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::MpPrio { backup, address_id })) => {
+            assert_eq!((backup, address_id), (true, None))
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::RemoveAddr { address_ids })) => assert_eq!(address_ids, &[5, 6]),
+        other => panic!("unexpected {:?}", other),
+    }
+    roundtrip(&fourth)?;
+
+    #[rustfmt::skip]
+    let fifth = segment(&[
+        // experimental tcp fast open cookie
+        254, 12, 0xf9, 0x89, 1, 2, 3, 4, 5, 6, 7, 8,
+        // MP_FASTCLOSE
+        30, 12, 0x70, 0, 0, 0, 0, 0, 0, 0, 0, 9,
+        // MP_FAIL
+        30, 12, 0x60, 0, 0, 0, 0, 0, 0, 0, 0, 10,
+    ]);
+    let tcp_pdu = TcpPdu::new(&fifth)?;
+    let mut options = tcp_pdu.options();
+    match options.next() {
+        Some(TcpOption::FastOpenExperimental { cookie }) => assert_eq!(cookie, &[1, 2, 3, 4, 5, 6, 7, 8]),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::MpFastclose { receiver_key })) => assert_eq!(receiver_key, 9),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::MpFail { data_sequence_number })) => assert_eq!(data_sequence_number, 10),
+        other => panic!("unexpected {:?}", other),
+    }
+    roundtrip(&fifth)?;
+
+    #[rustfmt::skip]
+    let sixth = segment(&[
+        // DSS whose length disagrees with its flags
+        30, 6, 0x20, 0x01, 0, 0,
+        // tcp fast open cookie request
+        34, 2,
+        // unsupported MPTCP subtype (MP_TCPRST)
+        30, 4, 0x80, 0x00,
+    ]);
+    let tcp_pdu = TcpPdu::new(&sixth)?;
+    let mut options = tcp_pdu.options();
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::Raw { subtype: 2, data })) => assert_eq!(data, &sixth[20..26]),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::FastOpen { cookie }) => assert!(cookie.is_empty()),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(TcpOption::Mptcp(MptcpOption::Raw { subtype: 8, .. })) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    roundtrip(&sixth)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_builder() -> Result<(), Box<dyn Error>> {
+    let ipv4_options = [Ipv4Option::Raw { option: 0x94, data: &[0x94, 0x04, 0x00, 0x00] }];
+    let tcp_options = [
+        TcpOption::Mss { size: 1460 },
+        TcpOption::SackPermitted,
+        TcpOption::Timestamp { val: 0x01020304, ecr: 0 },
+        TcpOption::NoOp,
+        TcpOption::WindowScale { shift: 7 },
+        TcpOption::Sack { blocks: [Some((1, 2)), Some((3, 4)), None, None] },
+    ];
+    let payload = b"hello, world";
+
+    let builder = PacketBuilder::new()
+        .ethernet(
+            EthernetBuilder::new()
+                .destination_address([0x02, 0x00, 0x00, 0x00, 0x00, 0x02])
+                .source_address([0x02, 0x00, 0x00, 0x00, 0x00, 0x01])
+                .vlan(100)
+                .vlan_pcp(5),
+        )
+        .ipv4(
+            Ipv4Builder::new()
+                .source_address([192, 0, 2, 1])
+                .destination_address([192, 0, 2, 2])
+                .identification(0x1234)
+                .dont_fragment(true)
+                .options(&ipv4_options),
+        )
+        .tcp(
+            TcpBuilder::new()
+                .source_port(49152)
+                .destination_port(80)
+                .sequence_number(1000)
+                .flags(TcpFlag::SYN | TcpFlag::ACK)
+                .window_size(65535)
+                .options(&tcp_options),
+        );
+
+    let mut buffer = [0u8; 128];
+    assert_eq!(builder.build(&mut buffer[..64], payload), Err(pdu::Error::Truncated));
+    let length = builder.build(&mut buffer, payload)?;
+    assert_eq!(length, builder.computed_length(payload.len()));
+
+    let ethernet_pdu = EthernetPdu::new(&buffer[..length])?;
+    assert_eq!(ethernet_pdu.vlan(), Some(100));
+    assert_eq!(ethernet_pdu.vlan_pcp(), Some(5));
+    assert_eq!(ethernet_pdu.ethertype(), EtherType::IPV4);
+    let ipv4_pdu = match ethernet_pdu.inner()? {
+        Ethernet::Ipv4(ipv4_pdu) => ipv4_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(ipv4_pdu.computed_ihl(), 24);
+    assert_eq!(ipv4_pdu.total_length() as usize, length - 18);
+    assert_eq!(ipv4_pdu.protocol(), IpProto::TCP);
+    assert!(ipv4_pdu.dont_fragment());
+    assert_eq!(ipv4_pdu.checksum(), ipv4_pdu.computed_checksum());
+    let tcp_pdu = match ipv4_pdu.inner()? {
+        Ipv4::Tcp(tcp_pdu) => tcp_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(tcp_pdu.destination_port(), 80);
+    assert!(tcp_pdu.syn() && tcp_pdu.ack());
+    assert_eq!(tcp_pdu.checksum(), tcp_pdu.computed_checksum(&Ip::Ipv4(ipv4_pdu)));
+    assert_eq!(tcp_pdu.options().count(), tcp_options.len());
+    match tcp_pdu.inner()? {
+        Tcp::Raw(raw) => assert_eq!(raw, payload),
+    }
+
+    let extension_headers = [Ipv6ExtensionHeader::Fragment { identification: 7, offset: 0, more_fragments: false }];
+    let builder = PacketBuilder::new()
+        .ipv6(Ipv6Builder::new().hop_limit(1).extension_headers(&extension_headers))
+        .udp(UdpBuilder::new().source_port(53).destination_port(53));
+    let length = builder.build(&mut buffer, payload)?;
+    let ipv6_pdu = match Ip::new(&buffer[..length])? {
+        Ip::Ipv6(ipv6_pdu) => ipv6_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(ipv6_pdu.computed_protocol(), IpProto::UDP);
+    assert_eq!(ipv6_pdu.computed_identification(), Some(7));
+    assert_eq!(ipv6_pdu.payload_length() as usize, length - 40);
+    match ipv6_pdu.inner()? {
+        Ipv6::Udp(udp_pdu) => assert_eq!(udp_pdu.length() as usize, 8 + payload.len()),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_checksums() -> Result<(), Box<dyn Error>> {
+    let builder = PacketBuilder::new()
+        .ethernet(EthernetBuilder::new())
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .udp(UdpBuilder::new().source_port(53).destination_port(53));
+    let mut buffer = [0u8; 128];
+    let length = builder.build(&mut buffer, b"hello, world")?;
+
+    let ethernet_pdu = EthernetPdu::new(&buffer[..length])?;
+    let statuses: Vec<_> = ethernet_pdu.verify_checksums().collect();
+    assert_eq!(
+        statuses,
+        [
+            (Layer::Ethernet, ChecksumStatus::NotChecked),
+            (Layer::Ipv4, ChecksumStatus::Good),
+            (Layer::Udp, ChecksumStatus::Good),
+        ]
+    );
+
+    // corrupt the udp payload
+    buffer[length - 1] ^= 0xFF;
+    let ethernet_pdu = EthernetPdu::new(&buffer[..length])?;
+    assert_eq!(ethernet_pdu.verify_checksums().last(), Some((Layer::Udp, ChecksumStatus::Bad)));
+
+    // a zero udp checksum means "no checksum" over ipv4
+    buffer[40..42].copy_from_slice(&[0, 0]);
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[14..length])?;
+    let statuses: Vec<_> = Ip::Ipv4(ipv4_pdu).verify_checksums().collect();
+    assert_eq!(statuses, [(Layer::Ipv4, ChecksumStatus::Good), (Layer::Udp, ChecksumStatus::NotChecked)]);
+
+    // corrupt the ipv4 header
+    buffer[22] ^= 0xFF;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[14..length])?;
+    assert_eq!(ipv4_pdu.verify_checksum(), ChecksumStatus::Bad);
+
+    // a zero udp checksum is invalid over ipv6
+    let builder = PacketBuilder::new().ipv6(Ipv6Builder::new()).udp(UdpBuilder::new());
+    let length = builder.build(&mut buffer, b"hello, world")?;
+    let ip = Ip::new(&buffer[..length])?;
+    assert_eq!(ip.verify_checksums().last(), Some((Layer::Udp, ChecksumStatus::Good)));
+    buffer[46..48].copy_from_slice(&[0, 0]);
+    let ip = Ip::new(&buffer[..length])?;
+    assert_eq!(ip.verify_checksums().last(), Some((Layer::Udp, ChecksumStatus::Bad)));
+
+    // trailing link-layer bytes after the datagram are not covered by the transport checksum
+    let mut frame = [0xa5u8; 128];
+    let length = builder.build(&mut frame, b"hello, world")?;
+    let ip = Ip::new(&frame[..length + 16])?;
+    assert_eq!(ip.verify_checksums().last(), Some((Layer::Udp, ChecksumStatus::Good)));
+    let builder = PacketBuilder::new()
+        .ethernet(EthernetBuilder::new())
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(80).flags(TcpFlag::SYN));
+    let mut frame = [0xa5u8; 64];
+    let length = builder.build(&mut frame, &[])?;
+    assert_eq!(length, 54);
+    let ethernet_pdu = EthernetPdu::new(&frame[..60])?;
+    assert_eq!(ethernet_pdu.verify_checksums().last(), Some((Layer::Tcp, ChecksumStatus::Good)));
+
+    Ok(())
+}
+
+#[test]
+fn test_incremental_checksum() -> Result<(), Box<dyn Error>> {
+    let data = [0x45u8, 0x00, 0x00, 0x3b, 0x2d, 0xfd, 0x00, 0x00, 0x40, 0x11];
+    let checksum = util::checksum([&data[..]]);
+    let mut rewritten = data;
+    rewritten[4..6].copy_from_slice(&0x1234u16.to_be_bytes());
+    assert_eq!(util::update_checksum_u16(checksum, 0x2dfd, 0x1234), util::checksum([&rewritten[..]]));
+    rewritten[6..10].copy_from_slice(&0xdeadbeefu32.to_be_bytes());
+    assert_eq!(util::update_checksum(checksum, &data[4..10], &rewritten[4..10]), util::checksum([&rewritten[..]]));
+    assert_eq!(
+        util::update_checksum_u32(util::checksum([&rewritten[..]]), 0xdeadbeef, 0x00004011),
+        util::update_checksum_u16(checksum, 0x2dfd, 0x1234)
+    );
+
+    let builder = PacketBuilder::new()
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(80).flags(TcpFlag::SYN));
+    let mut buffer = [0u8; 128];
+    let length = builder.build(&mut buffer, b"hello, world")?;
+
+    let mut ipv4_pdu = Ipv4PduMut::new(&mut buffer[..length])?;
+    ipv4_pdu.set_source_address([198, 51, 100, 7]);
+    ipv4_pdu.set_ttl(12);
+    ipv4_pdu.set_identification(0xbeef);
+    ipv4_pdu.set_dont_fragment(true);
+    match ipv4_pdu.inner()? {
+        Ipv4Mut::Tcp(mut tcp_pdu) => {
+            tcp_pdu.set_source_port(1024);
+            tcp_pdu.set_sequence_number(0x01020304);
+            tcp_pdu.set_ack(true);
+            tcp_pdu.set_window_size(1000);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let ip = Ip::new(&buffer[..length])?;
+    assert!(ip.verify_checksums().all(|(_, status)| status == ChecksumStatus::Good));
+
+    // options that join or leave the header are reflected in the header checksum
+    let router_alert = [Ipv4Option::RouterAlert { value: 0 }];
+    let length = Ipv4Builder::new().options(&router_alert).build(&mut buffer, b"hello, world")?;
+    let mut ipv4_pdu = Ipv4PduMut::new(&mut buffer[..length])?;
+    ipv4_pdu.set_ihl(5)?;
+    assert_eq!(ipv4_pdu.as_pdu().computed_ihl(), 20);
+    assert_eq!(ipv4_pdu.as_pdu().verify_checksum(), ChecksumStatus::Good);
+    ipv4_pdu.set_ihl(6)?;
+    assert_eq!(ipv4_pdu.as_pdu().verify_checksum(), ChecksumStatus::Good);
+    assert_eq!(ipv4_pdu.set_ihl(4).unwrap_err(), pdu::Error::Truncated);
+    assert_eq!(ipv4_pdu.as_pdu().computed_ihl(), 24);
+    assert_eq!(ipv4_pdu.as_pdu().verify_checksum(), ChecksumStatus::Good);
+
+    let builder = PacketBuilder::new()
+        .ipv6(Ipv6Builder::new().source_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]))
+        .udp(UdpBuilder::new().source_port(53).destination_port(53));
+    let length = builder.build(&mut buffer, b"hello, world")?;
+
+    let mut ipv6_pdu = Ipv6PduMut::new(&mut buffer[..length])?;
+    ipv6_pdu.set_destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xab, 0xcd]);
+    match ipv6_pdu.inner()? {
+        Ipv6Mut::Udp(mut udp_pdu) => udp_pdu.set_destination_port(5353),
+        other => panic!("unexpected {:?}", other),
+    }
+    let ip = Ip::new(&buffer[..length])?;
+    assert_eq!(ip.verify_checksums().last(), Some((Layer::Udp, ChecksumStatus::Good)));
+
+    Ok(())
+}
+
+#[test]
+fn test_vlan_tags() -> Result<(), Box<dyn Error>> {
+    let packet = [
+        0xffu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xca, 0x03, 0x0d, 0xb4, 0x00, 0x1c, 0x88, 0xa8, 0xa0, 0x64, 0x81, 0x00,
+        0x10, 0xc8, 0x08, 0x06, 0x00, 0x01, 0x08, 0x00, 0x06, 0x04, 0x00, 0x01, 0xca, 0x03, 0x0d, 0xb4, 0x00, 0x1c,
+        0xc0, 0xa8, 0x02, 0xc8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0xa8, 0x02, 0xfe,
+    ];
+
+    let ethernet_pdu = EthernetPdu::new(&packet)?;
+    assert_eq!(ethernet_pdu.tpid(), EtherType::DOT1AD);
+    assert_eq!(ethernet_pdu.ethertype(), EtherType::ARP);
+    assert_eq!(ethernet_pdu.computed_ihl(), 22);
+    assert_eq!(ethernet_pdu.vlan(), Some(100));
+    assert_eq!(ethernet_pdu.vlan_pcp(), Some(5));
+    assert_eq!(
+        ethernet_pdu.vlan_tags().collect::<Vec<_>>(),
+        [
+            VlanTag { tpid: EtherType::DOT1AD, pcp: 5, dei: false, vid: 100 },
+            VlanTag { tpid: EtherType::DOT1Q, pcp: 0, dei: true, vid: 200 },
+        ]
+    );
+    match ethernet_pdu.inner()? {
+        Ethernet::Arp(arp_pdu) => assert_eq!(arp_pdu.target_protocol_address(), [192, 168, 2, 254]),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert_eq!(EthernetPdu::new(&packet[..20]).unwrap_err(), pdu::Error::Truncated);
+
+    Ok(())
+}
+
+#[test]
+fn test_mpls() -> Result<(), Box<dyn Error>> {
+    let mut buffer = [0u8; 128];
+    let length = PacketBuilder::new()
+        .ethernet(EthernetBuilder::new().ethertype(EtherType::IPV4))
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .udp(UdpBuilder::new().source_port(49152).destination_port(53))
+        .build(&mut buffer, b"hello, world")?;
+
+    // two labels (16, 17) followed by a pseudowire control word and the ethernet frame built above
+    let mut packet = vec![0x00u8, 0x01, 0x00, 0x40, 0x00, 0x01, 0x13, 0x3f, 0x00, 0x00, 0x00, 0x2a];
+    packet.extend_from_slice(&buffer[..length]);
+
+    let mpls_pdu = MplsPdu::new(&packet)?;
+    assert_eq!(mpls_pdu.computed_ihl(), 8);
+    assert_eq!(mpls_pdu.label(), 16);
+    assert!(!mpls_pdu.bottom_of_stack());
+    assert_eq!(
+        mpls_pdu.labels().collect::<Vec<_>>(),
+        [
+            MplsLabel { label: 16, traffic_class: 0, bottom_of_stack: false, ttl: 64 },
+            MplsLabel { label: 17, traffic_class: 1, bottom_of_stack: true, ttl: 63 },
+        ]
+    );
+    assert_eq!(mpls_pdu.control_word(), Some(42));
+    match mpls_pdu.inner()? {
+        Mpls::Ethernet(ethernet_pdu) => match ethernet_pdu.inner()? {
+            Ethernet::Ipv4(ipv4_pdu) => assert_eq!(ipv4_pdu.destination_address(), [192, 0, 2, 2]),
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // a single label followed directly by an ipv4 packet
+    let mut packet = vec![0x00u8, 0x01, 0x01, 0x40];
+    packet.extend_from_slice(&buffer[14..length]);
+    let mpls_pdu = MplsPdu::new(&packet)?;
+    assert_eq!(mpls_pdu.control_word(), None);
+    match mpls_pdu.inner()? {
+        Mpls::Ipv4(ipv4_pdu) => assert_eq!(ipv4_pdu.source_address(), [192, 0, 2, 1]),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // reachable from ethernet
+    let mut frame = vec![0u8; 12];
+    frame.extend_from_slice(&EtherType::MPLS.to_be_bytes());
+    frame.extend_from_slice(&packet);
+    match EthernetPdu::new(&frame)?.inner()? {
+        Ethernet::Mpls(mpls_pdu) => assert_eq!(mpls_pdu.labels().count(), 1),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert_eq!(MplsPdu::new(&[0x00, 0x01, 0x00, 0x40, 0x00, 0x01]).unwrap_err(), pdu::Error::Truncated);
+
+    Ok(())
+}
+
+#[test]
+fn test_vxlan() -> Result<(), Box<dyn Error>> {
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/vxlan.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+
+    let mut count = 0;
+    while let Ok(packet) = pcap.next() {
+        let udp_pdu = match EthernetPdu::new(packet.data)?.into_inner()? {
+            Ethernet::Ipv4(ipv4_pdu) => match ipv4_pdu.into_inner()? {
+                Ipv4::Udp(udp_pdu) => udp_pdu,
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(udp_pdu.destination_port(), UdpPort::VXLAN);
+        let vxlan_pdu = match udp_pdu.inner()? {
+            Udp::Vxlan(vxlan_pdu) => vxlan_pdu,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert!(vxlan_pdu.has_vni());
+        assert_eq!(vxlan_pdu.vni(), 0);
+        assert_eq!(udp_pdu.vxlan()?.as_bytes(), vxlan_pdu.as_bytes());
+        match vxlan_pdu.inner()? {
+            Vxlan::Ethernet(ethernet_pdu) => assert!(ethernet_pdu.inner().is_ok()),
+            other => panic!("unexpected {:?}", other),
+        }
+        count += 1;
+    }
+    assert_eq!(count, 10);
+
+    // vxlan on a non-standard port is only decoded on request
+    let ip_buffer = [
+        0x45u8, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x40, 0x11, 0x00, 0x00, 0xc0, 0x00, 0x02, 0x01, 0xc0, 0x00,
+        0x02, 0x02,
+    ];
+    let vxlan_header = [0x08u8, 0x00, 0x00, 0x00, 0x12, 0x34, 0x56, 0x00];
+    let mut buffer = [0u8; 16];
+    let length = UdpBuilder::new().destination_port(8472).build(&mut buffer, &Ip::new(&ip_buffer)?, &vxlan_header)?;
+    let udp_pdu = UdpPdu::new(&buffer[..length])?;
+    assert!(matches!(udp_pdu.inner()?, Udp::Raw(_)));
+    assert_eq!(udp_pdu.vxlan()?.vni(), 0x123456);
+
+    Ok(())
+}
+
+#[test]
+fn test_geneve() -> Result<(), Box<dyn Error>> {
+    let mut buffer = [0u8; 128];
+    let length = PacketBuilder::new()
+        .ipv4(Ipv4Builder::new().source_address([10, 0, 0, 1]).destination_address([10, 0, 0, 2]))
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(443))
+        .build(&mut buffer, &[])?;
+
+    let mut packet = vec![
+        0x03u8, 0x40, 0x08, 0x00, 0x00, 0x00, 0x2a, 0x00, // version 0, 3 words of options, critical, ipv4, vni 42
+        0x01, 0x02, 0x80, 0x01, 0xde, 0xad, 0xbe, 0xef, // class 0x0102, critical type 0x80, 4 bytes of data
+        0xff, 0xff, 0x03, 0x00, // class 0xffff, type 3, no data
+    ];
+    packet.extend_from_slice(&buffer[..length]);
+
+    let geneve_pdu = GenevePdu::new(&packet)?;
+    assert_eq!(geneve_pdu.version(), 0);
+    assert_eq!(geneve_pdu.computed_ihl(), 20);
+    assert!(!geneve_pdu.oam());
+    assert!(geneve_pdu.critical());
+    assert_eq!(geneve_pdu.protocol_type(), EtherType::IPV4);
+    assert_eq!(geneve_pdu.vni(), 42);
+    let mut options = geneve_pdu.options();
+    match options.next() {
+        Some(GeneveOption::Raw { class: 0x0102, option_type: 0x80, data }) => {
+            assert_eq!(data, [0xde, 0xad, 0xbe, 0xef])
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(GeneveOption::Raw { class: 0xffff, option_type: 3, data }) => assert!(data.is_empty()),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(options.next().is_none());
+    match geneve_pdu.inner()? {
+        Geneve::Ipv4(ipv4_pdu) => match ipv4_pdu.inner()? {
+            Ipv4::Tcp(tcp_pdu) => assert_eq!(tcp_pdu.destination_port(), 443),
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert_eq!(GenevePdu::new(&packet[..19]).unwrap_err(), pdu::Error::Truncated);
+    packet[0] |= 0x40;
+    assert_eq!(GenevePdu::new(&packet).unwrap_err(), pdu::Error::Malformed);
+
+    Ok(())
+}
+
+#[test]
+fn test_gtpu() -> Result<(), Box<dyn Error>> {
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/ipv6-gtp6.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+    let packet = pcap.next()?;
+
+    let udp_pdu = match EthernetPdu::new(packet.data)?.into_inner()? {
+        Ethernet::Ipv6(ipv6_pdu) => match ipv6_pdu.into_inner()? {
+            Ipv6::Udp(udp_pdu) => udp_pdu,
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    };
+    let gtpu_pdu = match udp_pdu.into_inner()? {
+        Udp::Gtpu(gtpu_pdu) => gtpu_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(gtpu_pdu.version(), 1);
+    assert_eq!(gtpu_pdu.message_type(), GtpuMessageType::G_PDU);
+    assert_eq!(gtpu_pdu.teid(), 0x022be642);
+    assert_eq!(gtpu_pdu.sequence_number(), None);
+    assert_eq!(gtpu_pdu.computed_ihl(), 8);
+    assert_eq!(gtpu_pdu.length() as usize, gtpu_pdu.buffer().len() - 8);
+    match gtpu_pdu.inner()? {
+        Gtpu::Ipv6(ipv6_pdu) => match ipv6_pdu.inner()? {
+            Ipv6::Udp(udp_pdu) => assert_eq!(udp_pdu.source_port(), 53),
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // sequence number and a pdu session container (uplink, qfi 9) followed by an ipv4 t-pdu
+    let mut packet = vec![
+        0x36u8, 0xff, 0x00, 0x24, 0x00, 0x00, 0x00, 0x01, 0x12, 0x34, 0x00, 0x85, 0x01, 0x10, 0x09, 0x00,
+    ];
+    packet.extend_from_slice(&[
+        0x45, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x40, 0xfd, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x01, 0x0a, 0x00,
+        0x00, 0x02,
+    ]);
+    let gtpu_pdu = GtpuPdu::new(&packet)?;
+    assert_eq!(gtpu_pdu.computed_ihl(), 16);
+    assert_eq!(gtpu_pdu.sequence_number(), Some(0x1234));
+    assert_eq!(gtpu_pdu.npdu_number(), None);
+    assert_eq!(gtpu_pdu.next_extension_header_type(), Some(0x85));
+    let mut extension_headers = gtpu_pdu.extension_headers();
+    match extension_headers.next() {
+        Some(GtpuExtensionHeader::PduSessionContainer { pdu_type: 1, qfi: 9 }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(extension_headers.next().is_none());
+    match gtpu_pdu.inner()? {
+        Gtpu::Ipv4(ipv4_pdu) => assert_eq!(ipv4_pdu.destination_address(), [10, 0, 0, 2]),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert_eq!(GtpuPdu::new(&packet[..14]).unwrap_err(), pdu::Error::Truncated);
+    packet[12] = 0;
+    assert_eq!(GtpuPdu::new(&packet).unwrap_err(), pdu::Error::Malformed);
+
+    Ok(())
+}
+
+#[test]
+fn test_pptp() -> Result<(), Box<dyn Error>> {
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/PPTP_negotiation.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+
+    let mut protocols = Vec::new();
+    while let Ok(packet) = pcap.next() {
+        let gre_pdu = match EthernetPdu::new(packet.data)?.into_inner()? {
+            Ethernet::Ipv4(ipv4_pdu) => match ipv4_pdu.into_inner()? {
+                Ipv4::Gre(gre_pdu) => gre_pdu,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        assert_eq!(gre_pdu.version(), 1);
+        assert_eq!(gre_pdu.ethertype(), EtherType::PPP);
+        assert!(gre_pdu.call_id().is_some());
+        match gre_pdu.inner()? {
+            Gre::Ppp(ppp_pdu) => {
+                assert_eq!(ppp_pdu.address(), Some(0xff));
+                assert_eq!(ppp_pdu.control(), Some(0x03));
+                assert_eq!(ppp_pdu.computed_ihl(), 4);
+                protocols.push(ppp_pdu.protocol());
+                if let Ppp::Ipv4(ipv4_pdu) = ppp_pdu.inner()? {
+                    assert_eq!(ipv4_pdu.protocol(), IpProto::ICMP);
+                }
+            }
+            Gre::Raw(raw) => {
+                // acknowledgement-only frames carry no payload
+                assert_eq!(gre_pdu.payload_length(), Some(0));
+                assert!(gre_pdu.sequence_number().is_none());
+                assert!(gre_pdu.acknowledgement_number().is_some());
+                assert!(raw.iter().all(|b| *b == 0));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(protocols.contains(&PppProtocol::LCP));
+    assert!(protocols.contains(&PppProtocol::PAP));
+    assert!(protocols.contains(&PppProtocol::IPCP));
+    assert!(protocols.contains(&PppProtocol::IPV4));
+
+    let packet = [
+        0x30u8, 0x81, 0x88, 0x0b, 0x00, 0x0e, 0x00, 0x18, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x04, 0x21, 0x45,
+    ];
+    let gre_pdu = GrePdu::new(&packet)?;
+    assert_eq!(gre_pdu.computed_ihl(), 16);
+    assert_eq!(gre_pdu.payload_length(), Some(14));
+    assert_eq!(gre_pdu.call_id(), Some(0x18));
+    assert_eq!(gre_pdu.sequence_number(), Some(5));
+    assert_eq!(gre_pdu.acknowledgement_number(), Some(4));
+    let ppp_pdu = PppPdu::new(&packet[16..])?;
+    assert_eq!(ppp_pdu.address(), None);
+    assert_eq!(ppp_pdu.protocol(), PppProtocol::IPV4);
+    assert_eq!(ppp_pdu.computed_ihl(), 1);
+
+    assert_eq!(GrePdu::new(&[0x00, 0x01, 0x88, 0x0b]).unwrap_err(), pdu::Error::Malformed);
+    assert_eq!(GrePdu::new(&[0x20, 0x02, 0x88, 0x0b, 0x00, 0x00, 0x00, 0x00]).unwrap_err(), pdu::Error::Malformed);
+
+    let mut packet = packet;
+    let mut gre_pdu = GrePduMut::new(&mut packet)?;
+    gre_pdu.set_call_id(0x1234)?;
+    gre_pdu.set_acknowledgement_number(9)?;
+    gre_pdu.set_sequence_number(8)?;
+    assert_eq!(gre_pdu.set_version(0), Ok(()));
+    assert_eq!(gre_pdu.set_call_id(1), Err(pdu::Error::Malformed));
+    let gre_pdu = GrePdu::new(&packet)?;
+    assert_eq!(gre_pdu.key(), Some(0x000e1234));
+    assert_eq!(gre_pdu.sequence_number(), Some(8));
+    assert_eq!(gre_pdu.acknowledgement_number(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_erspan() -> Result<(), Box<dyn Error>> {
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/gre-erspan.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+
+    let mut count = 0;
+    while let Ok(packet) = pcap.next() {
+        let gre_pdu = match EthernetPdu::new(packet.data)?.into_inner()? {
+            Ethernet::Ipv4(ipv4_pdu) => match ipv4_pdu.into_inner()? {
+                Ipv4::Gre(gre_pdu) => gre_pdu,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        assert_eq!(gre_pdu.ethertype(), EtherType::ERSPAN_TYPE_II);
+        let erspan_pdu = match gre_pdu.into_inner()? {
+            Gre::Erspan(erspan_pdu) => erspan_pdu,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(erspan_pdu.version(), 1);
+        assert_eq!(erspan_pdu.vlan(), 0x017);
+        assert_eq!(erspan_pdu.session_id(), 0x064);
+        assert_eq!(erspan_pdu.index(), Some(0));
+        assert!(erspan_pdu.timestamp().is_none());
+        assert_eq!(erspan_pdu.computed_ihl(), 8);
+        match erspan_pdu.inner() {
+            Ok(Erspan::Ethernet(ethernet_pdu)) => {
+                assert!(ethernet_pdu.inner().is_ok());
+                count += 1;
+            }
+            // the capture also mirrors 802.3 (LLC) frames, which are not supported
+            Err(e) => assert_eq!(e, pdu::Error::Malformed),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(count > 0);
+
+    // erspan type I: no sequence number and no erspan header
+    let mut packet = vec![0x00u8, 0x00, 0x88, 0xbe];
+    packet.extend_from_slice(&[0xff; 12]);
+    packet.extend_from_slice(&[0x08, 0x06]);
+    match GrePdu::new(&packet)?.inner()? {
+        Gre::Ethernet(ethernet_pdu) => assert_eq!(ethernet_pdu.ethertype(), EtherType::ARP),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // erspan type III with a platform specific sub-header
+    let packet = [
+        0x20u8, 0x0a, 0xa4, 0x05, 0x01, 0x02, 0x03, 0x04, 0x00, 0x2a, 0x02, 0x39, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x07,
+    ];
+    let erspan_pdu = ErspanPdu::new(&packet)?;
+    assert_eq!(erspan_pdu.version(), 2);
+    assert_eq!(erspan_pdu.vlan(), 10);
+    assert_eq!(erspan_pdu.cos(), 5);
+    assert!(erspan_pdu.truncated());
+    assert_eq!(erspan_pdu.session_id(), 5);
+    assert_eq!(erspan_pdu.index(), None);
+    assert_eq!(erspan_pdu.timestamp(), Some(0x01020304));
+    assert_eq!(erspan_pdu.sgt(), Some(42));
+    assert_eq!(erspan_pdu.frame_type(), Some(0));
+    assert_eq!(erspan_pdu.hardware_id(), Some(0x23));
+    assert_eq!(erspan_pdu.direction(), Some(true));
+    assert_eq!(erspan_pdu.granularity(), Some(0));
+    assert!(erspan_pdu.has_platform_subheader());
+    assert_eq!(erspan_pdu.platform_id(), Some(3));
+    assert_eq!(erspan_pdu.platform_info(), Some(7));
+    assert_eq!(erspan_pdu.computed_ihl(), 20);
+    assert!(matches!(erspan_pdu.inner()?, Erspan::Raw(raw) if raw.is_empty()));
+    assert_eq!(ErspanPdu::new(&packet[..12]).unwrap_err(), pdu::Error::Truncated);
+    assert_eq!(ErspanPdu::new(&[0x30u8, 0, 0, 0, 0, 0, 0, 0]).unwrap_err(), pdu::Error::Malformed);
+
+    Ok(())
+}
+
+#[test]
+fn test_ipsec() -> Result<(), Box<dyn Error>> {
+    let icv = [0xa5u8; 12];
+    let payload = b"hello, world";
+
+    // ipv4 transport mode: ah followed by udp
+    let mut buffer = [0u8; 128];
+    let ah = AhBuilder::new().spi(0x1001).sequence_number(7).icv(&icv);
+    let ah_length = ah.computed_ihl();
+    let builder = PacketBuilder::new()
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .ah(ah)
+        .udp(UdpBuilder::new().source_port(500).destination_port(4500));
+    let length = builder.build(&mut buffer, payload)?;
+
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    let ah_pdu = match ipv4_pdu.inner()? {
+        Ipv4::Ah(ah_pdu) => ah_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(ah_pdu.next_header(), IpProto::UDP);
+    assert_eq!(ah_pdu.payload_length(), 4);
+    assert_eq!(ah_pdu.computed_ihl(), ah_length);
+    assert_eq!(ah_pdu.spi(), 0x1001);
+    assert_eq!(ah_pdu.sequence_number(), 7);
+    assert_eq!(ah_pdu.icv(), icv);
+    match ah_pdu.inner()? {
+        Ah::Udp(udp_pdu) => assert_eq!(udp_pdu.destination_port(), 4500),
+        other => panic!("unexpected {:?}", other),
+    }
+    let statuses: Vec<_> = Ip::Ipv4(ipv4_pdu).verify_checksums().collect();
+    assert_eq!(
+        statuses,
+        [
+            (Layer::Ipv4, ChecksumStatus::Good),
+            (Layer::Ah, ChecksumStatus::NotChecked),
+            (Layer::Udp, ChecksumStatus::Good)
+        ]
+    );
+    assert_eq!(AhPdu::new(&buffer[20..40]).unwrap_err(), pdu::Error::Truncated);
+
+    // the payload length field cannot describe an icv that is too long or not a multiple of 32 bits
+    let mut large = vec![0u8; 2048];
+    let long_icv = [0u8; 1024];
+    let builder = AhBuilder::new().icv(&long_icv);
+    assert_eq!(builder.build(&mut large, payload).unwrap_err(), pdu::Error::Malformed);
+    let builder = AhBuilder::new().icv(&icv[..10]);
+    assert_eq!(builder.build(&mut large, payload).unwrap_err(), pdu::Error::Malformed);
+
+    // the tcp pseudo-header length excludes the authentication header
+    let builder = PacketBuilder::new()
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .ah(ah)
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(80));
+    let length = builder.build(&mut buffer, payload)?;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv4_pdu.computed_upper_layer_length() as usize, 20 + payload.len());
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Tcp, ChecksumStatus::Good)));
+
+    // ipv6: ah is skipped as an extension header
+    let extension_headers = [Ipv6ExtensionHeader::Ah { spi: 0x2002, sequence_number: 9, icv: &icv }];
+    let builder = PacketBuilder::new()
+        .ipv6(Ipv6Builder::new().hop_limit(1).extension_headers(&extension_headers))
+        .udp(UdpBuilder::new().source_port(53).destination_port(53));
+    let length = builder.build(&mut buffer, payload)?;
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv6_pdu.next_header(), IpProto::AH);
+    assert_eq!(ipv6_pdu.computed_ihl(), 40 + ah_length);
+    assert_eq!(ipv6_pdu.computed_protocol(), IpProto::UDP);
+    match ipv6_pdu.extension_headers().next() {
+        Some(Ipv6ExtensionHeader::Ah { spi, sequence_number, icv: ah_icv }) => {
+            assert_eq!(spi, 0x2002);
+            assert_eq!(sequence_number, 9);
+            assert_eq!(ah_icv, icv);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let statuses: Vec<_> = Ip::Ipv6(ipv6_pdu).verify_checksums().collect();
+    assert_eq!(statuses, [(Layer::Ipv6, ChecksumStatus::NotChecked), (Layer::Udp, ChecksumStatus::Good)]);
+
+    let builder = PacketBuilder::new()
+        .ipv6(Ipv6Builder::new().hop_limit(1).extension_headers(&extension_headers))
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(80));
+    let length = builder.build(&mut buffer, payload)?;
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv6_pdu.computed_upper_layer_length() as usize, 20 + payload.len());
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Tcp, ChecksumStatus::Good)));
+
+    // esp payloads are opaque
+    let packet = [0x00u8, 0x00, 0x30, 0x03, 0x00, 0x00, 0x00, 0x2a, 0xde, 0xad, 0xbe, 0xef];
+    let esp_pdu = EspPdu::new(&packet)?;
+    assert_eq!(esp_pdu.spi(), 0x3003);
+    assert_eq!(esp_pdu.sequence_number(), 42);
+    assert_eq!(esp_pdu.payload(), [0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(EspPdu::new(&packet[..7]).unwrap_err(), pdu::Error::Truncated);
+
+    Ok(())
+}
+
+#[test]
+fn test_ipv4_options() -> Result<(), Box<dyn Error>> {
+    let first = [
+        0x94u8, 0x04, 0x00, 0x00, // router alert
+        0x07, 0x0b, 0x08, 0xc0, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, // record route
+        0x83, 0x07, 0x04, 0xc6, 0x33, 0x64, 0x01, // loose source route
+        0x44, 0x0c, 0x05, 0x01, 0xc0, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00, 0x2a, // timestamp and address
+        0x01, 0x00, // noop and end
+    ];
+    let second = [
+        0x44u8, 0x0c, 0x0d, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, // timestamp only
+        0x88, 0x04, 0x12, 0x34, // stream id
+        0x86, 0x08, 0x00, 0x00, 0x00, 0x03, 0x01, 0x02, // cipso
+        0x82, 0x04, 0x3d, 0x80, // security
+        0x89, 0x04, 0x00, 0x00, // strict source route with a malformed length
+        0x00, 0x00, 0x00, 0x00, // end and padding
+    ];
+    let mut buffers = [[0u8; 64]; 2];
+    let first_length =
+        Ipv4Builder::new().options(&[Ipv4Option::Raw { option: 0, data: &first }]).build(&mut buffers[0], &[])?;
+    let second_length =
+        Ipv4Builder::new().options(&[Ipv4Option::Raw { option: 0, data: &second }]).build(&mut buffers[1], &[])?;
+    let first_pdu = Ipv4Pdu::new(&buffers[0][..first_length])?;
+    let second_pdu = Ipv4Pdu::new(&buffers[1][..second_length])?;
+
+    let mut options = first_pdu.options();
+    match options.next() {
+        Some(Ipv4Option::RouterAlert { value }) => assert_eq!(value, 0),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::RecordRoute { pointer, addresses }) => {
+            assert_eq!(pointer, 8);
+            assert_eq!(addresses.collect::<Vec<_>>(), [[192, 0, 2, 1], [0, 0, 0, 0]]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::LooseSourceRoute { pointer, addresses }) => {
+            assert_eq!(pointer, 4);
+            assert_eq!(addresses.collect::<Vec<_>>(), [[198, 51, 100, 1]]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::Timestamp { pointer, overflow, flag, timestamps }) => {
+            assert_eq!((pointer, overflow, flag), (5, 0, 1));
+            assert_eq!(
+                timestamps.collect::<Vec<_>>(),
+                [Ipv4Timestamp { address: Some([192, 0, 2, 1]), timestamp: 42 }]
+            );
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(options.next(), Some(Ipv4Option::NoOp)));
+    assert!(matches!(options.next(), Some(Ipv4Option::End)));
+    assert!(options.next().is_none());
+
+    let mut options = second_pdu.options();
+    match options.next() {
+        Some(Ipv4Option::Timestamp { pointer, overflow, flag, timestamps }) => {
+            assert_eq!((pointer, overflow, flag), (13, 0, 0));
+            assert_eq!(
+                timestamps.collect::<Vec<_>>(),
+                [Ipv4Timestamp { address: None, timestamp: 1 }, Ipv4Timestamp { address: None, timestamp: 2 }]
+            );
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::StreamId { id }) => assert_eq!(id, 0x1234),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::Cipso { doi, tags }) => {
+            assert_eq!(doi, 3);
+            assert_eq!(tags, [0x01, 0x02]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::Security { classification, protection_authority }) => {
+            assert_eq!(classification, 0x3d);
+            assert_eq!(protection_authority, [0x80]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv4Option::Raw { option, data }) => {
+            assert_eq!(option, 137);
+            assert_eq!(data.len(), 4);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(options.next(), Some(Ipv4Option::End)));
+
+    // typed options serialize back to the same bytes
+    for pdu in [first_pdu, second_pdu].iter() {
+        let options: Vec<_> = pdu.options().collect();
+        let mut buffer = [0u8; 64];
+        let length = Ipv4Builder::new().options(&options).build(&mut buffer, &[])?;
+        assert_eq!(&buffer[..length], pdu.buffer());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_ipv6_options() -> Result<(), Box<dyn Error>> {
+    let hop_by_hop = [
+        0x11u8, 0x04, // next header and length
+        0x05, 0x02, 0x00, 0x00, // router alert (mld)
+        0x01, 0x00, // padn
+        0xc2, 0x04, 0x00, 0x01, 0x00, 0x00, // jumbo payload
+        0x31, 0x04, 0x00, 0x02, 0xaa, 0xbb, // ioam
+        0x00, // pad1
+        0x05, 0x03, 0x00, 0x00, 0x00, // router alert with a malformed length
+        0x9e, 0x02, 0x12, 0x34, // unknown, discard and send icmp
+        0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // padn
+    ];
+    let destination_options = [0x11u8, 0x00, 0x04, 0x01, 0x04, 0x01, 0x01, 0x00];
+    let extension_headers = [
+        Ipv6ExtensionHeader::Raw { header: 0, data: &hop_by_hop },
+        Ipv6ExtensionHeader::Raw { header: 60, data: &destination_options },
+    ];
+    let mut buffer = [0u8; 128];
+    let builder = Ipv6Builder::new().next_header(IpProto::UDP).extension_headers(&extension_headers);
+    let length = builder.build(&mut buffer, &[])?;
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv6_pdu.computed_ihl(), 40 + hop_by_hop.len() + destination_options.len());
+    assert_eq!(ipv6_pdu.computed_protocol(), IpProto::UDP);
+
+    let mut extension_headers = ipv6_pdu.extension_headers();
+    let mut options = match extension_headers.next() {
+        Some(Ipv6ExtensionHeader::HopByHop { options }) => options,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert!(matches!(options.next(), Some(Ipv6Option::RouterAlert { value: 0 })));
+    assert!(matches!(options.next(), Some(Ipv6Option::PadN { length: 0 })));
+    assert!(matches!(options.next(), Some(Ipv6Option::JumboPayload { length: 0x10000 })));
+    match options.next() {
+        Some(Ipv6Option::Ioam { option_type, data }) => {
+            assert_eq!(option_type, 2);
+            assert_eq!(data, [0xaa, 0xbb]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(options.next(), Some(Ipv6Option::Pad1)));
+    match options.next() {
+        Some(Ipv6Option::Raw { option, action, data }) => {
+            assert_eq!((option, action), (0x05, 0));
+            assert_eq!(data.len(), 5);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(Ipv6Option::Raw { option, action, data }) => {
+            assert_eq!((option, action), (0x9e, 2));
+            assert_eq!(data, [0x9e, 0x02, 0x12, 0x34]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(options.next(), Some(Ipv6Option::PadN { length: 8 })));
+    assert!(options.next().is_none());
+
+    let mut options = match extension_headers.next() {
+        Some(Ipv6ExtensionHeader::DestinationOptions { options }) => options,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert!(matches!(options.next(), Some(Ipv6Option::TunnelEncapsulationLimit { limit: 4 })));
+    assert!(matches!(options.next(), Some(Ipv6Option::PadN { length: 1 })));
+    assert!(options.next().is_none());
+    assert!(extension_headers.next().is_none());
+
+    // typed extension headers serialize back to the same bytes
+    let extension_headers: Vec<_> = ipv6_pdu.extension_headers().collect();
+    let mut rebuilt = [0u8; 128];
+    let builder = Ipv6Builder::new().next_header(IpProto::UDP).extension_headers(&extension_headers);
+    let rebuilt_length = builder.build(&mut rebuilt, &[])?;
+    assert_eq!(&rebuilt[..rebuilt_length], &buffer[..length]);
+
+    Ok(())
+}
+
+#[test]
+fn test_segment_routing() -> Result<(), Box<dyn Error>> {
+    let mut srh = vec![
+        IpProto::UDP, 0x06, 0x04, 0x01, 0x01, 0x00, 0x00, 0x2a, // two segments, one left
+    ];
+    srh.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02]); // final segment
+    srh.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01]); // active segment
+    srh.extend_from_slice(&[0x05, 0x0e, 0x80, 0x00, 0x00, 0x00, 0x00, 0x07]); // hmac tlv
+    srh.extend_from_slice(&[0xaa; 8]);
+    let extension_headers = [Ipv6ExtensionHeader::Raw { header: 43, data: &srh }];
+    let builder = Ipv6Builder::new()
+        .destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+        .next_header(IpProto::UDP)
+        .extension_headers(&extension_headers);
+    let mut buffer = [0u8; 128];
+    let length = builder.build(&mut buffer, &[])?;
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv6_pdu.computed_protocol(), IpProto::UDP);
+    assert_eq!(ipv6_pdu.computed_final_destination(), [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02]);
+
+    match ipv6_pdu.extension_headers().next() {
+        Some(Ipv6ExtensionHeader::SegmentRouting { segments_left, last_entry, flags, tag, segments, mut tlvs }) => {
+            assert_eq!((segments_left, last_entry, flags, tag), (1, 1, 0, 42));
+            assert_eq!(segments.map(|segment| segment[15]).collect::<Vec<_>>(), [2, 1]);
+            match tlvs.next() {
+                Some(SrhTlv::Hmac { destination_verification_disabled, key_id, hmac }) => {
+                    assert!(destination_verification_disabled);
+                    assert_eq!(key_id, 7);
+                    assert_eq!(hmac, [0xaa; 8]);
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+            assert!(tlvs.next().is_none());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // typed routing headers serialize back to the same bytes
+    let extension_headers: Vec<_> = ipv6_pdu.extension_headers().collect();
+    let mut rebuilt = [0u8; 128];
+    let rebuilt_length = builder.extension_headers(&extension_headers).build(&mut rebuilt, &[])?;
+    assert_eq!(&rebuilt[..rebuilt_length], &buffer[..length]);
+
+    // once no segments are left, the destination address is the final destination
+    buffer[40 + 3] = 0;
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv6_pdu.computed_final_destination(), ipv6_pdu.destination_address());
+
+    // other routing types are decoded generically
+    let mut routing = vec![IpProto::UDP, 0x02, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00];
+    routing.extend_from_slice(&[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x09]);
+    let extension_headers = [Ipv6ExtensionHeader::Raw { header: 43, data: &routing }];
+    let builder = Ipv6Builder::new().next_header(IpProto::UDP).extension_headers(&extension_headers);
+    let length = builder.build(&mut buffer, &[])?;
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    match ipv6_pdu.extension_headers().next() {
+        Some(Ipv6ExtensionHeader::Routing { routing_type, segments_left, data }) => {
+            assert_eq!((routing_type, segments_left), (2, 1));
+            assert_eq!(data.len(), 20);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(ipv6_pdu.computed_final_destination(), [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x09]);
+
+    Ok(())
+}
+
+#[test]
+fn test_ipv6_pseudo_header() -> Result<(), Box<dyn Error>> {
+    let final_destination = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02];
+    let mut srh = vec![IpProto::TCP, 0x04, 0x04, 0x01, 0x01, 0x00, 0x00, 0x00];
+    srh.extend_from_slice(&final_destination);
+    srh.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+    let destination_options = [IpProto::TCP, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00];
+    let extension_headers = [
+        Ipv6ExtensionHeader::Raw { header: 60, data: &destination_options },
+        Ipv6ExtensionHeader::Raw { header: 43, data: &srh },
+    ];
+    let builder = PacketBuilder::new()
+        .ipv6(
+            Ipv6Builder::new()
+                .source_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0a])
+                .destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+                .extension_headers(&extension_headers),
+        )
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(80).flags(TcpFlag::SYN));
+    let mut buffer = [0u8; 192];
+    let length = builder.build(&mut buffer, b"hello, world")?;
+
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv6_pdu.computed_final_destination(), final_destination);
+    assert_eq!(ipv6_pdu.computed_upper_layer_length() as usize, length - ipv6_pdu.computed_ihl());
+    let tcp_pdu = match ipv6_pdu.inner()? {
+        Ipv6::Tcp(tcp_pdu) => tcp_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    let mut segment = tcp_pdu.buffer().to_vec();
+    segment[16..=17].copy_from_slice(&[0, 0]);
+    let expected = util::checksum([
+        ipv6_pdu.source_address().as_ref(),
+        final_destination.as_ref(),
+        (segment.len() as u32).to_be_bytes().as_ref(),
+        [0, 0, 0, IpProto::TCP].as_ref(),
+        &segment,
+    ]);
+    assert_eq!(tcp_pdu.checksum(), expected);
+    assert_eq!(tcp_pdu.computed_checksum(&Ip::Ipv6(ipv6_pdu)), expected);
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Tcp, ChecksumStatus::Good)));
+
+    // rewriting the destination address does not change the pseudo-header while segments are left
+    let mut ipv6_pdu = Ipv6PduMut::new(&mut buffer[..length])?;
+    ipv6_pdu.set_destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x03]);
+    ipv6_pdu.set_source_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0b]);
+    let ipv6_pdu = Ipv6Pdu::new(&buffer[..length])?;
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Tcp, ChecksumStatus::Good)));
+
+    // tcp over ipv4 with an authentication header excludes the authentication header from its length
+    let icv = [0xa5u8; 12];
+    let builder = PacketBuilder::new()
+        .ipv4(Ipv4Builder::new().source_address([192, 0, 2, 1]).destination_address([192, 0, 2, 2]))
+        .ah(AhBuilder::new().spi(0x1001).sequence_number(7).icv(&icv))
+        .tcp(TcpBuilder::new().source_port(49152).destination_port(80));
+    let mut buffer = [0u8; 128];
+    let length = builder.build(&mut buffer, &[])?;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    assert_eq!(ipv4_pdu.computed_upper_layer_length(), 20);
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Tcp, ChecksumStatus::Good)));
+
+    // every transport checksum in the capture verifies across hop-by-hop, segment routing, and authentication headers
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/ipv6-extension-headers-and-ah.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+    let mut count = 0;
+    while let Ok(packet) = pcap.next() {
+        let statuses = EthernetPdu::new(packet.data)?.verify_checksums().collect::<Vec<_>>();
+        assert!(statuses.iter().any(|(layer, status)| {
+            (*layer == Layer::Tcp || *layer == Layer::Udp) && *status == ChecksumStatus::Good
+        }));
+        assert!(!statuses.iter().any(|(_, status)| *status == ChecksumStatus::Bad));
+        count += 1;
+    }
+    assert_eq!(count, 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_ipv4_reassembly() -> Result<(), Box<dyn Error>> {
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/ipv4-udp-fragmented.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+
+    let mut fragments = Vec::new();
+    let mut reassembler = Ipv4Reassembler::new();
+    let mut reassembled = None;
+    let mut count = 0;
+    while let Ok(packet) = pcap.next() {
+        count += 1;
+        let ipv4_pdu = match EthernetPdu::new(packet.data)?.into_inner()? {
+            Ethernet::Ipv4(ipv4_pdu) => ipv4_pdu,
+            _ => continue,
+        };
+        match reassembler.reassemble(&ipv4_pdu, std::time::Duration::from_millis(count)) {
+            Reassembly::Unfragmented => continue,
+            Reassembly::Pending => assert!(!reassembler.is_empty()),
+            Reassembly::Complete(buffer) => reassembled = Some(buffer),
+            other => panic!("unexpected {:?}", other),
+        }
+        fragments.push(ipv4_pdu.into_buffer().to_vec());
+    }
+    assert_eq!(fragments.len(), 4);
+    assert!(reassembler.is_empty());
+    assert_eq!(reassembler.computed_bytes(), 0);
+
+    let reassembled = reassembled.expect("datagram was not reassembled");
+    let ipv4_pdu = Ipv4Pdu::new(&reassembled)?;
+    assert_eq!(ipv4_pdu.total_length() as usize, reassembled.len());
+    assert_eq!(ipv4_pdu.total_length(), 20 + 4440 + 947);
+    assert_eq!(ipv4_pdu.identification(), 62895);
+    assert!(!ipv4_pdu.more_fragments());
+    assert_eq!(ipv4_pdu.fragment_offset(), 0);
+    assert_eq!(ipv4_pdu.verify_checksum(), ChecksumStatus::Good);
+    match ipv4_pdu.inner()? {
+        Ipv4::Udp(udp_pdu) => assert_eq!(udp_pdu.length() as usize, reassembled.len() - 20),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Udp, ChecksumStatus::Good)));
+
+    // fragments may arrive in any order
+    let mut reassembler = Ipv4Reassembler::new().overlap_policy(OverlapPolicy::Discard);
+    for (i, fragment) in fragments.iter().rev().enumerate() {
+        let ipv4_pdu = Ipv4Pdu::new(fragment)?;
+        match reassembler.reassemble(&ipv4_pdu, std::time::Duration::from_secs(0)) {
+            Reassembly::Pending if i < 3 => {}
+            Reassembly::Complete(buffer) if i == 3 => assert_eq!(buffer, reassembled),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    let fragment = |id: u16, protocol: u8, offset: u16, more_fragments: bool, payload: &[u8]| {
+        let mut buffer = vec![0u8; 20 + payload.len()];
+        Ipv4Builder::new()
+            .identification(id)
+            .protocol(protocol)
+            .fragment_offset(offset / 8)
+            .more_fragments(more_fragments)
+            .source_address([192, 0, 2, 1])
+            .destination_address([192, 0, 2, 2])
+            .build(&mut buffer, payload)
+            .unwrap();
+        buffer
+    };
+    let now = std::time::Duration::from_secs(100);
+
+    // overlapping data is resolved according to the overlap policy
+    for (policy, expected) in &[
+        (OverlapPolicy::FirstWins, Some(b"AAAAAAAABBBBBBBBDDDDDDDD".to_vec())),
+        (OverlapPolicy::LastWins, Some(b"AAAAAAAACCCCCCCCDDDDDDDD".to_vec())),
+        (OverlapPolicy::Discard, None),
+    ] {
+        let mut reassembler = Ipv4Reassembler::new().overlap_policy(*policy);
+        let first = fragment(1, IpProto::UDP, 0, true, b"AAAAAAAABBBBBBBB");
+        assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&first)?, now), Reassembly::Pending);
+        // exact duplicates do not count as overlaps
+        assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&first)?, now), Reassembly::Pending);
+        let overlap = fragment(1, IpProto::UDP, 8, true, b"CCCCCCCC");
+        let last = fragment(1, IpProto::UDP, 16, false, b"DDDDDDDD");
+        match (reassembler.reassemble(&Ipv4Pdu::new(&overlap)?, now), expected) {
+            (Reassembly::Pending, Some(expected)) => match reassembler.reassemble(&Ipv4Pdu::new(&last)?, now) {
+                Reassembly::Complete(buffer) => assert_eq!(&buffer[20..], &expected[..]),
+                other => panic!("unexpected {:?}", other),
+            },
+            (Reassembly::Dropped(DropReason::Overlap), None) => assert!(reassembler.is_empty()),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    // teardrop: a fragment beyond the end of the datagram discards the datagram
+    let mut reassembler = Ipv4Reassembler::new();
+    let last = fragment(2, IpProto::UDP, 16, false, b"DDDDDDDD");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&last)?, now), Reassembly::Pending);
+    let beyond = fragment(2, IpProto::UDP, 24, true, b"EEEEEEEE");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&beyond)?, now), Reassembly::Dropped(DropReason::Inconsistent));
+    assert!(reassembler.is_empty());
+
+    // tiny fragments of tcp headers are dropped (rfc1858)
+    let tiny = fragment(3, IpProto::TCP, 0, true, b"AAAAAAAA");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&tiny)?, now), Reassembly::Dropped(DropReason::TinyFragment));
+    let tiny = fragment(3, IpProto::TCP, 8, false, b"AAAAAAAA");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&tiny)?, now), Reassembly::Dropped(DropReason::TinyFragment));
+    let unaligned = fragment(3, IpProto::UDP, 0, true, b"AAAAAAAAA");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&unaligned)?, now), Reassembly::Dropped(DropReason::Malformed));
+    let truncated = fragment(3, IpProto::UDP, 0, true, b"AAAAAAAAAAAAAAAA");
+    let truncated = Ipv4Pdu::new(&truncated[..28])?;
+    assert_eq!(reassembler.reassemble(&truncated, now), Reassembly::Dropped(DropReason::Truncated));
+    let unfragmented = fragment(3, IpProto::UDP, 0, false, b"AAAAAAAA");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&unfragmented)?, now), Reassembly::Unfragmented);
+
+    // incomplete datagrams expire after the timeout
+    let mut reassembler = Ipv4Reassembler::new().timeout(std::time::Duration::from_secs(30));
+    let first = fragment(4, IpProto::UDP, 0, true, b"AAAAAAAA");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&first)?, now), Reassembly::Pending);
+    assert_eq!(reassembler.expire(now + std::time::Duration::from_secs(29)), 0);
+    let last = fragment(4, IpProto::UDP, 8, false, b"BBBBBBBB");
+    let later = now + std::time::Duration::from_secs(30);
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&last)?, later), Reassembly::Pending);
+    assert_eq!(reassembler.len(), 1);
+    assert_eq!(reassembler.expire(later + std::time::Duration::from_secs(30)), 1);
+    assert!(reassembler.is_empty());
+
+    // buffered data is limited
+    let mut reassembler = Ipv4Reassembler::new().max_bytes(40).max_datagrams(1);
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&first)?, now), Reassembly::Pending);
+    assert_eq!(reassembler.computed_bytes(), 28);
+    let other = fragment(5, IpProto::UDP, 0, true, b"AAAAAAAA");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&other)?, now), Reassembly::Dropped(DropReason::MemoryLimit));
+    let large = fragment(4, IpProto::UDP, 8, true, b"BBBBBBBBBBBBBBBB");
+    assert_eq!(reassembler.reassemble(&Ipv4Pdu::new(&large)?, now), Reassembly::Dropped(DropReason::MemoryLimit));
+    assert_eq!(reassembler.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_ipv6_reassembly() -> Result<(), Box<dyn Error>> {
+    let pcap_file = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pcaps/ipv6-udp-fragmented.pcap");
+    let mut pcap = pcap::Capture::from_file(&pcap_file)?;
+
+    let mut reassembler = Ipv6Reassembler::new();
+    let mut reassembled = None;
+    let mut count = 0;
+    while let Ok(packet) = pcap.next() {
+        count += 1;
+        let ipv6_pdu = match EthernetPdu::new(packet.data)?.into_inner()? {
+            Ethernet::Ipv6(ipv6_pdu) => ipv6_pdu,
+            _ => continue,
+        };
+        match reassembler.reassemble(&ipv6_pdu, std::time::Duration::from_millis(count)) {
+            Reassembly::Unfragmented => assert!(ipv6_pdu.computed_identification().is_none()),
+            Reassembly::Pending => assert_eq!(reassembler.len(), 1),
+            Reassembly::Complete(buffer) => reassembled = Some(buffer),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(reassembler.is_empty());
+
+    let reassembled = reassembled.expect("packet was not reassembled");
+    let ipv6_pdu = Ipv6Pdu::new(&reassembled)?;
+    assert_eq!(ipv6_pdu.payload_length() as usize, reassembled.len() - 40);
+    assert_eq!(ipv6_pdu.payload_length(), 4344 + 1043);
+    assert_eq!(ipv6_pdu.computed_ihl(), 40);
+    assert!(ipv6_pdu.computed_identification().is_none());
+    match ipv6_pdu.inner()? {
+        Ipv6::Udp(udp_pdu) => assert_eq!(udp_pdu.length(), ipv6_pdu.payload_length()),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Udp, ChecksumStatus::Good)));
+
+    // the unfragmentable part of the first fragment precedes the reassembled payload
+    let hop_by_hop = [0x00, 0x00, 0x05, 0x02, 0x00, 0x00, 0x01, 0x00];
+    let mut segment = [0u8; 64];
+    let tcp_builder = TcpBuilder::new().source_port(49152).destination_port(80).flags(TcpFlag::SYN);
+    let fragment = |identification: u32, offset: u16, more_fragments: bool, payload: &[u8]| {
+        let extension_headers = [
+            Ipv6ExtensionHeader::Raw { header: 0, data: &hop_by_hop },
+            Ipv6ExtensionHeader::Fragment { identification, offset: offset / 8, more_fragments },
+        ];
+        let mut buffer = vec![0u8; 56 + payload.len()];
+        Ipv6Builder::new()
+            .next_header(IpProto::TCP)
+            .source_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+            .destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02])
+            .extension_headers(&extension_headers)
+            .build(&mut buffer, payload)
+            .unwrap();
+        buffer
+    };
+    let mut header = [0u8; 40];
+    Ipv6Builder::new()
+        .next_header(IpProto::TCP)
+        .source_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+        .destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02])
+        .build(&mut header, &[])?;
+    let length = tcp_builder.build(&mut segment, &Ip::new(&header)?, b"hello, fragmented world")?;
+    let now = std::time::Duration::from_secs(100);
+
+    let mut reassembler = Ipv6Reassembler::new();
+    let last = fragment(1, 24, false, &segment[24..length]);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&last)?, now), Reassembly::Pending);
+    let first = fragment(1, 0, true, &segment[..24]);
+    let reassembled = match reassembler.reassemble(&Ipv6Pdu::new(&first)?, now) {
+        Reassembly::Complete(buffer) => buffer,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(&reassembled[8..40], &header[8..40]);
+    assert_eq!(&reassembled[40..48], &[IpProto::TCP, 0x00, 0x05, 0x02, 0x00, 0x00, 0x01, 0x00]);
+    let ipv6_pdu = Ipv6Pdu::new(&reassembled)?;
+    assert_eq!(ipv6_pdu.payload_length() as usize, 8 + length);
+    assert_eq!(ipv6_pdu.computed_protocol(), IpProto::TCP);
+    match ipv6_pdu.inner()? {
+        Ipv6::Tcp(tcp_pdu) => assert_eq!(tcp_pdu.destination_port(), 80),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Tcp, ChecksumStatus::Good)));
+
+    // overlapping fragments discard the entire packet (rfc5722), but exact duplicates are ignored
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&first)?, now), Reassembly::Pending);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&first)?, now), Reassembly::Pending);
+    let overlap = fragment(1, 16, true, &segment[16..32]);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&overlap)?, now), Reassembly::Dropped(DropReason::Overlap));
+    assert!(reassembler.is_empty());
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&last)?, now), Reassembly::Pending);
+
+    // atomic fragments are not buffered (rfc6946)
+    let atomic = fragment(1, 0, false, &segment[..length]);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&atomic)?, now), Reassembly::Unfragmented);
+    assert_eq!(reassembler.len(), 1);
+
+    // the first fragment must contain the upper-layer header (rfc7112)
+    let tiny = fragment(2, 0, true, &segment[..16]);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&tiny)?, now), Reassembly::Dropped(DropReason::TinyFragment));
+    let unaligned = fragment(2, 0, true, &segment[..28]);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&unaligned)?, now), Reassembly::Dropped(DropReason::Malformed));
+
+    // incomplete packets expire after the timeout
+    assert_eq!(reassembler.expire(now + std::time::Duration::from_secs(60)), 1);
+    assert!(reassembler.is_empty());
+    assert_eq!(reassembler.computed_bytes(), 0);
+
+    // the fragment header is located by the type of each preceding header, not by the next header it refers to
+    let icv = [0xa5u8; 12];
+    let authenticated = |offset: u16, more_fragments: bool, payload: &[u8]| {
+        let extension_headers = [
+            Ipv6ExtensionHeader::Raw { header: 0, data: &hop_by_hop },
+            Ipv6ExtensionHeader::Ah { spi: 0x100, sequence_number: 1, icv: &icv },
+            Ipv6ExtensionHeader::Fragment { identification: 3, offset: offset / 8, more_fragments },
+        ];
+        let mut buffer = vec![0u8; 80 + payload.len()];
+        Ipv6Builder::new()
+            .next_header(IpProto::TCP)
+            .source_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+            .destination_address([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02])
+            .extension_headers(&extension_headers)
+            .build(&mut buffer, payload)
+            .unwrap();
+        buffer
+    };
+    let mut reassembler = Ipv6Reassembler::new();
+    let first = authenticated(0, true, &segment[..24]);
+    assert_eq!(reassembler.reassemble(&Ipv6Pdu::new(&first)?, now), Reassembly::Pending);
+    let last = authenticated(24, false, &segment[24..length]);
+    let reassembled = match reassembler.reassemble(&Ipv6Pdu::new(&last)?, now) {
+        Reassembly::Complete(buffer) => buffer,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(&reassembled[40..48], &[IpProto::AH, 0x00, 0x05, 0x02, 0x00, 0x00, 0x01, 0x00]);
+    assert_eq!(&reassembled[48..50], &[IpProto::TCP, 4]);
+    assert_eq!(&reassembled[72..], &segment[..length]);
+    let ipv6_pdu = Ipv6Pdu::new(&reassembled)?;
+    assert_eq!(ipv6_pdu.payload_length() as usize, 32 + length);
+    assert_eq!(ipv6_pdu.computed_ihl(), 72);
+    assert!(ipv6_pdu.computed_identification().is_none());
+
+    // fragments that end before their payload length says they do are dropped
+    let mut truncated = authenticated(0, true, &segment[..24]);
+    truncated.truncate(96);
+    let ipv6_pdu = Ipv6Pdu::new(&truncated)?;
+    assert_eq!(reassembler.reassemble(&ipv6_pdu, now), Reassembly::Dropped(DropReason::Truncated));
+    assert!(reassembler.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_icmp() -> Result<(), Box<dyn Error>> {
+    let mut ip = [0u8; 20];
+    Ipv4Builder::new().protocol(IpProto::ICMP).build(&mut ip, &[])?;
+    let ip = Ip::new(&ip)?;
+    let mut buffer = [0u8; 128];
+
+    let length = IcmpBuilder::new().message_type(IcmpType::ECHO_REQUEST).build(
+        &mut buffer,
+        &ip,
+        &[0x12, 0x34, 0x00, 0x07, b'p', b'i', b'n', b'g'],
+    )?;
+    let icmp_pdu = IcmpPdu::new_icmpv4(&buffer[..length])?;
+    assert!(icmp_pdu.is_icmpv4());
+    assert!(!icmp_pdu.is_icmpv6());
+    assert_eq!(icmp_pdu.computed_original_transport(), None);
+    match icmp_pdu.inner()? {
+        Icmp::Icmpv4(Icmpv4::EchoRequest { identifier, sequence_number, data }) => {
+            assert_eq!(identifier, 0x1234);
+            assert_eq!(sequence_number, 7);
+            assert_eq!(data, b"ping");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // ethernet padding after a minimum-size echo request is not echo data
+    let length = IcmpBuilder::new().message_type(IcmpType::ECHO_REQUEST).build(&mut buffer, &ip, &[0, 1, 0, 1])?;
+    let mut datagram = [0u8; 64];
+    let length = Ipv4Builder::new().protocol(IpProto::ICMP).build(&mut datagram, &buffer[..length])?;
+    let mut frame = [0u8; 60];
+    EthernetBuilder::new().ethertype(EtherType::IPV4).build(&mut frame, &datagram[..length])?;
+    frame[14 + length..].copy_from_slice(&[0xff; 18]);
+    let ipv4_pdu = match EthernetPdu::new(&frame)?.into_inner()? {
+        Ethernet::Ipv4(ipv4_pdu) => ipv4_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    match ipv4_pdu.inner()? {
+        Ipv4::Icmp(icmp_pdu) => match icmp_pdu.inner()? {
+            Icmp::Icmpv4(Icmpv4::EchoRequest { data, .. }) => assert!(data.is_empty()),
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Icmp, ChecksumStatus::Good)));
+
+    // the quoted datagram of an error message is truncated after the first 8 bytes of its payload
+    let mut original = [0u8; 28];
+    Ipv4Builder::new()
+        .protocol(IpProto::UDP)
+        .source_address([192, 0, 2, 1])
+        .destination_address([198, 51, 100, 1])
+        .build(&mut original, &[0x82, 0x9b, 0x82, 0x9c, 0x05, 0xdc, 0x00, 0x00])?;
+    original[2..=3].copy_from_slice(&1500u16.to_be_bytes());
+    let mut message = vec![0x00, 0x00, 0x05, 0x78];
+    message.extend_from_slice(&original);
+    let length = IcmpBuilder::new()
+        .message_type(IcmpType::DESTINATION_UNREACHABLE)
+        .message_code(4)
+        .build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv4(&buffer[..length])?.inner()? {
+        Icmp::Icmpv4(Icmpv4::DestinationUnreachable { next_hop_mtu, original: Some(original) }) => {
+            assert_eq!(next_hop_mtu, Some(1400));
+            assert_eq!(original.total_length(), 1500);
+            assert_eq!(original.destination_address(), [198, 51, 100, 1]);
+            match original.inner()? {
+                Ipv4::Udp(udp_pdu) => {
+                    assert_eq!(udp_pdu.source_port(), 33435);
+                    assert_eq!(udp_pdu.destination_port(), 33436);
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let icmp_pdu = IcmpPdu::new_icmpv4(&buffer[..length])?;
+    assert_eq!(icmp_pdu.computed_original_transport(), Some(&original[20..]));
+    assert_eq!(icmp_pdu.computed_original_ports(), Some((33435, 33436)));
+
+    // the ports of a quoted tcp segment are available even though its header is incomplete
+    let mut quoted = message.clone();
+    quoted[4 + 9] = IpProto::TCP;
+    let length = IcmpBuilder::new().message_type(IcmpType::TIME_EXCEEDED).build(&mut buffer, &ip, &quoted)?;
+    let icmp_pdu = IcmpPdu::new_icmpv4(&buffer[..length])?;
+    match icmp_pdu.inner()? {
+        Icmp::Icmpv4(Icmpv4::TimeExceeded { original: Some(original) }) => assert!(original.inner().is_err()),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(icmp_pdu.computed_original_ports(), Some((33435, 33436)));
+
+    let length = IcmpBuilder::new().message_type(IcmpType::DESTINATION_UNREACHABLE).message_code(3).build(
+        &mut buffer,
+        &ip,
+        &message,
+    )?;
+    match IcmpPdu::new_icmpv4(&buffer[..length])?.inner()? {
+        Icmp::Icmpv4(Icmpv4::DestinationUnreachable { next_hop_mtu: None, original: Some(_) }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+
+    message[..4].copy_from_slice(&[192, 0, 2, 254]);
+    let length = IcmpBuilder::new().message_type(IcmpType::REDIRECT).message_code(1).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv4(&buffer[..length])?.inner()? {
+        Icmp::Icmpv4(Icmpv4::Redirect { gateway, original: Some(original) }) => {
+            assert_eq!(gateway, [192, 0, 2, 254]);
+            assert_eq!(original.source_address(), [192, 0, 2, 1]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    message[..4].copy_from_slice(&[0, 0, 0, 0]);
+    let length = IcmpBuilder::new().message_type(IcmpType::TIME_EXCEEDED).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv4(&buffer[..length])?.inner()? {
+        Icmp::Icmpv4(Icmpv4::TimeExceeded { original: Some(original) }) => {
+            assert_eq!(original.protocol(), IpProto::UDP)
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // an unparseable quoted datagram is not an error
+    message[..4].copy_from_slice(&[20, 0, 0, 0]);
+    let length =
+        IcmpBuilder::new().message_type(IcmpType::PARAMETER_PROBLEM).build(&mut buffer, &ip, &message[..12])?;
+    match IcmpPdu::new_icmpv4(&buffer[..length])?.inner()? {
+        Icmp::Icmpv4(Icmpv4::ParameterProblem { pointer, original: None }) => assert_eq!(pointer, 20),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let length = IcmpBuilder::new().message_type(IcmpType::TIMESTAMP_REPLY).build(
+        &mut buffer,
+        &ip,
+        &[0, 1, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5],
+    )?;
+    match IcmpPdu::new_icmpv4(&buffer[..length])?.inner()? {
+        Icmp::Icmpv4(Icmpv4::TimestampReply { identifier, sequence_number, originate, receive, transmit }) => {
+            assert_eq!((identifier, sequence_number), (1, 2));
+            assert_eq!((originate, receive, transmit), (3, 4, 5));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match IcmpPdu::new_icmpv4(&buffer[..length - 4])?.inner()? {
+        Icmp::Raw(rest) => assert_eq!(rest.len(), length - 8),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // messages of an unknown address family are not decoded
+    let icmp_pdu = IcmpPdu::new(&buffer[..length])?;
+    assert!(!icmp_pdu.is_icmpv4() && !icmp_pdu.is_icmpv6());
+    assert_eq!(icmp_pdu.computed_original_ports(), None);
+    match icmp_pdu.inner()? {
+        Icmp::Raw(rest) => assert_eq!(rest, &buffer[4..length]),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // icmpv6 messages are not decoded as icmpv4 messages
+    let length = IcmpBuilder::new().message_type(IcmpType::TIME_EXCEEDED).build(&mut buffer, &ip, &message)?;
+    let icmp_pdu = IcmpPdu::new_icmpv6(&buffer[..length])?;
+    assert!(icmp_pdu.is_icmpv6());
+    match icmp_pdu.inner()? {
+        Icmp::Raw(_) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_icmpv6() -> Result<(), Box<dyn Error>> {
+    let mut ip = [0u8; 40];
+    Ipv6Builder::new()
+        .next_header(IpProto::ICMP6)
+        .source_address([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0x02, 0x00, 0x5e, 0xff, 0xfe, 0x00, 0x53, 0x01])
+        .destination_address([0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+        .build(&mut ip, &[])?;
+    let ip = Ip::new(&ip)?;
+    let mut buffer = [0u8; 256];
+
+    #[rustfmt::skip]
+    let advertisement = [
+        0x40, 0xc0, 0x07, 0x08, 0x00, 0x00, 0x75, 0x30, 0x00, 0x00, 0x03, 0xe8,
+        // source link-layer address
+        0x01, 0x01, 0x00, 0x00, 0x5e, 0x00, 0x53, 0x01,
+        // mtu
+        0x05, 0x01, 0x00, 0x00, 0x00, 0x00, 0x05, 0xdc,
+        // prefix information
+        0x03, 0x04, 0x40, 0xc0, 0x00, 0x27, 0x8d, 0x00, 0x00, 0x09, 0x3a, 0x80, 0x00, 0x00, 0x00, 0x00,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // recursive dns server
+        0x19, 0x05, 0x00, 0x00, 0x00, 0x00, 0x0e, 0x10,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x53,
+        0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x53,
+        // dns search list
+        0x1f, 0x04, 0x00, 0x00, 0x00, 0x00, 0x0e, 0x10,
+        0x07, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0x03, b'c', b'o', b'm', 0x00, 0x04, b'c', b'o',
+        b'r', b'p', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // unknown
+        0xfe, 0x01, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+    ];
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::ROUTER_ADVERTISEMENT).build(
+        &mut buffer,
+        &ip,
+        &advertisement,
+    )?;
+    let icmp_pdu = IcmpPdu::new_icmpv6(&buffer[..length])?;
+    let mut options = match icmp_pdu.inner()? {
+        Icmp::Icmpv6(Icmpv6::RouterAdvertisement {
+            current_hop_limit,
+            managed_flag,
+            other_flag,
+            router_lifetime,
+            reachable_time,
+            retrans_timer,
+            options,
+        }) => {
+            assert_eq!(current_hop_limit, 64);
+            assert!(managed_flag && other_flag);
+            assert_eq!(router_lifetime, 1800);
+            assert_eq!(reachable_time, 30000);
+            assert_eq!(retrans_timer, 1000);
+            options
+        }
+        other => panic!("unexpected {:?}", other),
+    };
+    match options.next() {
+        Some(NdpOption::SourceLinkLayerAddress { address }) => {
+            assert_eq!(address, [0x00, 0x00, 0x5e, 0x00, 0x53, 0x01])
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(NdpOption::Mtu { mtu }) => assert_eq!(mtu, 1500),
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(NdpOption::PrefixInformation {
+            prefix_length,
+            on_link,
+            autonomous,
+            valid_lifetime,
+            preferred_lifetime,
+            prefix,
+        }) => {
+            assert_eq!(prefix_length, 64);
+            assert!(on_link && autonomous);
+            assert_eq!(valid_lifetime, 2592000);
+            assert_eq!(preferred_lifetime, 604800);
+            assert_eq!(prefix[..6], [0x20, 0x01, 0x0d, 0xb8, 0x00, 0x01]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(NdpOption::RecursiveDnsServer { lifetime, addresses }) => {
+            assert_eq!(lifetime, 3600);
+            let addresses = addresses.collect::<Vec<_>>();
+            assert_eq!(addresses.len(), 2);
+            assert_eq!(addresses[1][14..], [0x05, 0x53]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(NdpOption::DnsSearchList { lifetime, domains }) => {
+            assert_eq!(lifetime, 3600);
+            let domains = domains.map(|labels| labels.collect::<Vec<_>>()).collect::<Vec<_>>();
+            assert_eq!(domains, vec![vec![&b"example"[..], &b"com"[..]], vec![&b"corp"[..]]]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match options.next() {
+        Some(NdpOption::Raw { option_type, data }) => {
+            assert_eq!(option_type, 0xfe);
+            assert_eq!(data, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(options.next().is_none());
+
+    let mut message = vec![0xe0, 0x00, 0x00, 0x00];
+    message.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+    message.extend_from_slice(&[0x02, 0x01, 0x00, 0x00, 0x5e, 0x00, 0x53, 0x02]);
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::NEIGHBOR_ADVERTISEMENT).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::NeighborAdvertisement {
+            router_flag,
+            solicited_flag,
+            override_flag,
+            target_address,
+            mut options,
+        }) => {
+            assert!(router_flag && solicited_flag && override_flag);
+            assert_eq!(target_address[15], 0x01);
+            match options.next() {
+                Some(NdpOption::TargetLinkLayerAddress { address }) => assert_eq!(address[5], 0x02),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // a zero-length option stops iteration
+    message[0] = 0;
+    message[21] = 0;
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::NEIGHBOR_SOLICITATION).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::NeighborSolicitation { target_address, mut options }) => {
+            assert_eq!(target_address[0], 0x20);
+            assert!(options.next().is_none());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::REDIRECT).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Raw(rest) => assert_eq!(rest.len(), message.len()),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // error messages quote the invoking packet
+    let mut message = vec![0x00, 0x00, 0x05, 0x00];
+    let mut original = [0u8; 48];
+    Ipv6Builder::new().next_header(IpProto::UDP).build(&mut original, &[0xc0, 0x00, 0x00, 0x35, 0x05, 0xdc, 0, 0])?;
+    message.extend_from_slice(&original);
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::PACKET_TOO_BIG).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::PacketTooBig { mtu, original: Some(original) }) => {
+            assert_eq!(mtu, 1280);
+            match original.inner()? {
+                Ipv6::Udp(udp_pdu) => assert_eq!(udp_pdu.destination_port(), 53),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::PARAMETER_PROBLEM).build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::ParameterProblem { pointer, original: Some(_) }) => assert_eq!(pointer, 0x500),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(IcmpPdu::new_icmpv6(&buffer[..length])?.computed_original_ports(), Some((49152, 53)));
+
+    // icmpv6 is decoded when reached through an ipv6 header
+    let mut packet = [0u8; 128];
+    let length = Ipv6Builder::new().next_header(IpProto::ICMP6).build(&mut packet, &buffer[..length])?;
+    match Ipv6Pdu::new(&packet[..length])?.inner()? {
+        Ipv6::Icmp(icmp_pdu) => assert!(icmp_pdu.is_icmpv6()),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // trailing link-layer bytes after the packet are not echo data
+    let length = IcmpBuilder::new().message_type(Icmpv6Type::ECHO_REQUEST).build(&mut buffer, &ip, &[0, 1, 0, 1])?;
+    let mut packet = [0xffu8; 64];
+    Ipv6Builder::new()
+        .next_header(IpProto::ICMP6)
+        .source_address([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0x02, 0x00, 0x5e, 0xff, 0xfe, 0x00, 0x53, 0x01])
+        .destination_address([0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01])
+        .build(&mut packet, &buffer[..length])?;
+    let ipv6_pdu = Ipv6Pdu::new(&packet)?;
+    match ipv6_pdu.inner()? {
+        Ipv6::Icmp(icmp_pdu) => match icmp_pdu.inner()? {
+            Icmp::Icmpv6(Icmpv6::EchoRequest { data, .. }) => assert!(data.is_empty()),
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Icmp, ChecksumStatus::Good)));
+
+    Ok(())
+}
+
+#[test]
+fn test_mld() -> Result<(), Box<dyn Error>> {
+    let mut ip = [0u8; 40];
+    Ipv6Builder::new().next_header(IpProto::ICMP6).build(&mut ip, &[])?;
+    let ip = Ip::new(&ip)?;
+    let mut buffer = [0u8; 256];
+    let group = [0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xfb];
+    let source = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01];
+
+    let query = IcmpBuilder::new().message_type(Icmpv6Type::MULTICAST_LISTENER_QUERY);
+    let report = IcmpBuilder::new().message_type(Icmpv6Type::MULTICAST_LISTENER_REPORT);
+    let done = IcmpBuilder::new().message_type(Icmpv6Type::MULTICAST_LISTENER_DONE);
+    let report_v2 = IcmpBuilder::new().message_type(Icmpv6Type::MULTICAST_LISTENER_REPORT_V2);
+
+    let mut message = vec![0x27, 0x10, 0x00, 0x00];
+    message.extend_from_slice(&group);
+    let length = query.build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::MulticastListenerQuery { maximum_response_delay, multicast_address }) => {
+            assert_eq!(maximum_response_delay, 10000);
+            assert_eq!(multicast_address, group);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let length = report.build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::MulticastListenerReport { multicast_address }) => assert_eq!(multicast_address, group),
+        other => panic!("unexpected {:?}", other),
+    }
+    let length = done.build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::MulticastListenerDone { multicast_address }) => assert_eq!(multicast_address, group),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // mldv2 queries are longer than mldv1 queries
+    message.extend_from_slice(&[0x0a, 0x7d, 0x00, 0x02]);
+    message.extend_from_slice(&source);
+    message.extend_from_slice(&source);
+    let length = query.build(&mut buffer, &ip, &message)?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Icmpv6(Icmpv6::MulticastListenerQueryV2 {
+            maximum_response_code,
+            multicast_address,
+            suppress_router_processing,
+            robustness,
+            query_interval_code,
+            sources,
+        }) => {
+            assert_eq!(maximum_response_code, 10000);
+            assert_eq!(multicast_address, group);
+            assert!(suppress_router_processing);
+            assert_eq!(robustness, 2);
+            assert_eq!(query_interval_code, 125);
+            assert_eq!(sources.collect::<Vec<_>>(), vec![source, source]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    // a source list that extends beyond the end of the message is not decoded
+    let length = query.build(&mut buffer, &ip, &message[..40])?;
+    match IcmpPdu::new_icmpv6(&buffer[..length])?.inner()? {
+        Icmp::Raw(_) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let mut message = vec![0x00, 0x00, 0x00, 0x03];
+    message.extend_from_slice(&[0x01, 0x01, 0x00, 0x01]);
+    message.extend_from_slice(&group);
+    message.extend_from_slice(&source);
+    message.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    message.extend_from_slice(&[0x04, 0x00, 0x00, 0x00]);
+    message.extend_from_slice(&group);
+    let length = report_v2.build(&mut buffer, &ip, &message)?;
+    let mut records = match IcmpPdu::new_icmpv6(&buffer[..length])?.into_inner()? {
+        Icmp::Icmpv6(Icmpv6::MulticastListenerReportV2 { records }) => records,
+        other => panic!("unexpected {:?}", other),
+    };
+    let record = records.next().expect("missing record");
+    assert_eq!(record.record_type, 1);
+    assert_eq!(record.multicast_address, group);
+    assert_eq!(record.sources.collect::<Vec<_>>(), vec![source]);
+    assert_eq!(record.auxiliary_data, [0xde, 0xad, 0xbe, 0xef]);
+    let record = records.next().expect("missing record");
+    assert_eq!(record.record_type, 4);
+    assert_eq!(record.sources.count(), 0);
+    // the report claims a third record that is not present
+    assert!(records.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_igmp() -> Result<(), Box<dyn Error>> {
+    let group = [239, 1, 2, 3];
+    let mut buffer = [0u8; 128];
+    let router_alert = [Ipv4Option::RouterAlert { value: 0 }];
+    let igmp = |message: &[u8]| {
+        let mut message = message.to_vec();
+        let checksum = util::checksum([&message[..]]);
+        message[2..=3].copy_from_slice(&checksum.to_be_bytes());
+        message
+    };
+
+    // igmp messages are sent with the router alert option
+    let report = igmp(&[IgmpType::MEMBERSHIP_REPORT_V2, 0x00, 0x00, 0x00, 239, 1, 2, 3]);
+    let length = Ipv4Builder::new()
+        .protocol(IpProto::IGMP)
+        .ttl(1)
+        .options(&router_alert)
+        .destination_address(group)
+        .build(&mut buffer, &report)?;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    match ipv4_pdu.options().next() {
+        Some(Ipv4Option::RouterAlert { value: 0 }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    let igmp_pdu = match ipv4_pdu.inner()? {
+        Ipv4::Igmp(igmp_pdu) => igmp_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(igmp_pdu.verify_checksum(), ChecksumStatus::Good);
+    match igmp_pdu.inner()? {
+        Igmp::MembershipReportV2 { group_address } => assert_eq!(group_address, group),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Igmp, ChecksumStatus::Good)));
+    buffer[length - 1] ^= 0xff;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Igmp, ChecksumStatus::Bad)));
+
+    // ethernet padding after a short query is not mistaken for the rest of an igmpv3 query
+    let query = igmp(&[IgmpType::MEMBERSHIP_QUERY, 0x64, 0x00, 0x00, 0, 0, 0, 0]);
+    let length = Ipv4Builder::new()
+        .protocol(IpProto::IGMP)
+        .ttl(1)
+        .options(&router_alert)
+        .destination_address([224, 0, 0, 1])
+        .build(&mut buffer, &query)?;
+    let mut frame = [0u8; 60];
+    EthernetBuilder::new().ethertype(EtherType::IPV4).build(&mut frame, &buffer[..length])?;
+    let ipv4_pdu = match EthernetPdu::new(&frame)?.into_inner()? {
+        Ethernet::Ipv4(ipv4_pdu) => ipv4_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    match ipv4_pdu.inner()? {
+        Ipv4::Igmp(igmp_pdu) => match igmp_pdu.inner()? {
+            Igmp::MembershipQuery { max_response_time: 100, .. } => {}
+            other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().all(|(_, status)| status == ChecksumStatus::Good));
+
+    match IgmpPdu::new(&igmp(&[IgmpType::MEMBERSHIP_QUERY, 0x00, 0x00, 0x00, 0, 0, 0, 0]))?.inner()? {
+        Igmp::MembershipQuery { max_response_time, group_address } => {
+            assert_eq!(max_response_time, 0);
+            assert_eq!(group_address, [0, 0, 0, 0]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match IgmpPdu::new(&igmp(&[IgmpType::MEMBERSHIP_QUERY, 0x64, 0x00, 0x00, 239, 1, 2, 3]))?.inner()? {
+        Igmp::MembershipQuery { max_response_time, group_address } => {
+            assert_eq!(max_response_time, 100);
+            assert_eq!(group_address, group);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match IgmpPdu::new(&igmp(&[IgmpType::MEMBERSHIP_REPORT_V1, 0x00, 0x00, 0x00, 239, 1, 2, 3]))?.inner()? {
+        Igmp::MembershipReportV1 { group_address } => assert_eq!(group_address, group),
+        other => panic!("unexpected {:?}", other),
+    }
+    match IgmpPdu::new(&igmp(&[IgmpType::LEAVE_GROUP, 0x00, 0x00, 0x00, 239, 1, 2, 3]))?.inner()? {
+        Igmp::LeaveGroup { group_address } => assert_eq!(group_address, group),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // igmpv3 queries are longer than older queries
+    #[rustfmt::skip]
+    let query = igmp(&[
+        IgmpType::MEMBERSHIP_QUERY, 0x64, 0x00, 0x00, 239, 1, 2, 3, 0x0a, 0x7d, 0x00, 0x02,
+        192, 0, 2, 1, 192, 0, 2, 2,
+    ]);
+    let igmp_pdu = IgmpPdu::new(&query)?;
+    assert_eq!(igmp_pdu.verify_checksum(), ChecksumStatus::Good);
+    match igmp_pdu.inner()? {
+        Igmp::MembershipQueryV3 {
+            max_response_code,
+            group_address,
+            suppress_router_processing,
+            robustness,
+            query_interval_code,
+            sources,
+        } => {
+            assert_eq!(max_response_code, 100);
+            assert_eq!(group_address, group);
+            assert!(suppress_router_processing);
+            assert_eq!(robustness, 2);
+            assert_eq!(query_interval_code, 125);
+            assert_eq!(sources.collect::<Vec<_>>(), vec![[192, 0, 2, 1], [192, 0, 2, 2]]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match IgmpPdu::new(&query[..16])?.inner()? {
+        Igmp::Raw(raw) => assert_eq!(raw, &query[8..16]),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    #[rustfmt::skip]
+    let report = igmp(&[
+        IgmpType::MEMBERSHIP_REPORT_V3, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+        0x01, 0x01, 0x00, 0x01, 239, 1, 2, 3, 192, 0, 2, 1, 0xde, 0xad, 0xbe, 0xef,
+        0x04, 0x00, 0x00, 0x00, 239, 1, 2, 4,
+    ]);
+    let mut records = match IgmpPdu::new(&report)?.into_inner()? {
+        Igmp::MembershipReportV3 { records } => records,
+        other => panic!("unexpected {:?}", other),
+    };
+    let record = records.next().expect("missing record");
+    assert_eq!(record.record_type, 1);
+    assert_eq!(record.multicast_address, group);
+    assert_eq!(record.sources.collect::<Vec<_>>(), vec![[192, 0, 2, 1]]);
+    assert_eq!(record.auxiliary_data, [0xde, 0xad, 0xbe, 0xef]);
+    let record = records.next().expect("missing record");
+    assert_eq!(record.record_type, 4);
+    assert_eq!(record.multicast_address, [239, 1, 2, 4]);
+    assert_eq!(record.sources.count(), 0);
+    // the report claims a third record that is not present
+    assert!(records.next().is_none());
+
+    assert_eq!(IgmpPdu::new(&report[..7]).unwrap_err(), pdu::Error::Truncated);
+
+    Ok(())
+}
+
+#[test]
+fn test_sctp() -> Result<(), Box<dyn Error>> {
+    // crc32c test vector (rfc3720 appendix B.4)
+    assert_eq!(util::crc32c([&[0u8; 32][..]]), 0x8a91_36aa);
+
+    #[rustfmt::skip]
+    let mut packet = vec![
+        // common header
+        0x13, 0x88, 0x0b, 0xb8, 0xde, 0xad, 0xbe, 0xef, 0x00, 0x00, 0x00, 0x00,
+        // INIT with an IPv4 address and a supported address types parameter
+        0x01, 0x00, 0x00, 0x22, 0x11, 0x22, 0x33, 0x44, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0xff, 0xff,
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x05, 0x00, 0x08, 192, 0, 2, 1, 0x00, 0x0c, 0x00, 0x06,
+        0x00, 0x05, 0x00, 0x00,
+        // DATA (unfragmented) with a 3 byte payload and padding
+        0x00, 0x03, 0x00, 0x13, 0x00, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x00, 0x2f,
+        b'a', b'b', b'c', 0x00,
+        // SACK with one gap ack block and one duplicate TSN
+        0x03, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x05, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01,
+        0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x00, 0x04,
+        // SHUTDOWN COMPLETE with the T bit set
+        0x0e, 0x01, 0x00, 0x04,
+        // unknown chunk type, truncated
+        0x40, 0x00, 0x00, 0x10,
+    ];
+    let checksum = util::crc32c([&packet[..]]);
+    packet[8..12].copy_from_slice(&checksum.to_le_bytes());
+
+    let mut buffer = [0u8; 256];
+    let length = Ipv4Builder::new().protocol(IpProto::SCTP).build(&mut buffer, &packet)?;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    let sctp_pdu = match ipv4_pdu.inner()? {
+        Ipv4::Sctp(sctp_pdu) => sctp_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(sctp_pdu.source_port(), 5000);
+    assert_eq!(sctp_pdu.destination_port(), 3000);
+    assert_eq!(sctp_pdu.verification_tag(), 0xdead_beef);
+    assert_eq!(sctp_pdu.checksum(), sctp_pdu.computed_checksum());
+    assert_eq!(sctp_pdu.verify_checksum(), ChecksumStatus::Good);
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Sctp, ChecksumStatus::Good)));
+
+    let mut chunks = sctp_pdu.chunks();
+    match chunks.next() {
+        Some(SctpChunk::Init {
+            initiate_tag,
+            advertised_receiver_window_credit,
+            outbound_streams,
+            inbound_streams,
+            initial_tsn,
+            mut parameters,
+        }) => {
+            assert_eq!(initiate_tag, 0x1122_3344);
+            assert_eq!(advertised_receiver_window_credit, 0x0001_0000);
+            assert_eq!(outbound_streams, 10);
+            assert_eq!(inbound_streams, 0xffff);
+            assert_eq!(initial_tsn, 1);
+            match parameters.next() {
+                Some(SctpParameter::Ipv4Address { address }) => assert_eq!(address, [192, 0, 2, 1]),
+                other => panic!("unexpected {:?}", other),
+            }
+            match parameters.next() {
+                Some(SctpParameter::SupportedAddressTypes { types }) => assert_eq!(types, &[0x00, 0x05]),
+                other => panic!("unexpected {:?}", other),
+            }
+            assert!(parameters.next().is_none());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match chunks.next() {
+        Some(SctpChunk::Data {
+            unordered,
+            beginning,
+            ending,
+            tsn,
+            stream_identifier,
+            stream_sequence_number,
+            payload_protocol_identifier,
+            data,
+            ..
+        }) => {
+            assert!(!unordered && beginning && ending);
+            assert_eq!(tsn, 1);
+            assert_eq!(stream_identifier, 2);
+            assert_eq!(stream_sequence_number, 3);
+            assert_eq!(payload_protocol_identifier, 47);
+            assert_eq!(data, b"abc");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match chunks.next() {
+        Some(SctpChunk::Sack { cumulative_tsn_ack, gap_ack_blocks, duplicate_tsns, .. }) => {
+            assert_eq!(cumulative_tsn_ack, 5);
+            assert_eq!(gap_ack_blocks.collect::<Vec<_>>(), vec![(2, 3)]);
+            assert_eq!(duplicate_tsns.collect::<Vec<_>>(), vec![4]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match chunks.next() {
+        Some(SctpChunk::ShutdownComplete { reflected }) => assert!(reflected),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(chunks.next().is_none());
+
+    buffer[length - 1] ^= 0xff;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Sctp, ChecksumStatus::Bad)));
+
+    assert_eq!(SctpPdu::new(&packet[..11]).unwrap_err(), pdu::Error::Truncated);
+
+    // ethernet padding after a minimum-size packet is not covered by the checksum
+    let mut cookie_ack = vec![0x13, 0x88, 0x0b, 0xb8, 0xde, 0xad, 0xbe, 0xef, 0, 0, 0, 0, 0x0b, 0x00, 0x00, 0x04];
+    let checksum = util::crc32c([&cookie_ack[..]]);
+    cookie_ack[8..12].copy_from_slice(&checksum.to_le_bytes());
+    let length = Ipv4Builder::new().protocol(IpProto::SCTP).build(&mut buffer, &cookie_ack)?;
+    let mut frame = [0u8; 60];
+    EthernetBuilder::new().ethertype(EtherType::IPV4).build(&mut frame, &buffer[..length])?;
+    let ipv4_pdu = match EthernetPdu::new(&frame)?.into_inner()? {
+        Ethernet::Ipv4(ipv4_pdu) => ipv4_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    match ipv4_pdu.inner()? {
+        Ipv4::Sctp(sctp_pdu) => {
+            assert_eq!(sctp_pdu.buffer().len(), 16);
+            assert!(matches!(sctp_pdu.chunks().next(), Some(SctpChunk::CookieAck)));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::Sctp, ChecksumStatus::Good)));
+
+    let length = Ipv6Builder::new().next_header(IpProto::SCTP).build(&mut buffer, &cookie_ack)?;
+    let mut padded = buffer[..length].to_vec();
+    padded.extend_from_slice(&[0u8; 8]);
+    let ipv6_pdu = Ipv6Pdu::new(&padded)?;
+    assert!(Ip::Ipv6(ipv6_pdu).verify_checksums().any(|item| item == (Layer::Sctp, ChecksumStatus::Good)));
+
+    Ok(())
+}
+
+#[test]
+fn test_udplite() -> Result<(), Box<dyn Error>> {
+    let source = [192, 0, 2, 1];
+    let destination = [192, 0, 2, 2];
+    // a checksum coverage of 12 bytes covers the header and the first 4 bytes of the payload
+    #[rustfmt::skip]
+    let mut packet = [
+        0x13, 0x88, 0x13, 0x89, 0x00, 0x0c, 0x00, 0x00,
+        b'c', b'o', b'v', b'd', b'u', b'n', b'c', b'o', b'v', b'e', b'r', b'e', b'd',
+    ];
+    let checksum = util::checksum([
+        &source[..],
+        &destination[..],
+        &[0x00, IpProto::UDP_LITE, 0x00, packet.len() as u8],
+        &packet[..12],
+    ]);
+    packet[6..=7].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut buffer = [0u8; 64];
+    let length = Ipv4Builder::new()
+        .protocol(IpProto::UDP_LITE)
+        .source_address(source)
+        .destination_address(destination)
+        .build(&mut buffer, &packet)?;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    let udplite_pdu = match ipv4_pdu.inner()? {
+        Ipv4::UdpLite(udplite_pdu) => udplite_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(udplite_pdu.source_port(), 5000);
+    assert_eq!(udplite_pdu.destination_port(), 5001);
+    assert_eq!(udplite_pdu.checksum_coverage(), 12);
+    assert_eq!(udplite_pdu.computed_checksum_coverage(), 12);
+    assert_eq!(udplite_pdu.checksum(), checksum);
+    assert_eq!(udplite_pdu.computed_checksum(&Ip::Ipv4(ipv4_pdu)), checksum);
+    match udplite_pdu.inner()? {
+        UdpLite::Raw(raw) => assert_eq!(raw, &packet[8..]),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::UdpLite, ChecksumStatus::Good)));
+
+    // damage outside of the checksum coverage goes undetected, damage within it does not
+    buffer[length - 1] ^= 0xff;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::UdpLite, ChecksumStatus::Good)));
+    buffer[20 + 8] ^= 0xff;
+    let ipv4_pdu = Ipv4Pdu::new(&buffer[..length])?;
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::UdpLite, ChecksumStatus::Bad)));
+
+    // a checksum coverage of zero covers the entire packet
+    packet[4..=7].copy_from_slice(&[0, 0, 0, 0]);
+    let checksum = util::checksum([
+        &source[..],
+        &destination[..],
+        &[0x00, IpProto::UDP_LITE, 0x00, packet.len() as u8],
+        &packet[..],
+    ]);
+    packet[6..=7].copy_from_slice(&checksum.to_be_bytes());
+    let ip = Ip::Ipv4(Ipv4Pdu::new(&buffer[..length])?);
+    let udplite_pdu = UdpLitePdu::new(&packet)?;
+    assert_eq!(udplite_pdu.computed_checksum_coverage(), packet.len());
+    assert_eq!(udplite_pdu.verify_checksum(&ip), ChecksumStatus::Good);
+
+    // ethernet padding after the packet is not covered
+    let mut datagram = [0u8; 64];
+    let length = Ipv4Builder::new()
+        .protocol(IpProto::UDP_LITE)
+        .source_address(source)
+        .destination_address(destination)
+        .build(&mut datagram, &packet)?;
+    let mut frame = [0u8; 64];
+    EthernetBuilder::new().ethertype(EtherType::IPV4).build(&mut frame, &datagram[..length])?;
+    let ipv4_pdu = match EthernetPdu::new(&frame)?.into_inner()? {
+        Ethernet::Ipv4(ipv4_pdu) => ipv4_pdu,
+        other => panic!("unexpected {:?}", other),
+    };
+    match ipv4_pdu.inner()? {
+        Ipv4::UdpLite(udplite_pdu) => assert_eq!(udplite_pdu.computed_checksum_coverage(), packet.len()),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Ip::Ipv4(ipv4_pdu).verify_checksums().any(|item| item == (Layer::UdpLite, ChecksumStatus::Good)));
+
+    // a checksum coverage shorter than the header or longer than the packet is invalid, as is a zero checksum
+    packet[4..=5].copy_from_slice(&[0x00, 0x04]);
+    assert_eq!(UdpLitePdu::new(&packet)?.verify_checksum(&ip), ChecksumStatus::Bad);
+    packet[4..=5].copy_from_slice(&[0x00, 0x40]);
+    assert_eq!(UdpLitePdu::new(&packet)?.verify_checksum(&ip), ChecksumStatus::Bad);
+    packet[4..=7].copy_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(UdpLitePdu::new(&packet)?.verify_checksum(&ip), ChecksumStatus::Bad);
+
+    assert_eq!(UdpLitePdu::new(&packet[..7]).unwrap_err(), pdu::Error::Truncated);
+
+    Ok(())
+}